@@ -0,0 +1,118 @@
+// src/ipc/deadlock.rs
+// Wait-for graph construction and cycle detection
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::lock::LockTable;
+
+/// Edges from each waiting PID to the PIDs it is waiting on
+pub type WaitForGraph = BTreeMap<u32, BTreeSet<u32>>;
+
+/// Build the wait-for graph from lock ownership: every waiter on a lock
+/// points at that lock's holder. Semaphores have no owner, so they can't
+/// contribute edges.
+pub fn wait_for_graph(locks: &LockTable) -> WaitForGraph {
+    let mut graph = WaitForGraph::new();
+    for (_, lock) in locks.all_locks() {
+        if let Some(holder) = lock.holder {
+            for &waiter in &lock.waiters {
+                graph.entry(waiter).or_default().insert(holder);
+            }
+        }
+    }
+    graph
+}
+
+/// Every distinct cycle in the graph, each listed in wait order starting
+/// from its lowest PID
+pub fn find_cycles(graph: &WaitForGraph) -> Vec<Vec<u32>> {
+    let mut finished = BTreeSet::new();
+    let mut cycles = Vec::new();
+
+    for &start in graph.keys() {
+        if !finished.contains(&start) {
+            let mut path = Vec::new();
+            visit(start, graph, &mut path, &mut finished, &mut cycles);
+        }
+    }
+
+    cycles
+}
+
+/// Depth-first search; a node already on the current path closes a cycle
+fn visit(
+    pid: u32,
+    graph: &WaitForGraph,
+    path: &mut Vec<u32>,
+    finished: &mut BTreeSet<u32>,
+    cycles: &mut Vec<Vec<u32>>,
+) {
+    if let Some(pos) = path.iter().position(|&p| p == pid) {
+        let mut cycle = path[pos..].to_vec();
+        let min_pos = cycle.iter().enumerate().min_by_key(|(_, &p)| p).map_or(0, |(i, _)| i);
+        cycle.rotate_left(min_pos);
+        if !cycles.contains(&cycle) {
+            cycles.push(cycle);
+        }
+        return;
+    }
+    if finished.contains(&pid) {
+        return;
+    }
+
+    path.push(pid);
+    if let Some(targets) = graph.get(&pid) {
+        for &next in targets {
+            visit(next, graph, path, finished, cycles);
+        }
+    }
+    path.pop();
+    finished.insert(pid);
+}
+
+/// PIDs that are part of at least one cycle
+pub fn deadlocked_set(graph: &WaitForGraph) -> BTreeSet<u32> {
+    find_cycles(graph).into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_process_circular_wait() {
+        let mut locks = LockTable::new();
+        locks.acquire("a", 2);
+        locks.acquire("b", 3);
+        locks.acquire("b", 2);
+        locks.acquire("a", 3);
+
+        let graph = wait_for_graph(&locks);
+        assert_eq!(find_cycles(&graph), vec![vec![2, 3]]);
+        assert_eq!(deadlocked_set(&graph), BTreeSet::from([2, 3]));
+    }
+
+    #[test]
+    fn test_chain_without_cycle() {
+        let mut locks = LockTable::new();
+        locks.acquire("a", 2);
+        locks.acquire("b", 3);
+        locks.acquire("a", 3);
+        locks.acquire("b", 4);
+
+        let graph = wait_for_graph(&locks);
+        assert!(find_cycles(&graph).is_empty());
+    }
+
+    #[test]
+    fn test_waiter_outside_cycle_not_reported() {
+        let mut graph = WaitForGraph::new();
+        graph.entry(5).or_default().insert(2);
+        graph.entry(2).or_default().insert(3);
+        graph.entry(3).or_default().insert(4);
+        graph.entry(4).or_default().insert(2);
+
+        assert_eq!(find_cycles(&graph), vec![vec![2, 3, 4]]);
+        assert_eq!(deadlocked_set(&graph), BTreeSet::from([2, 3, 4]));
+    }
+}
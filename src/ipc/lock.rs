@@ -0,0 +1,161 @@
+// src/ipc/lock.rs
+// Named mutual-exclusion locks with FIFO wait queues
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// A single named lock
+#[derive(Debug, Clone, Default)]
+pub struct Lock {
+    pub holder: Option<u32>,
+    pub waiters: VecDeque<u32>,
+}
+
+/// Result of trying to take a lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockOutcome {
+    Acquired,
+    AlreadyHeld,
+    Waiting { holder: u32 },
+}
+
+/// Every lock in the system, keyed by resource name
+#[derive(Debug, Clone, Default)]
+pub struct LockTable {
+    locks: BTreeMap<String, Lock>,
+}
+
+impl LockTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take `resource` for `pid`, or join its wait queue if someone else holds it
+    pub fn acquire(&mut self, resource: &str, pid: u32) -> LockOutcome {
+        let lock = self.locks.entry(resource.to_string()).or_default();
+        match lock.holder {
+            None => {
+                lock.holder = Some(pid);
+                LockOutcome::Acquired
+            }
+            Some(holder) if holder == pid => LockOutcome::AlreadyHeld,
+            Some(holder) => {
+                if !lock.waiters.contains(&pid) {
+                    lock.waiters.push_back(pid);
+                }
+                LockOutcome::Waiting { holder }
+            }
+        }
+    }
+
+    /// Release `resource` held by `pid`, handing it to the next waiter.
+    /// Returns the new holder, if any.
+    pub fn release(&mut self, resource: &str, pid: u32) -> Result<Option<u32>, String> {
+        let lock = self
+            .locks
+            .get_mut(resource)
+            .ok_or_else(|| format!("Lock '{}' does not exist", resource))?;
+
+        if lock.holder != Some(pid) {
+            return Err(format!("Process {} does not hold '{}'", pid, resource));
+        }
+
+        lock.holder = lock.waiters.pop_front();
+        Ok(lock.holder)
+    }
+
+    /// Drop every claim `pid` has: held locks are handed on and queued waits
+    /// are withdrawn. Returns `(resource, new_holder)` for each released lock.
+    pub fn release_all(&mut self, pid: u32) -> Vec<(String, Option<u32>)> {
+        let mut released = Vec::new();
+        for (name, lock) in self.locks.iter_mut() {
+            lock.waiters.retain(|&p| p != pid);
+            if lock.holder == Some(pid) {
+                lock.holder = lock.waiters.pop_front();
+                released.push((name.clone(), lock.holder));
+            }
+        }
+        released
+    }
+
+    pub fn holder(&self, resource: &str) -> Option<u32> {
+        self.locks.get(resource).and_then(|lock| lock.holder)
+    }
+
+    pub fn waiters(&self, resource: &str) -> Vec<u32> {
+        self.locks
+            .get(resource)
+            .map_or_else(Vec::new, |lock| lock.waiters.iter().copied().collect())
+    }
+
+    /// Resources currently held by `pid`
+    pub fn held_by(&self, pid: u32) -> Vec<String> {
+        self.locks
+            .iter()
+            .filter(|(_, lock)| lock.holder == Some(pid))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Resource `pid` is queued on, if any
+    pub fn waiting_on(&self, pid: u32) -> Option<String> {
+        self.locks
+            .iter()
+            .find(|(_, lock)| lock.waiters.contains(&pid))
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Processes queued on any lock that `pid` holds
+    pub fn blocked_by(&self, pid: u32) -> Vec<u32> {
+        self.locks
+            .values()
+            .filter(|lock| lock.holder == Some(pid))
+            .flat_map(|lock| lock.waiters.iter().copied())
+            .collect()
+    }
+
+    pub fn all_locks(&self) -> impl Iterator<Item = (&String, &Lock)> {
+        self.locks.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_and_wait() {
+        let mut table = LockTable::new();
+        assert_eq!(table.acquire("disk", 2), LockOutcome::Acquired);
+        assert_eq!(table.acquire("disk", 2), LockOutcome::AlreadyHeld);
+        assert_eq!(table.acquire("disk", 3), LockOutcome::Waiting { holder: 2 });
+
+        assert_eq!(table.held_by(2), vec!["disk".to_string()]);
+        assert_eq!(table.waiting_on(3), Some("disk".to_string()));
+        assert_eq!(table.blocked_by(2), vec![3]);
+    }
+
+    #[test]
+    fn test_release_hands_off_in_fifo_order() {
+        let mut table = LockTable::new();
+        table.acquire("disk", 2);
+        table.acquire("disk", 3);
+        table.acquire("disk", 4);
+
+        assert!(table.release("disk", 3).is_err());
+        assert_eq!(table.release("disk", 2), Ok(Some(3)));
+        assert_eq!(table.holder("disk"), Some(3));
+        assert_eq!(table.waiters("disk"), vec![4]);
+    }
+
+    #[test]
+    fn test_release_all() {
+        let mut table = LockTable::new();
+        table.acquire("disk", 2);
+        table.acquire("net", 3);
+        table.acquire("net", 2);
+        table.acquire("disk", 3);
+
+        assert_eq!(table.release_all(2), vec![("disk".to_string(), Some(3))]);
+        assert!(table.waiters("net").is_empty());
+    }
+}
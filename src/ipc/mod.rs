@@ -0,0 +1,9 @@
+// src/ipc/mod.rs - Inter-process synchronization primitives
+
+pub mod deadlock;
+pub mod lock;
+pub mod semaphore;
+
+pub use deadlock::{deadlocked_set, find_cycles, wait_for_graph, WaitForGraph};
+pub use lock::{Lock, LockOutcome, LockTable};
+pub use semaphore::{Semaphore, SemaphoreTable};
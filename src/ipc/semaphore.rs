@@ -0,0 +1,134 @@
+// src/ipc/semaphore.rs
+// Counting semaphores with FIFO wait queues (Dijkstra's P/V)
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// A counting semaphore
+#[derive(Debug, Clone, Default)]
+pub struct Semaphore {
+    pub count: u32,
+    pub waiters: VecDeque<u32>,
+}
+
+impl Semaphore {
+    pub fn new(count: u32) -> Self {
+        Semaphore { count, waiters: VecDeque::new() }
+    }
+
+    /// P operation: take a unit, or queue `pid` if none are left.
+    /// Returns true if the process may proceed.
+    pub fn wait(&mut self, pid: u32) -> bool {
+        if self.count > 0 {
+            self.count -= 1;
+            true
+        } else {
+            if !self.waiters.contains(&pid) {
+                self.waiters.push_back(pid);
+            }
+            false
+        }
+    }
+
+    /// V operation: hand the unit straight to the oldest waiter, or return
+    /// it to the pool. Returns the PID that was woken, if any.
+    pub fn signal(&mut self) -> Option<u32> {
+        match self.waiters.pop_front() {
+            Some(pid) => Some(pid),
+            None => {
+                self.count += 1;
+                None
+            }
+        }
+    }
+}
+
+/// Every semaphore in the system, keyed by name
+#[derive(Debug, Clone, Default)]
+pub struct SemaphoreTable {
+    semaphores: BTreeMap<String, Semaphore>,
+}
+
+impl SemaphoreTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(&mut self, name: &str, count: u32) -> Result<(), String> {
+        if self.semaphores.contains_key(name) {
+            return Err(format!("Semaphore '{}' already exists", name));
+        }
+        self.semaphores.insert(name.to_string(), Semaphore::new(count));
+        Ok(())
+    }
+
+    pub fn wait(&mut self, name: &str, pid: u32) -> Result<bool, String> {
+        self.get_mut(name).map(|sem| sem.wait(pid))
+    }
+
+    pub fn signal(&mut self, name: &str) -> Result<Option<u32>, String> {
+        self.get_mut(name).map(|sem| sem.signal())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Semaphore> {
+        self.semaphores.get(name)
+    }
+
+    /// Semaphore `pid` is queued on, if any
+    pub fn waiting_on(&self, pid: u32) -> Option<String> {
+        self.semaphores
+            .iter()
+            .find(|(_, sem)| sem.waiters.contains(&pid))
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Withdraw `pid` from every wait queue
+    pub fn remove_waiter(&mut self, pid: u32) {
+        for sem in self.semaphores.values_mut() {
+            sem.waiters.retain(|&p| p != pid);
+        }
+    }
+
+    pub fn all_semaphores(&self) -> impl Iterator<Item = (&String, &Semaphore)> {
+        self.semaphores.iter()
+    }
+
+    fn get_mut(&mut self, name: &str) -> Result<&mut Semaphore, String> {
+        self.semaphores
+            .get_mut(name)
+            .ok_or_else(|| format!("Semaphore '{}' does not exist", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_semaphore() {
+        let mut sem = Semaphore::new(2);
+        assert!(sem.wait(2));
+        assert!(sem.wait(3));
+        assert!(!sem.wait(4));
+        assert_eq!(sem.count, 0);
+
+        assert_eq!(sem.signal(), Some(4));
+        assert_eq!(sem.count, 0);
+        assert_eq!(sem.signal(), None);
+        assert_eq!(sem.count, 1);
+    }
+
+    #[test]
+    fn test_table_errors() {
+        let mut table = SemaphoreTable::new();
+        table.create("mutex", 1).unwrap();
+        assert!(table.create("mutex", 1).is_err());
+        assert!(table.wait("missing", 2).is_err());
+        assert!(table.signal("missing").is_err());
+
+        assert_eq!(table.wait("mutex", 2), Ok(true));
+        assert_eq!(table.wait("mutex", 3), Ok(false));
+        assert_eq!(table.waiting_on(3), Some("mutex".to_string()));
+        table.remove_waiter(3);
+        assert_eq!(table.waiting_on(3), None);
+    }
+}
@@ -1,6 +1,6 @@
 // src/main.rs
 
-use os_simulator::shell::{Shell, parse_command};
+use os_simulator::shell::{Shell, ShellConfig};
 use std::io::{self, Write};
 
 fn main() {
@@ -12,12 +12,32 @@ fn main() {
     println!("Welcome to the OS Simulator!");
     println!("Type 'help' for available commands or 'exit' to quit.\n");
 
-    let mut shell = Shell::new();
+    // `--config file.toml` reproduces a saved environment
+    let args: Vec<String> = std::env::args().collect();
+    let mut shell = match args.iter().position(|arg| arg == "--config") {
+        Some(i) => {
+            let built = match args.get(i + 1) {
+                Some(path) => ShellConfig::load(path).and_then(|config| Shell::from_config(&config)),
+                None => Err("--config needs a file path".to_string()),
+            };
+            match built {
+                Ok(shell) => shell,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => Shell::new(),
+    };
 
     // Main REPL loop
     loop {
-        // Print prompt
-        print!("os> ");
+        // Print prompt, marking how many subshells deep we are
+        match shell.subshell_depth() {
+            0 => print!("os> "),
+            depth => print!("os({})> ", depth),
+        }
         io::stdout().flush().unwrap();
 
         // Read input
@@ -31,20 +51,13 @@ fn main() {
                     continue;
                 }
 
-                // Parse and execute command
-                match parse_command(trimmed) {
-                    Some(cmd) => {
-                        let output = shell.execute(cmd);
-                        println!("{}", output);
-
-                        // Check if we should exit
-                        if !shell.is_running() {
-                            break;
-                        }
-                    }
-                    None => {
-                        println!("Error: Unknown command '{}'. Type 'help' for available commands.", trimmed);
-                    }
+                // Parse and execute command (with any piped filters)
+                let output = shell.execute_line(trimmed);
+                println!("{}", output);
+
+                // Check if we should exit
+                if !shell.is_running() {
+                    break;
                 }
             }
             Err(e) => {
@@ -0,0 +1,134 @@
+// src/process/cgroup.rs
+// Control groups: named sets of processes sharing one CPU-time budget
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A named group whose members draw from a shared CPU budget
+#[derive(Debug, Clone, Default)]
+pub struct Cgroup {
+    pub cpu_limit: u64,             // Total CPU the group may use (ms)
+    pub cpu_used: u64,              // CPU charged to the group so far (ms)
+    pub members: BTreeSet<u32>,
+}
+
+impl Cgroup {
+    pub fn new(cpu_limit: u64) -> Self {
+        Cgroup { cpu_limit, ..Default::default() }
+    }
+
+    /// CPU left before the group is throttled
+    pub fn remaining(&self) -> u64 {
+        self.cpu_limit.saturating_sub(self.cpu_used)
+    }
+
+    /// True once the group has used its whole budget
+    pub fn is_throttled(&self) -> bool {
+        self.cpu_used >= self.cpu_limit
+    }
+}
+
+/// Every cgroup in the system, keyed by name. A process belongs to at most
+/// one group.
+#[derive(Debug, Clone, Default)]
+pub struct CgroupTable {
+    groups: BTreeMap<String, Cgroup>,
+}
+
+impl CgroupTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(&mut self, name: &str, cpu_limit: u64) -> Result<(), String> {
+        if self.groups.contains_key(name) {
+            return Err(format!("Cgroup '{}' already exists", name));
+        }
+        self.groups.insert(name.to_string(), Cgroup::new(cpu_limit));
+        Ok(())
+    }
+
+    /// Put `pid` in `name`, moving it out of any group it was in. Returns
+    /// the group it left, if any.
+    pub fn add(&mut self, name: &str, pid: u32) -> Result<Option<String>, String> {
+        if !self.groups.contains_key(name) {
+            return Err(format!("Cgroup '{}' does not exist", name));
+        }
+
+        let previous = self.group_of(pid).map(str::to_string);
+        self.remove_member(pid);
+        self.groups.get_mut(name).expect("checked above").members.insert(pid);
+        Ok(previous.filter(|prev| prev != name))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cgroup> {
+        self.groups.get(name)
+    }
+
+    /// Name of the group `pid` belongs to, if any
+    pub fn group_of(&self, pid: u32) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(_, group)| group.members.contains(&pid))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// CPU `pid` may still use under its group's cap; None if it is in no group
+    pub fn remaining_for(&self, pid: u32) -> Option<u64> {
+        self.groups
+            .values()
+            .find(|group| group.members.contains(&pid))
+            .map(Cgroup::remaining)
+    }
+
+    pub fn is_throttled(&self, pid: u32) -> bool {
+        self.remaining_for(pid) == Some(0)
+    }
+
+    /// Charge `ms` of CPU run by `pid` to its group
+    pub fn charge(&mut self, pid: u32, ms: u64) {
+        if let Some(group) = self.groups.values_mut().find(|group| group.members.contains(&pid)) {
+            group.cpu_used += ms;
+        }
+    }
+
+    /// Drop `pid` from whatever group it is in
+    pub fn remove_member(&mut self, pid: u32) {
+        for group in self.groups.values_mut() {
+            group.members.remove(&pid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_members_share_one_budget() {
+        let mut table = CgroupTable::new();
+        table.create("batch", 50).unwrap();
+        assert!(table.create("batch", 10).is_err());
+        assert_eq!(table.add("batch", 2), Ok(None));
+        assert_eq!(table.add("batch", 3), Ok(None));
+
+        table.charge(2, 30);
+        assert_eq!(table.remaining_for(3), Some(20));
+        table.charge(3, 20);
+        assert!(table.is_throttled(2) && table.is_throttled(3));
+        assert!(!table.is_throttled(4));
+        assert_eq!(table.remaining_for(4), None);
+    }
+
+    #[test]
+    fn test_add_moves_between_groups() {
+        let mut table = CgroupTable::new();
+        table.create("a", 10).unwrap();
+        table.create("b", 10).unwrap();
+        table.add("a", 2).unwrap();
+
+        assert_eq!(table.add("b", 2), Ok(Some("a".to_string())));
+        assert_eq!(table.group_of(2), Some("b"));
+        assert!(table.get("a").unwrap().members.is_empty());
+        assert!(table.add("missing", 2).is_err());
+    }
+}
@@ -1,6 +1,10 @@
 // src/process/mod.rs
 
-use std::collections::HashMap;
+pub mod cgroup;
+
+pub use cgroup::{Cgroup, CgroupTable};
+
+use std::collections::BTreeMap;
 use chrono::{DateTime, Utc};
 
 /// Process state enum representing the different states a process can be in
@@ -9,9 +13,65 @@ pub enum ProcessState {
     Ready,
     Running,
     Blocked,
+    Sleeping,
+    Stopped,
     Terminated,
 }
 
+impl ProcessState {
+    /// Stable lowercase name for display, independent of the Debug spelling
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcessState::Ready => "ready",
+            ProcessState::Running => "running",
+            ProcessState::Blocked => "blocked",
+            ProcessState::Sleeping => "sleeping",
+            ProcessState::Stopped => "stopped",
+            ProcessState::Terminated => "terminated",
+        }
+    }
+}
+
+impl std::str::FromStr for ProcessState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ready" => Ok(ProcessState::Ready),
+            "running" => Ok(ProcessState::Running),
+            "blocked" => Ok(ProcessState::Blocked),
+            "sleeping" => Ok(ProcessState::Sleeping),
+            "stopped" => Ok(ProcessState::Stopped),
+            "terminated" => Ok(ProcessState::Terminated),
+            _ => Err(format!("Unknown process state '{}'", s)),
+        }
+    }
+}
+
+/// Scheduling class, consulted before the MLFQ level when dispatching
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SchedClass {
+    /// Always dispatched ahead of normal processes
+    RealTime,
+    #[default]
+    Normal,
+    /// Only runs when nothing else is runnable
+    Idle,
+}
+
+impl std::str::FromStr for SchedClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rt" | "realtime" | "real-time" => Ok(SchedClass::RealTime),
+            "normal" => Ok(SchedClass::Normal),
+            "idle" => Ok(SchedClass::Idle),
+            _ => Err(format!("Unknown scheduling class '{}'", s)),
+        }
+    }
+}
+
 /// Simulated CPU registers
 #[derive(Debug, Clone)]
 pub struct Registers {
@@ -67,14 +127,18 @@ impl Default for MemoryContext {
 pub struct Process {
     pub pid: u32,
     pub ppid: u32, // Parent PID
+    pub name: String, // Human-readable label shown in listings
+    pub owner: String, // User the process runs as
     pub state: ProcessState,
     pub priority: u8, // 0-3, where 0 is highest priority
+    pub sched_class: SchedClass,
     pub program_counter: u64,
+    pub program_length: Option<u64>, // Estimated instruction count; None runs forever
     pub registers: Registers,
     pub memory_context: MemoryContext,
     pub time_allocated: u32, // Time allocated to this quantum (ms)
     pub time_used: u32, // Time used in current quantum (ms)
-    pub total_time: u32, // Total execution time (ms)
+    pub total_time: u64, // Total execution time (ms)
     pub creation_time: DateTime<Utc>,
     pub termination_time: Option<DateTime<Utc>>,
     pub queue_entry_time: DateTime<Utc>,
@@ -87,9 +151,13 @@ impl Process {
         Process {
             pid,
             ppid,
+            name: format!("proc-{}", pid),
+            owner: "root".to_string(),
             state: ProcessState::Ready,
             priority: 3, // Start at lowest priority
+            sched_class: SchedClass::Normal,
             program_counter: 0,
+            program_length: None,
             registers: Registers::default(),
             memory_context: MemoryContext::default(),
             time_allocated: 0,
@@ -130,7 +198,7 @@ impl Process {
 
     /// Get waiting time (turnaround time - total execution time)
     pub fn waiting_time(&self) -> u64 {
-        self.turnaround_time().saturating_sub(self.total_time as u64)
+        self.turnaround_time().saturating_sub(self.total_time)
     }
 
     /// Check if process has used its time quantum
@@ -142,30 +210,69 @@ impl Process {
     pub fn reset_quantum(&mut self) {
         self.time_used = 0;
     }
+
+    /// Advance the program counter by the amount of work executed,
+    /// never stepping past the end of the program
+    pub fn advance_program_counter(&mut self, amount: u64) {
+        self.program_counter = self.program_counter.saturating_add(amount);
+        if let Some(length) = self.program_length {
+            self.program_counter = self.program_counter.min(length);
+        }
+    }
+
+    /// Whether the process may be dispatched: only Ready or Running ones
+    pub fn is_runnable(&self) -> bool {
+        matches!(self.state, ProcessState::Ready | ProcessState::Running)
+    }
+
+    /// Check if the program counter has reached the end of the program
+    pub fn is_complete(&self) -> bool {
+        match self.program_length {
+            Some(length) => self.program_counter >= length,
+            None => false,
+        }
+    }
+}
+
+/// CPU time and process count attributed to one owner
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnerUsage {
+    pub cpu_time: u64,
+    pub processes: usize,
 }
 
 /// Process Manager for managing all processes
+#[derive(Debug, Clone)]
 pub struct ProcessManager {
-    processes: HashMap<u32, Process>,
+    processes: BTreeMap<u32, Process>, // Keyed by PID so iteration is always ascending
     next_pid: u32,
     current_process_id: Option<u32>,
 }
 
+fn not_found(pid: u32) -> String {
+    format!("Error: Process {} not found", pid)
+}
+
 impl ProcessManager {
     /// Create a new process manager
     pub fn new() -> Self {
         ProcessManager {
-            processes: HashMap::new(),
+            processes: BTreeMap::new(),
             next_pid: 1,
             current_process_id: None,
         }
     }
 
-    /// Create a new process
+    /// Create a new process, inheriting the parent's owner and named
+    /// after it with a `-child` suffix
     pub fn create_process(&mut self, ppid: u32) -> u32 {
         let pid = self.next_pid;
         self.next_pid += 1;
-        let process = Process::new(pid, ppid);
+        let mut process = Process::new(pid, ppid);
+        if let Some(parent) = self.processes.get(&ppid) {
+            process.owner = parent.owner.clone();
+            process.name = format!("{}-child", parent.name);
+        }
         self.processes.insert(pid, process);
         pid
     }
@@ -180,6 +287,26 @@ impl ProcessManager {
         self.processes.get_mut(&pid)
     }
 
+    /// Get a process, or the standard shell error for a missing PID
+    pub fn get_or_error(&self, pid: u32) -> Result<&Process, String> {
+        self.processes.get(&pid).ok_or_else(|| not_found(pid))
+    }
+
+    /// Mutable variant of `get_or_error`
+    pub fn get_mut_or_error(&mut self, pid: u32) -> Result<&mut Process, String> {
+        self.processes.get_mut(&pid).ok_or_else(|| not_found(pid))
+    }
+
+    /// Give a process a new name; names are single words so they stay
+    /// one column in listings
+    pub fn rename(&mut self, pid: u32, name: &str) -> Result<(), String> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("Error: Invalid process name '{}'", name));
+        }
+        self.get_mut_or_error(pid)?.name = name.to_string();
+        Ok(())
+    }
+
     /// Terminate a process
     pub fn terminate_process(&mut self, pid: u32) -> bool {
         if let Some(process) = self.processes.get_mut(&pid) {
@@ -189,12 +316,59 @@ impl ProcessManager {
         false
     }
 
-    /// Get all processes
+    /// Get all processes, in ascending PID order
     pub fn all_processes(&self) -> Vec<&Process> {
         self.processes.values().collect()
     }
 
-    /// Get all active (non-terminated) processes
+    /// PIDs of the live (non-terminated) children of `pid`, in PID order
+    pub fn children(&self, pid: u32) -> Vec<u32> {
+        self.processes
+            .values()
+            .filter(|p| p.ppid == pid && p.pid != pid && p.state != ProcessState::Terminated)
+            .map(|p| p.pid)
+            .collect()
+    }
+
+    /// PIDs of every live process below `pid` in the tree, parents before
+    /// their children
+    pub fn descendants(&self, pid: u32) -> Vec<u32> {
+        let mut found = Vec::new();
+        let mut stack = vec![pid];
+        while let Some(parent) = stack.pop() {
+            for child in self.children(parent).into_iter().rev() {
+                found.push(child);
+                stack.push(child);
+            }
+        }
+        found
+    }
+
+    /// Hand the live children of `pid` to init (PID 1)
+    pub fn reparent_children(&mut self, pid: u32) {
+        for child in self.children(pid) {
+            if let Some(process) = self.processes.get_mut(&child) {
+                process.ppid = 1;
+            }
+        }
+    }
+
+    /// Live processes whose parent is missing or terminated, i.e. children
+    /// that should have been reparented but weren't. A PPID of 0 marks a
+    /// root (init) rather than an orphan.
+    pub fn orphans(&self) -> Vec<&Process> {
+        self.processes
+            .values()
+            .filter(|p| p.state != ProcessState::Terminated && p.ppid != 0)
+            .filter(|p| {
+                self.processes
+                    .get(&p.ppid)
+                    .is_none_or(|parent| parent.state == ProcessState::Terminated)
+            })
+            .collect()
+    }
+
+    /// Get all active (non-terminated) processes, in ascending PID order
     pub fn active_processes(&self) -> Vec<&Process> {
         self.processes
             .values()
@@ -227,16 +401,61 @@ impl ProcessManager {
         self.current_process_id = None;
     }
 
+    /// Sum CPU time and process counts per owner (terminated processes included)
+    pub fn usage_by_owner(&self) -> BTreeMap<String, OwnerUsage> {
+        let mut usage: BTreeMap<String, OwnerUsage> = BTreeMap::new();
+        for process in self.processes.values() {
+            let entry = usage.entry(process.owner.clone()).or_default();
+            entry.cpu_time += process.total_time;
+            entry.processes += 1;
+        }
+        usage
+    }
+
     /// Get process count
     pub fn process_count(&self) -> usize {
         self.processes.len()
     }
 }
 
+impl Default for ProcessManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_state_names() {
+        assert_eq!(ProcessState::Ready.name(), "ready");
+        assert_eq!(ProcessState::Running.name(), "running");
+        assert_eq!(ProcessState::Blocked.name(), "blocked");
+        assert_eq!(ProcessState::Sleeping.name(), "sleeping");
+        assert_eq!(ProcessState::Stopped.name(), "stopped");
+        assert_eq!(ProcessState::Terminated.name(), "terminated");
+    }
+
+    #[test]
+    fn test_parse_state() {
+        for state in [
+            ProcessState::Ready,
+            ProcessState::Running,
+            ProcessState::Blocked,
+            ProcessState::Sleeping,
+            ProcessState::Stopped,
+            ProcessState::Terminated,
+        ] {
+            assert_eq!(state.name().parse::<ProcessState>(), Ok(state));
+        }
+        assert_eq!("BLOCKED".parse::<ProcessState>(), Ok(ProcessState::Blocked));
+        assert_eq!("Running".parse::<ProcessState>(), Ok(ProcessState::Running));
+        assert!("zombie".parse::<ProcessState>().is_err());
+        assert!("".parse::<ProcessState>().is_err());
+    }
+
     #[test]
     fn test_process_creation() {
         let process = Process::new(1, 0);
@@ -246,6 +465,19 @@ mod tests {
         assert_eq!(process.priority, 3);
     }
 
+    #[test]
+    fn test_is_runnable() {
+        let mut process = Process::new(1, 0);
+        assert!(process.is_runnable());
+        process.set_state(ProcessState::Running);
+        assert!(process.is_runnable());
+
+        for state in [ProcessState::Blocked, ProcessState::Sleeping, ProcessState::Stopped, ProcessState::Terminated] {
+            process.set_state(state);
+            assert!(!process.is_runnable(), "{:?}", state);
+        }
+    }
+
     #[test]
     fn test_process_state_transition() {
         let mut process = Process::new(1, 0);
@@ -271,6 +503,45 @@ mod tests {
         assert_eq!(manager.process_count(), 2);
     }
 
+    #[test]
+    fn test_processes_iterate_in_pid_order() {
+        let mut manager = ProcessManager::new();
+        for _ in 0..5 {
+            manager.create_process(0);
+        }
+        manager.processes.insert(42, Process::new(42, 0));
+        manager.processes.insert(7, Process::new(7, 0));
+
+        let pids: Vec<u32> = manager.all_processes().iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1, 2, 3, 4, 5, 7, 42]);
+    }
+
+    #[test]
+    fn test_owner_inherited_on_fork() {
+        let mut manager = ProcessManager::new();
+        let parent = manager.create_process(0);
+        manager.get_process_mut(parent).unwrap().owner = "alice".to_string();
+
+        let child = manager.create_process(parent);
+        assert_eq!(manager.get_process(child).unwrap().owner, "alice");
+    }
+
+    #[test]
+    fn test_usage_by_owner() {
+        let mut manager = ProcessManager::new();
+        let a = manager.create_process(0);
+        let b = manager.create_process(0);
+        let c = manager.create_process(0);
+        manager.get_process_mut(a).unwrap().total_time = 100;
+        manager.get_process_mut(b).unwrap().owner = "bob".to_string();
+        manager.get_process_mut(b).unwrap().total_time = 40;
+        manager.get_process_mut(c).unwrap().total_time = 10;
+
+        let usage = manager.usage_by_owner();
+        assert_eq!(usage["root"], OwnerUsage { cpu_time: 110, processes: 2 });
+        assert_eq!(usage["bob"], OwnerUsage { cpu_time: 40, processes: 1 });
+    }
+
     #[test]
     fn test_process_quantum_tracking() {
         let mut process = Process::new(1, 0);
@@ -284,11 +555,109 @@ mod tests {
 
     #[test]
     fn test_process_metrics() {
-        let process = Process::new(1, 0);
+        let mut process = Process::new(1, 0);
+
+        // Turnaround runs from creation to termination
+        process.termination_time = Some(process.creation_time + chrono::Duration::milliseconds(25));
+        assert_eq!(process.turnaround_time(), 25);
+    }
+
+    #[test]
+    fn test_program_counter_advancement() {
+        let mut process = Process::new(1, 0);
+        process.program_length = Some(100);
+
+        process.advance_program_counter(64);
+        assert_eq!(process.program_counter, 64);
+        assert!(!process.is_complete());
+
+        process.advance_program_counter(64);
+        assert_eq!(process.program_counter, 100);
+        assert!(process.is_complete());
+    }
+
+    #[test]
+    fn test_children() {
+        let mut manager = ProcessManager::new();
+        let parent = manager.create_process(0);
+        let a = manager.create_process(parent);
+        let b = manager.create_process(parent);
+        manager.create_process(a);
+
+        assert_eq!(manager.children(parent), vec![a, b]);
+        manager.terminate_process(b);
+        assert_eq!(manager.children(parent), vec![a]);
+        assert!(manager.children(b).is_empty());
+    }
+
+    #[test]
+    fn test_active_processes_in_pid_order() {
+        let mut manager = ProcessManager::new();
+        let init = manager.create_process(0);
+        let pids: Vec<u32> = (0..20).map(|_| manager.create_process(init)).collect();
+        manager.terminate_process(pids[7]);
+
+        let active: Vec<u32> = manager.active_processes().iter().map(|p| p.pid).collect();
+        assert_eq!(active.len(), 20);
+        assert!(active.windows(2).all(|w| w[0] < w[1]), "{:?}", active);
+    }
+
+    #[test]
+    fn test_descendants_and_reparent() {
+        let mut manager = ProcessManager::new();
+        let init = manager.create_process(0);
+        let root = manager.create_process(init);
+        let child = manager.create_process(root);
+        let grandchild = manager.create_process(child);
+
+        let mut subtree = manager.descendants(root);
+        subtree.sort_unstable();
+        assert_eq!(subtree, vec![child, grandchild]);
+
+        manager.reparent_children(child);
+        assert_eq!(manager.get_process(grandchild).unwrap().ppid, 1);
+    }
+
+    #[test]
+    fn test_orphans_have_dead_or_missing_parents() {
+        let mut manager = ProcessManager::new();
+        let init = manager.create_process(0);
+        let parent = manager.create_process(init);
+        let child = manager.create_process(parent);
+        let stray = manager.create_process(99);
+        assert_eq!(manager.orphans().iter().map(|p| p.pid).collect::<Vec<_>>(), vec![stray]);
+
+        manager.terminate_process(parent);
+        assert_eq!(manager.orphans().iter().map(|p| p.pid).collect::<Vec<_>>(), vec![child, stray]);
+
+        manager.reparent_children(parent);
+        assert_eq!(manager.orphans().iter().map(|p| p.pid).collect::<Vec<_>>(), vec![stray]);
+    }
+
+    #[test]
+    fn test_names_default_and_rename() {
+        let mut manager = ProcessManager::new();
+        let init = manager.create_process(0);
+        let child = manager.create_process(init);
+        assert_eq!(manager.get_process(init).unwrap().name, "proc-1");
+        assert_eq!(manager.get_process(child).unwrap().name, "proc-1-child");
+
+        assert_eq!(manager.rename(child, "worker"), Ok(()));
+        let grandchild = manager.create_process(child);
+        assert_eq!(manager.get_process(grandchild).unwrap().name, "worker-child");
+
+        assert!(manager.rename(child, "two words").is_err());
+        assert!(manager.rename(99, "ghost").is_err());
+    }
+
+    #[test]
+    fn test_get_or_error() {
+        let mut manager = ProcessManager::new();
+        let pid = manager.create_process(0);
 
-        // Just verify turnaround_time method doesn't panic and returns a value
-        let turnaround = process.turnaround_time();
-        assert!(turnaround >= 0); // Should always be non-negative
+        assert_eq!(manager.get_or_error(pid).map(|p| p.pid), Ok(pid));
+        assert_eq!(manager.get_or_error(99).unwrap_err(), "Error: Process 99 not found");
+        assert_eq!(manager.get_mut_or_error(99).unwrap_err(), "Error: Process 99 not found");
     }
 
     #[test]
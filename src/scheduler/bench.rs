@@ -0,0 +1,420 @@
+// src/scheduler/bench.rs
+// Side-by-side comparison of MLFQ against FCFS and Round Robin
+
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::MLFQScheduler;
+
+/// Seed used by `bench` when none is given, so runs are comparable
+pub const DEFAULT_BENCH_SEED: u64 = 42;
+
+/// Number of jobs in a generated workload
+pub const DEFAULT_BENCH_JOBS: usize = 12;
+
+/// Time slice for the Round Robin baseline (ms)
+pub const RR_QUANTUM: u64 = 16;
+
+/// One job in a benchmark workload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Job {
+    pub pid: u32,
+    pub arrival: u64,           // Sim time the job becomes runnable (ms)
+    pub burst: u64,             // Total CPU time it needs (ms)
+    pub interactive: bool,      // Gives up the CPU early each slice
+}
+
+/// Averages for one scheduler over a workload
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub scheduler: String,
+    pub avg_turnaround: f64,    // ms
+    pub avg_waiting: f64,       // ms
+    pub fairness: f64,          // Jain's index over burst/turnaround (1.0 = fair)
+    pub throughput: f64,        // Jobs completed per second of sim time
+}
+
+/// Mean and sample standard deviation of one metric across repeated runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spread {
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Spread {
+    pub fn of(values: &[f64]) -> Self {
+        let n = values.len();
+        if n == 0 {
+            return Spread { mean: 0.0, stddev: 0.0 };
+        }
+
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let stddev = if n > 1 {
+            let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+            variance.sqrt()
+        } else {
+            0.0
+        };
+        Spread { mean, stddev }
+    }
+}
+
+/// One scheduler's metrics aggregated over several seeded workloads
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatedResult {
+    pub scheduler: String,
+    pub runs: usize,
+    pub avg_turnaround: Spread,
+    pub avg_waiting: Spread,
+    pub fairness: Spread,
+    pub throughput: Spread,
+}
+
+/// Generate a reproducible mix of short interactive and long CPU-bound jobs
+pub fn generate_workload(seed: u64, count: usize) -> Vec<Job> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut arrival = 0;
+
+    (0..count)
+        .map(|i| {
+            let interactive = rng.gen_bool(0.5);
+            let burst = if interactive {
+                rng.gen_range(10..=60)
+            } else {
+                rng.gen_range(100..=400)
+            };
+            let job = Job { pid: i as u32 + 2, arrival, burst, interactive };
+            arrival += rng.gen_range(0..=20);
+            job
+        })
+        .collect()
+}
+
+/// Run every scheduler over the same workload. MLFQ runs on a fresh
+/// scheduler configured like `template`.
+pub fn run_bench(template: &MLFQScheduler, jobs: &[Job]) -> Vec<BenchResult> {
+    vec![
+        summarize("MLFQ", jobs, &simulate_mlfq(template, jobs)),
+        summarize("FCFS", jobs, &simulate_fcfs(jobs)),
+        summarize("RR", jobs, &simulate_rr(jobs, RR_QUANTUM)),
+    ]
+}
+
+/// Run every scheduler over `runs` workloads seeded `seed`, `seed + 1`, ...
+/// and aggregate each metric per scheduler
+pub fn run_repeated(template: &MLFQScheduler, seed: u64, count: usize, runs: u32) -> Vec<RepeatedResult> {
+    let per_run: Vec<Vec<BenchResult>> = (0..runs as u64)
+        .map(|i| run_bench(template, &generate_workload(seed.wrapping_add(i), count)))
+        .collect();
+
+    let Some(first) = per_run.first() else {
+        return Vec::new();
+    };
+
+    (0..first.len())
+        .map(|idx| {
+            let runs: Vec<&BenchResult> = per_run.iter().map(|results| &results[idx]).collect();
+            let spread = |metric: fn(&BenchResult) -> f64| {
+                Spread::of(&runs.iter().map(|r| metric(r)).collect::<Vec<_>>())
+            };
+
+            RepeatedResult {
+                scheduler: first[idx].scheduler.clone(),
+                runs: runs.len(),
+                avg_turnaround: spread(|r| r.avg_turnaround),
+                avg_waiting: spread(|r| r.avg_waiting),
+                fairness: spread(|r| r.fairness),
+                throughput: spread(|r| r.throughput),
+            }
+        })
+        .collect()
+}
+
+/// Completion time of each job, by index into `jobs`
+fn simulate_fcfs(jobs: &[Job]) -> Vec<u64> {
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by_key(|&i| jobs[i].arrival);
+
+    let mut completion = vec![0; jobs.len()];
+    let mut clock = 0;
+    for i in order {
+        clock = clock.max(jobs[i].arrival) + jobs[i].burst;
+        completion[i] = clock;
+    }
+    completion
+}
+
+fn simulate_rr(jobs: &[Job], quantum: u64) -> Vec<u64> {
+    let mut remaining: Vec<u64> = jobs.iter().map(|j| j.burst).collect();
+    let mut completion = vec![0; jobs.len()];
+    let mut ready = VecDeque::new();
+    let mut admitted = vec![false; jobs.len()];
+    let mut clock = 0;
+    let mut done = 0;
+
+    while done < jobs.len() {
+        admit_arrivals(jobs, clock, &mut admitted, |i| ready.push_back(i));
+
+        let Some(i) = ready.pop_front() else {
+            clock = next_arrival(jobs, &admitted);
+            continue;
+        };
+
+        let ran = quantum.min(remaining[i]);
+        clock += ran;
+        remaining[i] -= ran;
+
+        // Arrivals during the slice queue ahead of the preempted job
+        admit_arrivals(jobs, clock, &mut admitted, |i| ready.push_back(i));
+
+        if remaining[i] == 0 {
+            completion[i] = clock;
+            done += 1;
+        } else {
+            ready.push_back(i);
+        }
+    }
+    completion
+}
+
+fn simulate_mlfq(template: &MLFQScheduler, jobs: &[Job]) -> Vec<u64> {
+    let mut scheduler = template.clone_config();
+    let mut remaining: Vec<u64> = jobs.iter().map(|j| j.burst).collect();
+    let mut completion = vec![0; jobs.len()];
+    let mut admitted = vec![false; jobs.len()];
+    let mut clock = 0;
+    let mut done = 0;
+
+    while done < jobs.len() {
+        admit_arrivals(jobs, clock, &mut admitted, |i| scheduler.add_process(jobs[i].pid));
+
+        let Some((pid, quantum)) = scheduler.next_process() else {
+            clock = next_arrival(jobs, &admitted);
+            continue;
+        };
+        let i = jobs.iter().position(|j| j.pid == pid).expect("scheduled unknown job");
+
+        let slice = if jobs[i].interactive { (quantum as u64 / 2).max(1) } else { quantum as u64 };
+        let ran = slice.min(remaining[i]);
+        clock += ran;
+        remaining[i] -= ran;
+
+        if remaining[i] == 0 {
+            completion[i] = clock;
+            scheduler.remove_process(pid);
+            done += 1;
+        } else if ran < quantum as u64 {
+            scheduler.process_yielded_early(pid);
+        } else {
+            scheduler.process_used_full_quantum(pid);
+        }
+    }
+    completion
+}
+
+fn admit_arrivals<F: FnMut(usize)>(jobs: &[Job], clock: u64, admitted: &mut [bool], mut admit: F) {
+    for (i, job) in jobs.iter().enumerate() {
+        if !admitted[i] && job.arrival <= clock {
+            admitted[i] = true;
+            admit(i);
+        }
+    }
+}
+
+/// Earliest arrival among jobs not yet admitted (the CPU idles until then)
+fn next_arrival(jobs: &[Job], admitted: &[bool]) -> u64 {
+    jobs.iter()
+        .zip(admitted)
+        .filter(|(_, &seen)| !seen)
+        .map(|(job, _)| job.arrival)
+        .min()
+        .unwrap_or(0)
+}
+
+fn summarize(name: &str, jobs: &[Job], completion: &[u64]) -> BenchResult {
+    let n = jobs.len().max(1) as f64;
+
+    let turnarounds: Vec<f64> = jobs
+        .iter()
+        .zip(completion)
+        .map(|(job, &done)| done.saturating_sub(job.arrival) as f64)
+        .collect();
+    let total_burst: f64 = jobs.iter().map(|j| j.burst as f64).sum();
+    let total_turnaround: f64 = turnarounds.iter().sum();
+
+    // Jain's fairness index over each job's share of the CPU while it existed
+    let shares: Vec<f64> = jobs
+        .iter()
+        .zip(&turnarounds)
+        .map(|(job, &t)| if t > 0.0 { job.burst as f64 / t } else { 1.0 })
+        .collect();
+    let sum: f64 = shares.iter().sum();
+    let sum_sq: f64 = shares.iter().map(|s| s * s).sum();
+    let fairness = if sum_sq > 0.0 { sum * sum / (n * sum_sq) } else { 1.0 };
+
+    let first_arrival = jobs.iter().map(|j| j.arrival).min().unwrap_or(0);
+    let makespan = completion.iter().copied().max().unwrap_or(0).saturating_sub(first_arrival);
+    let throughput = if makespan > 0 { jobs.len() as f64 * 1000.0 / makespan as f64 } else { 0.0 };
+
+    BenchResult {
+        scheduler: name.to_string(),
+        avg_turnaround: total_turnaround / n,
+        avg_waiting: (total_turnaround - total_burst) / n,
+        fairness,
+        throughput,
+    }
+}
+
+/// Human-readable comparison table
+pub fn format_table(results: &[BenchResult]) -> String {
+    let mut output = String::from(
+        "Scheduler Comparison\n\
+         ────────────────────────────────────────────────────────────\n\
+         SCHED  AVG_TURNAROUND  AVG_WAITING  FAIRNESS  THROUGHPUT\n"
+    );
+    for r in results {
+        output.push_str(&format!(
+            "{:<6} {:>12.2}ms {:>10.2}ms {:>9.3} {:>8.2}/s\n",
+            r.scheduler, r.avg_turnaround, r.avg_waiting, r.fairness, r.throughput
+        ));
+    }
+    output
+}
+
+/// Machine-readable comparison, one row per scheduler
+pub fn format_csv(results: &[BenchResult]) -> String {
+    let mut output = String::from("scheduler,avg_turnaround,avg_waiting,fairness,throughput\n");
+    for r in results {
+        output.push_str(&format!(
+            "{},{:.2},{:.2},{:.4},{:.4}\n",
+            r.scheduler, r.avg_turnaround, r.avg_waiting, r.fairness, r.throughput
+        ));
+    }
+    output
+}
+
+/// Human-readable comparison of repeated runs as mean ± stddev
+pub fn format_repeated_table(results: &[RepeatedResult]) -> String {
+    let runs = results.first().map_or(0, |r| r.runs);
+    let mut output = format!(
+        "Scheduler Comparison ({} runs, mean ± stddev)\n\
+         ──────────────────────────────────────────────────────────────────────────────\n\
+         {:<6} {:>19} {:>19} {:>15} {:>17}\n",
+        runs, "SCHED", "AVG_TURNAROUND", "AVG_WAITING", "FAIRNESS", "THROUGHPUT"
+    );
+    for r in results {
+        output.push_str(&format!(
+            "{:<6} {:>19} {:>19} {:>15} {:>17}\n",
+            r.scheduler,
+            format!("{:.2} ± {:.2}ms", r.avg_turnaround.mean, r.avg_turnaround.stddev),
+            format!("{:.2} ± {:.2}ms", r.avg_waiting.mean, r.avg_waiting.stddev),
+            format!("{:.3} ± {:.3}", r.fairness.mean, r.fairness.stddev),
+            format!("{:.2} ± {:.2}/s", r.throughput.mean, r.throughput.stddev),
+        ));
+    }
+    output
+}
+
+/// Machine-readable comparison of repeated runs, one row per scheduler
+pub fn format_repeated_csv(results: &[RepeatedResult]) -> String {
+    let mut output = String::from(
+        "scheduler,runs,avg_turnaround,avg_turnaround_stddev,avg_waiting,avg_waiting_stddev,\
+         fairness,fairness_stddev,throughput,throughput_stddev\n"
+    );
+    for r in results {
+        output.push_str(&format!(
+            "{},{},{:.2},{:.2},{:.2},{:.2},{:.4},{:.4},{:.4},{:.4}\n",
+            r.scheduler,
+            r.runs,
+            r.avg_turnaround.mean,
+            r.avg_turnaround.stddev,
+            r.avg_waiting.mean,
+            r.avg_waiting.stddev,
+            r.fairness.mean,
+            r.fairness.stddev,
+            r.throughput.mean,
+            r.throughput.stddev
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::SchedulerConfig;
+
+    fn job(pid: u32, arrival: u64, burst: u64) -> Job {
+        Job { pid, arrival, burst, interactive: false }
+    }
+
+    #[test]
+    fn test_workload_is_reproducible() {
+        assert_eq!(generate_workload(7, 8), generate_workload(7, 8));
+        assert_ne!(generate_workload(7, 8), generate_workload(8, 8));
+    }
+
+    #[test]
+    fn test_fcfs_and_rr_completion() {
+        let jobs = vec![job(2, 0, 30), job(3, 0, 10)];
+
+        assert_eq!(simulate_fcfs(&jobs), vec![30, 40]);
+        // RR(16): 2 runs 0-16, 3 runs 16-26 and finishes, 2 runs 26-40
+        assert_eq!(simulate_rr(&jobs, 16), vec![40, 26]);
+    }
+
+    #[test]
+    fn test_every_job_completes_under_mlfq() {
+        let jobs = generate_workload(DEFAULT_BENCH_SEED, DEFAULT_BENCH_JOBS);
+        let completion = simulate_mlfq(&MLFQScheduler::new(), &jobs);
+        let total_burst: u64 = jobs.iter().map(|j| j.burst).sum();
+
+        assert!(completion.iter().zip(&jobs).all(|(&done, j)| done >= j.arrival + j.burst));
+        assert!(completion.iter().copied().max().unwrap() >= total_burst);
+    }
+
+    #[test]
+    fn test_mlfq_uses_template_config() {
+        let jobs = vec![job(2, 0, 30), job(3, 0, 10)];
+        // Admitted to Q3 (64ms) by default, so both jobs run to completion
+        assert_eq!(simulate_mlfq(&MLFQScheduler::new(), &jobs), vec![30, 40]);
+
+        // A single 16ms level is plain round robin
+        let config = SchedulerConfig { quantums: vec![16], boost_interval: 0, ..Default::default() };
+        let template = MLFQScheduler::with_config(&config).unwrap();
+        assert_eq!(simulate_mlfq(&template, &jobs), simulate_rr(&jobs, 16));
+    }
+
+    #[test]
+    fn test_summary_waiting_is_turnaround_minus_burst() {
+        let jobs = vec![job(2, 0, 30), job(3, 0, 10)];
+        let result = summarize("FCFS", &jobs, &simulate_fcfs(&jobs));
+
+        assert_eq!(result.avg_turnaround, 35.0);
+        assert_eq!(result.avg_waiting, 15.0);
+        assert_eq!(result.throughput, 50.0);
+        assert!(result.fairness > 0.0 && result.fairness <= 1.0);
+    }
+
+    #[test]
+    fn test_spread_mean_and_sample_stddev() {
+        let spread = Spread::of(&[2.0, 4.0, 6.0]);
+        assert_eq!(spread.mean, 4.0);
+        assert_eq!(spread.stddev, 2.0);
+        assert_eq!(Spread::of(&[5.0]).stddev, 0.0);
+    }
+
+    #[test]
+    fn test_repeated_runs_each_scheduler() {
+        let results = run_repeated(&MLFQScheduler::new(), DEFAULT_BENCH_SEED, 6, 3);
+        let names: Vec<&str> = results.iter().map(|r| r.scheduler.as_str()).collect();
+
+        assert_eq!(names, vec!["MLFQ", "FCFS", "RR"]);
+        assert!(results.iter().all(|r| r.runs == 3));
+        // Different seeds give different workloads, so the runs disagree
+        assert!(results.iter().all(|r| r.avg_turnaround.stddev > 0.0));
+        assert_eq!(run_repeated(&MLFQScheduler::new(), DEFAULT_BENCH_SEED, 6, 0), Vec::new());
+    }
+}
@@ -1,8 +1,45 @@
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::programs::ProgramType;
+use crate::process::ProcessState;
+
+/// Sparkline glyphs from empty to the busiest sample
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Heatmap shades from no time to a process's busiest queue
+const HEAT_LEVELS: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// Queue-depth samples kept before the oldest are dropped
+pub const DEFAULT_QUEUE_DEPTH_WINDOW: usize = 1000;
+
+/// Cycles without a queue change before the summary calls the MLFQ converged
+pub const DEFAULT_CONVERGENCE_WINDOW: u64 = 20;
+
+/// Why a process moved between queues
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueChange {
+    /// Moved to a lower-priority queue
+    Demotion,
+    /// Moved to a higher-priority queue on its own merit (early yield, renice)
+    Promotion,
+    /// Lifted by the scheduler: periodic boost, starvation guard or priority inheritance
+    Boost,
+}
+
+/// How a process came off the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchKind {
+    /// The process gave the CPU up itself (yielded early or exited)
+    Voluntary,
+    /// The scheduler took it away (quantum expired or preempted)
+    Involuntary,
+}
 
 /// Metrics for a single process
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ProcessMetrics {
     pub pid: u32,
     pub turnaround_time: u64,      // Time from creation to termination (ms)
@@ -10,7 +47,17 @@ pub struct ProcessMetrics {
     pub waiting_time: u64,          // Turnaround - execution time (ms)
     pub execution_time: u64,        // Total time actually running (ms)
     pub context_switches: u32,      // How many times this process was switched
+    pub voluntary_switches: u32,    // Switches out where the process gave up the CPU
+    pub involuntary_switches: u32,  // Switches out forced by quantum expiry or preemption
     pub queue_changes: u32,         // How many times it moved between queues
+    pub demotions: u32,             // Moves to a lower-priority queue
+    pub promotions: u32,            // Moves up earned by the process itself
+    pub boosts: u32,                // Moves up forced by the scheduler
+    pub arrival_tick: u64,          // Sim clock when the process was created (ms)
+    pub terminated: bool,           // Final figures recorded
+    pub ready_time: u64,            // Sim time spent runnable in a queue (ms)
+    pub running_time: u64,          // Sim time spent on the CPU (ms)
+    pub blocked_time: u64,          // Sim time spent blocked, sleeping or stopped (ms)
 }
 
 impl ProcessMetrics {
@@ -22,20 +69,192 @@ impl ProcessMetrics {
             waiting_time: 0,
             execution_time: 0,
             context_switches: 0,
+            voluntary_switches: 0,
+            involuntary_switches: 0,
             queue_changes: 0,
+            demotions: 0,
+            promotions: 0,
+            boosts: 0,
+            arrival_tick: 0,
+            terminated: false,
+            ready_time: 0,
+            running_time: 0,
+            blocked_time: 0,
+        }
+    }
+
+    /// Turnaround so far for a live process, measured on the sim clock
+    pub fn current_turnaround(&self, now: u64) -> u64 {
+        now.saturating_sub(self.arrival_tick)
+    }
+
+    /// Time spent runnable but not running so far, measured on the sim clock
+    pub fn current_waiting(&self, now: u64) -> u64 {
+        self.current_turnaround(now).saturating_sub(self.execution_time)
+    }
+}
+
+/// A contiguous stretch of CPU time given to one process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionSlice {
+    pub pid: u32,
+    pub start_tick: u64,            // Sim clock when the slice began (ms)
+    pub duration: u64,              // How long the process ran (ms)
+    pub queue: usize,               // Queue level it was dispatched from
+}
+
+/// Running totals of non-runnable processes, one sample per scheduling cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StateBreakdown {
+    pub samples: u64,
+    pub blocked: u64,               // Waiting on I/O, a lock, or a semaphore
+    pub sleeping: u64,              // Waiting on a timer
+    pub stopped: u64,               // Suspended until continued
+}
+
+impl StateBreakdown {
+    fn average(&self, total: u64) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        total as f64 / self.samples as f64
+    }
+
+    pub fn avg_blocked(&self) -> f64 {
+        self.average(self.blocked)
+    }
+
+    pub fn avg_sleeping(&self) -> f64 {
+        self.average(self.sleeping)
+    }
+
+    pub fn avg_stopped(&self) -> f64 {
+        self.average(self.stopped)
+    }
+}
+
+/// Per-queue tallies of CPU-bound vs I/O-bound occupants, one sample per cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueOccupancy {
+    pub samples: u64,
+    pub cpu_bound: [u64; 4],        // CpuBound and Batch programs
+    pub io_bound: [u64; 4],         // IoBound and Interactive programs
+    pub other: [u64; 4],            // Mixed programs and plain processes
+}
+
+impl QueueOccupancy {
+    fn occupants(&self, level: usize) -> u64 {
+        self.cpu_bound[level] + self.io_bound[level] + self.other[level]
+    }
+
+    fn fraction(&self, count: u64, level: usize) -> f64 {
+        match self.occupants(level) {
+            0 => 0.0,
+            total => count as f64 / total as f64,
+        }
+    }
+
+    /// Share of the queue's occupants that were CPU-bound (0.0-1.0)
+    pub fn cpu_fraction(&self, level: usize) -> f64 {
+        self.fraction(self.cpu_bound[level], level)
+    }
+
+    /// Share of the queue's occupants that were I/O-bound (0.0-1.0)
+    pub fn io_fraction(&self, level: usize) -> f64 {
+        self.fraction(self.io_bound[level], level)
+    }
+
+    pub fn other_fraction(&self, level: usize) -> f64 {
+        self.fraction(self.other[level], level)
+    }
+}
+
+/// CPU time split by who got it: useful work per workload class, plus the
+/// cost of switching between processes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Goodput {
+    pub interactive: u64,           // IoBound and Interactive programs (ms)
+    pub batch: u64,                 // CpuBound and Batch programs (ms)
+    pub other: u64,                 // Mixed programs and plain processes (ms)
+    pub switch_overhead: u64,       // Context-switch cost (ms)
+}
+
+impl Goodput {
+    /// Time spent running processes, excluding switch overhead
+    pub fn useful(&self) -> u64 {
+        self.interactive + self.batch + self.other
+    }
+}
+
+/// Ordering for the per-process table in the summary report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetricSortKey {
+    #[default]
+    Pid,
+    Turnaround,
+    Waiting,
+    Execution,
+}
+
+impl std::str::FromStr for MetricSortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pid" => Ok(MetricSortKey::Pid),
+            "turnaround" => Ok(MetricSortKey::Turnaround),
+            "waiting" => Ok(MetricSortKey::Waiting),
+            "execution" => Ok(MetricSortKey::Execution),
+            _ => Err(format!("Unknown sort key '{}'", s)),
         }
     }
 }
 
+/// Counters in the JSON export
+#[derive(Serialize)]
+struct JsonTotals {
+    ticks: u64,
+    context_switches: u64,
+    switches_per_queue: [u64; 4],
+    processes_created: u32,
+    processes_terminated: u32,
+    execution_time: u64,
+    waiting_time: u64,
+    idle_time: u64,
+}
+
+/// Derived figures in the JSON export
+#[derive(Serialize)]
+struct JsonAverages {
+    turnaround_time: f64,
+    response_time: f64,
+    waiting_time: f64,
+    cpu_utilization: f64,
+    context_switch_rate: f64,
+    queue_depth: [f64; 4],
+    max_queue_depth: [usize; 4],
+}
+
+/// The JSON export; borrows the per-process metrics rather than copying them
+#[derive(Serialize)]
+struct JsonStats<'a> {
+    totals: JsonTotals,
+    averages: JsonAverages,
+    processes: Vec<&'a ProcessMetrics>,
+}
+
 /// System-wide scheduler statistics
 #[derive(Debug, Clone)]
 pub struct SchedulerStats {
-    /// Per-process metrics
-    pub process_metrics: HashMap<u32, ProcessMetrics>,
+    /// Per-process metrics, keyed by PID so iteration is always ascending
+    pub process_metrics: BTreeMap<u32, ProcessMetrics>,
 
     /// Total number of context switches in system
     pub total_context_switches: u64,
 
+    /// Context switches broken down by the queue level dispatched from
+    pub switches_per_queue: [u64; 4],
+
     /// Total system time elapsed (ticks/cycles)
     pub total_ticks: u64,
 
@@ -48,82 +267,435 @@ pub struct SchedulerStats {
     /// Total time all processes spent executing
     pub total_execution_time: u64,
 
+    /// Part of `total_execution_time` not held by any `ProcessMetrics`:
+    /// time recorded for untracked PIDs or zeroed by `reset_process`
+    pub unattributed_execution_time: u64,
+
     /// Total time all processes spent waiting
     pub total_waiting_time: u64,
 
-    /// Track queue depths over time (for analysis)
-    pub queue_depth_samples: Vec<[usize; 4]>,
+    /// Time the idle task held the CPU because nothing else was runnable
+    pub idle_time: u64,
+
+    /// Most recent queue depths, oldest first (for analysis)
+    pub queue_depth_samples: VecDeque<[usize; 4]>,
+
+    /// Maximum number of queue-depth samples retained
+    pub queue_depth_window: usize,
+
+    /// Deepest each queue has been in any sample, including ones that have
+    /// since left the window
+    pub max_queue_depth: [usize; 4],
+
+    /// CPU-bound vs I/O-bound occupants of each queue, sampled each cycle
+    pub queue_occupancy: QueueOccupancy,
+
+    /// Non-runnable process counts sampled each cycle
+    pub state_breakdown: StateBreakdown,
+
+    /// Useful CPU time per workload class and switch overhead
+    pub goodput: Goodput,
+
+    /// Every dispatch in order, for per-process timelines
+    pub execution_log: Vec<ExecutionSlice>,
+
+    /// (tick, pid) of every context switch, for windowed reports
+    pub switch_log: Vec<(u64, u32)>,
 
     /// Time when stats were started/reset
     pub start_time: std::time::Instant,
+
+    /// Wall-clock interval between CPU samples (None = sampling off)
+    pub sampling_interval: Option<std::time::Duration>,
+
+    /// Wall time the last CPU sample was taken
+    pub last_sample: Option<std::time::Instant>,
+
+    /// (elapsed_ms, cpu_utilization) points for live graphs
+    pub cpu_samples: Vec<(u64, f64)>,
+
+    /// Leading scheduling cycles excluded from the accumulated metrics
+    pub warmup_cycles: u32,
+
+    /// Scheduling cycles started since the stats were created/reset
+    pub cycles_seen: u64,
+
+    /// Cycle the most recent queue change happened in (0 = none yet).
+    /// Global boosts, and the demotions that bring a process back down to
+    /// where it was boosted from, don't count.
+    pub last_queue_change_cycle: u64,
+
+    /// Level each process was lifted from by the last global boost, kept
+    /// until it sinks back there or moves some other way
+    pub boosted_from: BTreeMap<u32, usize>,
 }
 
 impl SchedulerStats {
     pub fn new() -> Self {
         SchedulerStats {
-            process_metrics: HashMap::new(),
+            process_metrics: BTreeMap::new(),
             total_context_switches: 0,
+            switches_per_queue: [0; 4],
             total_ticks: 0,
             processes_created: 0,
             processes_terminated: 0,
             total_execution_time: 0,
+            unattributed_execution_time: 0,
             total_waiting_time: 0,
-            queue_depth_samples: Vec::new(),
+            idle_time: 0,
+            queue_depth_samples: VecDeque::new(),
+            queue_depth_window: DEFAULT_QUEUE_DEPTH_WINDOW,
+            max_queue_depth: [0; 4],
+            queue_occupancy: QueueOccupancy::default(),
+            state_breakdown: StateBreakdown::default(),
+            goodput: Goodput::default(),
+            execution_log: Vec::new(),
+            switch_log: Vec::new(),
             start_time: std::time::Instant::now(),
+            sampling_interval: None,
+            last_sample: None,
+            cpu_samples: Vec::new(),
+            warmup_cycles: 0,
+            cycles_seen: 0,
+            last_queue_change_cycle: 0,
+            boosted_from: BTreeMap::new(),
         }
     }
 
+    /// Ignore switches, ticks, execution time and samples from the first
+    /// `cycles` scheduling cycles so startup transients don't skew averages
+    pub fn set_warmup(&mut self, cycles: u32) {
+        self.warmup_cycles = cycles;
+    }
+
+    /// Mark the start of a scheduling cycle
+    pub fn record_cycle(&mut self) {
+        self.cycles_seen += 1;
+    }
+
+    /// True while the current cycle is still inside the warm-up window
+    pub fn in_warmup(&self) -> bool {
+        self.warmup_cycles > 0 && self.cycles_seen <= self.warmup_cycles as u64
+    }
+
     /// Record a new process creation
     pub fn record_process_created(&mut self, pid: u32) {
+        self.record_process_created_at(pid, 0);
+    }
+
+    /// Record a new process creation at sim time `tick`
+    pub fn record_process_created_at(&mut self, pid: u32, tick: u64) {
         self.processes_created += 1;
-        self.process_metrics.insert(pid, ProcessMetrics::new(pid));
+        let mut metrics = ProcessMetrics::new(pid);
+        metrics.arrival_tick = tick;
+        self.process_metrics.insert(pid, metrics);
     }
 
     /// Record a context switch
     pub fn record_context_switch(&mut self, pid: u32) {
+        self.record_context_switch_at(pid, 0);
+    }
+
+    /// Record a context switch at sim time `tick`
+    pub fn record_context_switch_at(&mut self, pid: u32, tick: u64) {
+        if self.in_warmup() {
+            return;
+        }
+
         self.total_context_switches += 1;
+        self.switch_log.push((tick, pid));
 
         if let Some(metrics) = self.process_metrics.get_mut(&pid) {
             metrics.context_switches += 1;
         }
     }
 
-    /// Record queue change for a process
-    pub fn record_queue_change(&mut self, pid: u32) {
+    /// Record how `pid` left the CPU at the end of a dispatch
+    pub fn record_switch_out(&mut self, pid: u32, kind: SwitchKind) {
+        if self.in_warmup() {
+            return;
+        }
+
+        if let Some(metrics) = self.process_metrics.get_mut(&pid) {
+            match kind {
+                SwitchKind::Voluntary => metrics.voluntary_switches += 1,
+                SwitchKind::Involuntary => metrics.involuntary_switches += 1,
+            }
+        }
+    }
+
+    /// Credit a context switch to the queue level it was dispatched from
+    pub fn record_queue_switch(&mut self, queue_idx: usize) {
+        if self.in_warmup() {
+            return;
+        }
+
+        if queue_idx < 4 {
+            self.switches_per_queue[queue_idx] += 1;
+        }
+    }
+
+    /// Record queue change for a process, crediting the reason behind it
+    pub fn record_queue_change(&mut self, pid: u32, reason: QueueChange) {
+        if self.in_warmup() {
+            return;
+        }
+
+        self.last_queue_change_cycle = self.cycles_seen;
+        self.boosted_from.remove(&pid);
+        self.count_queue_change(pid, reason);
+    }
+
+    /// Record a periodic or starvation-guard boost that lifted `pid` to Q0
+    /// from `from_level`. It is counted as a boost but, being scheduler-wide
+    /// housekeeping, doesn't disturb convergence.
+    pub fn record_global_boost(&mut self, pid: u32, from_level: usize) {
+        if self.in_warmup() {
+            return;
+        }
+
+        self.boosted_from.insert(pid, from_level);
+        self.count_queue_change(pid, QueueChange::Boost);
+    }
+
+    /// Record a feedback move that left `pid` in `level`. A demotion that
+    /// only returns a boosted process toward the level it was boosted from
+    /// is the process re-settling, so it doesn't disturb convergence.
+    pub fn record_queue_change_to(&mut self, pid: u32, reason: QueueChange, level: usize) {
+        let home = self.boosted_from.get(&pid).copied();
+        let resettling = reason == QueueChange::Demotion && home.is_some_and(|home| level <= home);
+        if !resettling {
+            self.record_queue_change(pid, reason);
+            return;
+        }
+        if self.in_warmup() {
+            return;
+        }
+
+        if home == Some(level) {
+            self.boosted_from.remove(&pid);
+        }
+        self.count_queue_change(pid, reason);
+    }
+
+    fn count_queue_change(&mut self, pid: u32, reason: QueueChange) {
         if let Some(metrics) = self.process_metrics.get_mut(&pid) {
             metrics.queue_changes += 1;
+            match reason {
+                QueueChange::Demotion => metrics.demotions += 1,
+                QueueChange::Promotion => metrics.promotions += 1,
+                QueueChange::Boost => metrics.boosts += 1,
+            }
         }
     }
 
     /// Record execution time for a process
     pub fn record_execution_time(&mut self, pid: u32, time: u64) {
+        if self.in_warmup() {
+            return;
+        }
+
         self.total_execution_time += time;
 
+        match self.process_metrics.get_mut(&pid) {
+            Some(metrics) => metrics.execution_time += time,
+            None => self.unattributed_execution_time += time,
+        }
+        debug_assert!(self.validate().is_ok(), "{:?}", self.validate());
+    }
+
+    /// Credit `ms` of sim time to the bucket for the state `pid` spent it in
+    pub fn record_state_time(&mut self, pid: u32, state: ProcessState, ms: u64) {
+        if self.in_warmup() {
+            return;
+        }
+
         if let Some(metrics) = self.process_metrics.get_mut(&pid) {
-            metrics.execution_time += time;
+            match state {
+                ProcessState::Ready => metrics.ready_time += ms,
+                ProcessState::Running => metrics.running_time += ms,
+                ProcessState::Blocked | ProcessState::Sleeping | ProcessState::Stopped => {
+                    metrics.blocked_time += ms
+                }
+                ProcessState::Terminated => {}
+            }
         }
     }
 
+    /// Record a slice of CPU time in the execution log
+    pub fn record_execution_slice(&mut self, pid: u32, start_tick: u64, duration: u64, queue: usize) {
+        self.execution_log.push(ExecutionSlice { pid, start_tick, duration, queue });
+    }
+
+    /// Get all execution slices for a process in dispatch order
+    pub fn process_timeline(&self, pid: u32) -> Vec<&ExecutionSlice> {
+        self.execution_log.iter().filter(|slice| slice.pid == pid).collect()
+    }
+
     /// Record process termination with metrics
     pub fn record_process_terminated(&mut self, pid: u32, turnaround: u64, response: u64) {
         self.processes_terminated += 1;
+        self.boosted_from.remove(&pid);
 
         if let Some(metrics) = self.process_metrics.get_mut(&pid) {
             metrics.turnaround_time = turnaround;
             metrics.response_time = response;
             metrics.waiting_time = turnaround.saturating_sub(metrics.execution_time);
+            metrics.terminated = true;
             self.total_waiting_time += metrics.waiting_time;
         }
+        debug_assert!(self.validate().is_ok(), "{:?}", self.validate());
+    }
+
+    /// Check the accounting invariant: `total_execution_time` equals the
+    /// per-process execution times plus the unattributed remainder
+    pub fn validate(&self) -> Result<(), String> {
+        let per_process: u64 = self.process_metrics.values().map(|m| m.execution_time).sum();
+        let expected = per_process + self.unattributed_execution_time;
+        if self.total_execution_time != expected {
+            return Err(format!(
+                "total_execution_time is {}ms but processes account for {}ms (+{}ms unattributed)",
+                self.total_execution_time, per_process, self.unattributed_execution_time
+            ));
+        }
+        Ok(())
     }
 
     /// Sample current queue depths
     pub fn sample_queue_depths(&mut self, depths: [usize; 4]) {
-        self.queue_depth_samples.push(depths);
+        if self.in_warmup() {
+            return;
+        }
+
+        if self.queue_depth_samples.len() == self.queue_depth_window {
+            self.queue_depth_samples.pop_front();
+        }
+        self.queue_depth_samples.push_back(depths);
+        for (max, depth) in self.max_queue_depth.iter_mut().zip(depths) {
+            *max = (*max).max(depth);
+        }
+    }
+
+    /// Keep at most `window` queue-depth samples (minimum 1), dropping the
+    /// oldest ones already recorded if needed
+    pub fn set_queue_depth_window(&mut self, window: usize) {
+        self.queue_depth_window = window.max(1);
+        while self.queue_depth_samples.len() > self.queue_depth_window {
+            self.queue_depth_samples.pop_front();
+        }
+    }
+
+    /// Sample which kind of workload occupies each queue. `program_type`
+    /// maps a PID to the program it was launched from, if any.
+    pub fn sample_queue_occupancy<F>(&mut self, queues: &[Vec<u32>; 4], program_type: F)
+    where
+        F: Fn(u32) -> Option<ProgramType>,
+    {
+        if self.in_warmup() {
+            return;
+        }
+
+        let occupancy = &mut self.queue_occupancy;
+        occupancy.samples += 1;
+
+        for (level, pids) in queues.iter().enumerate() {
+            for &pid in pids {
+                match program_type(pid) {
+                    Some(ProgramType::CpuBound | ProgramType::Batch) => occupancy.cpu_bound[level] += 1,
+                    Some(ProgramType::IoBound | ProgramType::Interactive | ProgramType::Realtime) => {
+                        occupancy.io_bound[level] += 1
+                    }
+                    _ => occupancy.other[level] += 1,
+                }
+            }
+        }
+    }
+
+    /// Sample how many processes are blocked, sleeping, and stopped
+    pub fn sample_process_states(&mut self, blocked: usize, sleeping: usize, stopped: usize) {
+        if self.in_warmup() {
+            return;
+        }
+
+        self.state_breakdown.samples += 1;
+        self.state_breakdown.blocked += blocked as u64;
+        self.state_breakdown.sleeping += sleeping as u64;
+        self.state_breakdown.stopped += stopped as u64;
+    }
+
+    /// Credit `time` of useful CPU to the workload class of `program_type`
+    /// (None for a process not running a program)
+    pub fn record_goodput(&mut self, program_type: Option<ProgramType>, time: u64) {
+        if self.in_warmup() {
+            return;
+        }
+
+        let goodput = &mut self.goodput;
+        match program_type {
+            Some(ProgramType::IoBound | ProgramType::Interactive | ProgramType::Realtime) => {
+                goodput.interactive += time
+            }
+            Some(ProgramType::CpuBound | ProgramType::Batch) => goodput.batch += time,
+            _ => goodput.other += time,
+        }
+    }
+
+    /// Record CPU time lost to a context switch
+    pub fn record_switch_overhead(&mut self, time: u64) {
+        if self.in_warmup() {
+            return;
+        }
+
+        self.goodput.switch_overhead += time;
+    }
+
+    /// Record time the idle task (PID 0) ran
+    pub fn record_idle_time(&mut self, time: u64) {
+        if self.in_warmup() {
+            return;
+        }
+
+        self.idle_time += time;
     }
 
     /// Record a tick
     pub fn record_tick(&mut self) {
+        if self.in_warmup() {
+            return;
+        }
+
         self.total_ticks += 1;
+        self.maybe_sample_cpu();
+    }
+
+    /// Start recording CPU utilization at most once per `interval` of wall time
+    pub fn begin_sampling(&mut self, interval: std::time::Duration) {
+        self.sampling_interval = Some(interval);
+        self.last_sample = None;
+    }
+
+    /// Stop recording CPU utilization samples (already recorded points are kept)
+    pub fn end_sampling(&mut self) {
+        self.sampling_interval = None;
+    }
+
+    /// Recorded (elapsed_ms, cpu_utilization) points, oldest first
+    pub fn cpu_series(&self) -> &[(u64, f64)] {
+        &self.cpu_samples
+    }
+
+    fn maybe_sample_cpu(&mut self) {
+        let Some(interval) = self.sampling_interval else {
+            return;
+        };
+
+        let now = std::time::Instant::now();
+        let due = self.last_sample.is_none_or(|last| now.duration_since(last) >= interval);
+        if due {
+            let elapsed_ms = now.duration_since(self.start_time).as_millis() as u64;
+            self.cpu_samples.push((elapsed_ms, self.cpu_utilization()));
+            self.last_sample = Some(now);
+        }
     }
 
     /// Get average turnaround time across all terminated processes
@@ -165,6 +737,27 @@ impl SchedulerStats {
         self.total_waiting_time as f64 / self.processes_terminated as f64
     }
 
+    /// Processes that have not terminated yet, by PID
+    pub fn in_flight_processes(&self) -> Vec<&ProcessMetrics> {
+        self.process_metrics.values().filter(|m| !m.terminated).collect()
+    }
+
+    /// Current turnaround of a live process at sim time `now`
+    pub fn current_turnaround(&self, pid: u32, now: u64) -> Option<u64> {
+        self.process_metrics
+            .get(&pid)
+            .filter(|m| !m.terminated)
+            .map(|m| m.current_turnaround(now))
+    }
+
+    /// Current waiting time of a live process at sim time `now`
+    pub fn current_waiting(&self, pid: u32, now: u64) -> Option<u64> {
+        self.process_metrics
+            .get(&pid)
+            .filter(|m| !m.terminated)
+            .map(|m| m.current_waiting(now))
+    }
+
     /// Get CPU utilization (execution time / total time)
     pub fn cpu_utilization(&self) -> f64 {
         if self.total_ticks == 0 {
@@ -183,7 +776,7 @@ impl SchedulerStats {
         self.total_context_switches as f64 / self.total_ticks as f64
     }
 
-    /// Get average queue depth for specific queue
+    /// Get average queue depth for specific queue over the retained window
     pub fn avg_queue_depth(&self, queue_idx: usize) -> f64 {
         if self.queue_depth_samples.is_empty() {
             return 0.0;
@@ -197,13 +790,178 @@ impl SchedulerStats {
         total as f64 / self.queue_depth_samples.len() as f64
     }
 
+    /// Average number of queued (runnable) processes across all levels
+    /// over the retained sample window
+    pub fn load_average(&self) -> f64 {
+        if self.queue_depth_samples.is_empty() {
+            return 0.0;
+        }
+
+        let total: usize = self.queue_depth_samples.iter().map(|sample| sample.iter().sum::<usize>()).sum();
+        total as f64 / self.queue_depth_samples.len() as f64
+    }
+
+    /// The queue with the highest average sampled depth and that average.
+    /// Ties go to the higher-priority queue; None before any samples.
+    pub fn busiest_queue(&self) -> Option<(usize, f64)> {
+        if self.queue_depth_samples.is_empty() {
+            return None;
+        }
+
+        let mut busiest = (0, self.avg_queue_depth(0));
+        for queue_idx in 1..4 {
+            let depth = self.avg_queue_depth(queue_idx);
+            if depth > busiest.1 {
+                busiest = (queue_idx, depth);
+            }
+        }
+        Some(busiest)
+    }
+
+    /// One-line summary of `busiest_queue`
+    pub fn busiest_queue_insight(&self) -> String {
+        match self.busiest_queue() {
+            Some((queue_idx, depth)) => format!("Most contended: Q{}, avg {:.1} procs", queue_idx, depth),
+            None => "Most contended: no queue-depth samples yet".to_string(),
+        }
+    }
+
+    /// Cycles run since any process last changed queue
+    pub fn cycles_since_queue_change(&self) -> u64 {
+        self.cycles_seen - self.last_queue_change_cycle
+    }
+
+    /// True once processes have settled into their queues: no demotion,
+    /// promotion or targeted boost for the last `window` cycles. Periodic
+    /// boosts and the re-sinking after them are ignored.
+    pub fn is_converged(&self, window: u64) -> bool {
+        window > 0 && self.cycles_since_queue_change() >= window
+    }
+
+    /// One-line summary of `is_converged`
+    pub fn convergence_insight(&self, window: u64) -> String {
+        let quiet = self.cycles_since_queue_change();
+        if self.is_converged(window) {
+            format!("Converged: no queue changes for {} cycles", quiet)
+        } else {
+            format!("Not converged: {} of {} quiet cycles needed", quiet, window)
+        }
+    }
+
+    /// Render a queue's sampled depths, oldest first, as a sparkline scaled
+    /// to the deepest retained sample. Empty if nothing has been sampled.
+    pub fn queue_sparkline(&self, queue_idx: usize) -> String {
+        let depths: Vec<usize> = self.queue_depth_samples
+            .iter()
+            .map(|sample| sample[queue_idx])
+            .collect();
+        let max = depths.iter().copied().max().unwrap_or(0);
+        let top = SPARK_LEVELS.len() - 1;
+
+        depths
+            .iter()
+            .map(|&depth| SPARK_LEVELS[(depth * top).checked_div(max).unwrap_or(0)])
+            .collect()
+    }
+
+    /// CPU time (ms) each process spent running in each queue, summed from
+    /// the execution log
+    pub fn queue_time_by_process(&self) -> BTreeMap<u32, [u64; 4]> {
+        let mut grid: BTreeMap<u32, [u64; 4]> = BTreeMap::new();
+        for slice in &self.execution_log {
+            let row = grid.entry(slice.pid).or_default();
+            row[slice.queue.min(3)] += slice.duration;
+        }
+        grid
+    }
+
+    /// Processes × queues grid of running time. Each row is shaded relative
+    /// to that process's busiest queue, so it reads as where the process
+    /// spent its CPU time.
+    pub fn queue_heatmap(&self, levels: usize) -> String {
+        let grid = self.queue_time_by_process();
+        let levels = levels.min(4);
+        let top = HEAT_LEVELS.len() - 1;
+
+        let mut report = String::from("Queue Time Heatmap (ms running per queue):\n");
+        report.push_str("─────────────────────────────────────────────────────────────\n");
+        report.push_str("PID ");
+        for level in 0..levels {
+            report.push_str(&format!(" {:^10}", format!("Q{}", level)));
+        }
+        report.push('\n');
+
+        for (pid, row) in &grid {
+            let max = row.iter().copied().max().unwrap_or(0);
+            report.push_str(&format!("{:<4}", pid));
+            for &ms in &row[..levels] {
+                // Any time at all gets at least the lightest shade
+                let shade = match ms {
+                    0 => HEAT_LEVELS[0],
+                    _ => HEAT_LEVELS[((ms * top as u64).div_ceil(max) as usize).max(1)],
+                };
+                report.push_str(&format!(" {}{}{} {:>6}", shade, shade, shade, ms));
+            }
+            report.push('\n');
+        }
+
+        report
+    }
+
     /// Get process-specific metrics
     pub fn get_process_metrics(&self, pid: u32) -> Option<&ProcessMetrics> {
         self.process_metrics.get(&pid)
     }
 
+    /// Per-process metrics ordered by `key`. PID sorts ascending, every
+    /// other key descending so the worst performer comes first.
+    pub fn sorted_process_metrics(&self, key: MetricSortKey) -> Vec<&ProcessMetrics> {
+        let mut metrics: Vec<&ProcessMetrics> = self.process_metrics.values().collect();
+
+        match key {
+            MetricSortKey::Pid => {}
+            MetricSortKey::Turnaround => metrics.sort_by_key(|m| std::cmp::Reverse(m.turnaround_time)),
+            MetricSortKey::Waiting => metrics.sort_by_key(|m| std::cmp::Reverse(m.waiting_time)),
+            MetricSortKey::Execution => metrics.sort_by_key(|m| std::cmp::Reverse(m.execution_time)),
+        }
+
+        metrics
+    }
+
+    /// Totals, averages and every process's metrics as a JSON object
+    pub fn to_json(&self) -> String {
+        let export = JsonStats {
+            totals: JsonTotals {
+                ticks: self.total_ticks,
+                context_switches: self.total_context_switches,
+                switches_per_queue: self.switches_per_queue,
+                processes_created: self.processes_created,
+                processes_terminated: self.processes_terminated,
+                execution_time: self.total_execution_time,
+                waiting_time: self.total_waiting_time,
+                idle_time: self.idle_time,
+            },
+            averages: JsonAverages {
+                turnaround_time: self.avg_turnaround_time(),
+                response_time: self.avg_response_time(),
+                waiting_time: self.avg_waiting_time(),
+                cpu_utilization: self.cpu_utilization(),
+                context_switch_rate: self.context_switch_rate(),
+                queue_depth: std::array::from_fn(|q| self.avg_queue_depth(q)),
+                max_queue_depth: self.max_queue_depth,
+            },
+            processes: self.process_metrics.values().collect(),
+        };
+        serde_json::to_string_pretty(&export).expect("stats serialize to JSON")
+    }
+
     /// Generate summary report
     pub fn summary_report(&self) -> String {
+        self.summary_report_sorted(MetricSortKey::Pid)
+    }
+
+    /// Generate summary report with the per-process table ordered by `key`
+    pub fn summary_report_sorted(&self, key: MetricSortKey) -> String {
         let mut report = String::from(
             "╔════════════════════════════════════════════════════════════════╗\n\
              ║             SCHEDULER METRICS AND STATISTICS                  ║\n\
@@ -216,7 +974,8 @@ impl SchedulerStats {
         report.push_str(&format!("Total Ticks:              {}\n", self.total_ticks));
         report.push_str(&format!("Processes Created:        {}\n", self.processes_created));
         report.push_str(&format!("Processes Terminated:     {}\n", self.processes_terminated));
-        report.push_str(&format!("Total Context Switches:   {}\n\n", self.total_context_switches));
+        report.push_str(&format!("Total Context Switches:   {}\n", self.total_context_switches));
+        report.push_str(&format!("Warm-up Cycles Excluded:  {}\n\n", self.warmup_cycles.min(self.cycles_seen as u32)));
 
         // Performance Metrics
         report.push_str("Performance Metrics:\n");
@@ -224,7 +983,8 @@ impl SchedulerStats {
         report.push_str(&format!("CPU Utilization:          {:.2}%\n", self.cpu_utilization()));
         report.push_str(&format!("Context Switch Rate:      {:.4} per tick\n", self.context_switch_rate()));
         report.push_str(&format!("Total Execution Time:     {}ms\n", self.total_execution_time));
-        report.push_str(&format!("Total Waiting Time:       {}ms\n\n", self.total_waiting_time));
+        report.push_str(&format!("Total Waiting Time:       {}ms\n", self.total_waiting_time));
+        report.push_str(&format!("Idle Time (PID 0):        {}ms\n\n", self.idle_time));
 
         // Average Metrics
         report.push_str("Average Metrics (Terminated Processes):\n");
@@ -234,12 +994,25 @@ impl SchedulerStats {
         report.push_str(&format!("Avg Waiting Time:         {:.2}ms\n\n", self.avg_waiting_time()));
 
         // Queue Analysis
-        report.push_str("Queue Depth Analysis:\n");
+        report.push_str(&format!("Queue Depth Analysis (last {} samples):\n", self.queue_depth_samples.len()));
+        report.push_str("─────────────────────────────────────────────────────────────\n");
+        for queue in 0..4 {
+            report.push_str(&format!(
+                "Avg Q{} Depth:             {:.2} (peak {})\n",
+                queue,
+                self.avg_queue_depth(queue),
+                self.max_queue_depth[queue]
+            ));
+        }
+        report.push_str(&format!("{}\n", self.busiest_queue_insight()));
+        report.push_str(&format!("{}\n\n", self.convergence_insight(DEFAULT_CONVERGENCE_WINDOW)));
+
+        // Non-runnable processes
+        report.push_str("State Breakdown (avg per cycle):\n");
         report.push_str("─────────────────────────────────────────────────────────────\n");
-        report.push_str(&format!("Avg Q0 Depth:             {:.2}\n", self.avg_queue_depth(0)));
-        report.push_str(&format!("Avg Q1 Depth:             {:.2}\n", self.avg_queue_depth(1)));
-        report.push_str(&format!("Avg Q2 Depth:             {:.2}\n", self.avg_queue_depth(2)));
-        report.push_str(&format!("Avg Q3 Depth:             {:.2}\n\n", self.avg_queue_depth(3)));
+        report.push_str(&format!("Blocked:                  {:.2}\n", self.state_breakdown.avg_blocked()));
+        report.push_str(&format!("Sleeping:                 {:.2}\n", self.state_breakdown.avg_sleeping()));
+        report.push_str(&format!("Stopped:                  {:.2}\n\n", self.state_breakdown.avg_stopped()));
 
         // Per-Process Metrics
         if !self.process_metrics.is_empty() {
@@ -248,37 +1021,185 @@ impl SchedulerStats {
             report.push_str("PID  Turnaround  Response  Waiting  Execution  Ctx-Sw  Q-Changes\n");
             report.push_str("─────────────────────────────────────────────────────────────\n");
 
-            for pid in self.process_metrics.keys() {
-                if let Some(metrics) = self.process_metrics.get(pid) {
-                    report.push_str(&format!(
-                        "{:<4} {:<10} {:<9} {:<8} {:<10} {:<7} {:<10}\n",
-                        metrics.pid,
-                        format!("{}ms", metrics.turnaround_time),
-                        format!("{}ms", metrics.response_time),
-                        format!("{}ms", metrics.waiting_time),
-                        format!("{}ms", metrics.execution_time),
-                        metrics.context_switches,
-                        metrics.queue_changes,
-                    ));
-                }
+            for metrics in self.sorted_process_metrics(key) {
+                report.push_str(&format!(
+                    "{:<4} {:<10} {:<9} {:<8} {:<10} {:<7} {:<10}\n",
+                    metrics.pid,
+                    format!("{}ms", metrics.turnaround_time),
+                    format!("{}ms", metrics.response_time),
+                    format!("{}ms", metrics.waiting_time),
+                    format!("{}ms", metrics.execution_time),
+                    metrics.context_switches,
+                    metrics.queue_changes,
+                ));
             }
         }
 
-        report.push_str("\n");
+        report.push('\n');
+        report
+    }
+
+    /// Context switches and CPU time per process from sim time `since`
+    /// onward. Slices that straddle `since` count only their later part.
+    pub fn window_report(&self, since: u64) -> String {
+        let mut window: BTreeMap<u32, (u64, u64)> = BTreeMap::new();
+        for &(_, pid) in self.switch_log.iter().filter(|(tick, _)| *tick >= since) {
+            window.entry(pid).or_default().0 += 1;
+        }
+        for slice in &self.execution_log {
+            let end = slice.start_tick + slice.duration;
+            if end > since {
+                window.entry(slice.pid).or_default().1 += end - slice.start_tick.max(since);
+            }
+        }
+
+        let switches: u64 = window.values().map(|(switches, _)| switches).sum();
+        let execution: u64 = window.values().map(|(_, execution)| execution).sum();
+        let mut report = format!("Metrics Since Tick {}:\n", since);
+        report.push_str("─────────────────────────────────────────────────────────────\n");
+        report.push_str(&format!("Context Switches:         {}\n", switches));
+        report.push_str(&format!("Execution Time:           {}ms\n\n", execution));
+        report.push_str("PID  Ctx-Sw  Execution\n");
+
+        for (pid, (switches, execution)) in window {
+            report.push_str(&format!("{:<4} {:<7} {:<10}\n", pid, switches, format!("{}ms", execution)));
+        }
+
+        report
+    }
+
+    /// Progress of processes still running at sim time `now`
+    pub fn in_flight_report(&self, now: u64) -> String {
+        let live = self.in_flight_processes();
+        let mut report = format!("In-Flight Processes ({} at {}ms):\n", live.len(), now);
+        report.push_str("─────────────────────────────────────────────────────────────\n");
+        report.push_str("PID  Turnaround  Waiting  Execution\n");
+
+        for metrics in live {
+            report.push_str(&format!(
+                "{:<4} {:<11} {:<8} {:<10}\n",
+                metrics.pid,
+                format!("{}ms", metrics.current_turnaround(now)),
+                format!("{}ms", metrics.current_waiting(now)),
+                format!("{}ms", metrics.execution_time),
+            ));
+        }
+
+        report
+    }
+
+    /// Average mix of CPU-bound and I/O-bound processes in each queue
+    pub fn occupancy_report(&self) -> String {
+        let occupancy = &self.queue_occupancy;
+        let mut report = format!(
+            "Queue Occupancy by Workload ({} samples):\n\
+             ─────────────────────────────────────────────────────────────\n\
+             QUEUE  CPU-BOUND  I/O-BOUND  OTHER\n",
+            occupancy.samples
+        );
+
+        for level in 0..4 {
+            report.push_str(&format!(
+                "Q{}     {:>8.1}%  {:>8.1}%  {:>5.1}%\n",
+                level,
+                occupancy.cpu_fraction(level) * 100.0,
+                occupancy.io_fraction(level) * 100.0,
+                occupancy.other_fraction(level) * 100.0
+            ));
+        }
+
+        report
+    }
+
+    /// Percent of CPU time that was useful work, idle, and switch overhead,
+    /// in that order; all zero before any time is accounted
+    pub fn cpu_split(&self) -> (f64, f64, f64) {
+        Self::split_percentages(self.goodput.useful(), self.idle_time, self.goodput.switch_overhead)
+    }
+
+    /// `useful`, `idle` and `overhead` as percentages of their sum; all zero
+    /// if the sum is zero
+    pub fn split_percentages(useful: u64, idle: u64, overhead: u64) -> (f64, f64, f64) {
+        let total = useful + idle + overhead;
+        if total == 0 {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let pct = |time: u64| time as f64 / total as f64 * 100.0;
+        (pct(useful), pct(idle), pct(overhead))
+    }
+
+    /// Share of all CPU time (useful, switch overhead and idle) each
+    /// workload class got
+    pub fn goodput_report(&self) -> String {
+        let goodput = &self.goodput;
+        let total = goodput.useful() + goodput.switch_overhead + self.idle_time;
+        let share = |time: u64| if total == 0 { 0.0 } else { time as f64 / total as f64 * 100.0 };
+
+        let mut report = format!(
+            "CPU Goodput by Workload ({}ms total CPU):\n\
+             ─────────────────────────────────────────────────────────────\n\
+             CLASS            CPU_TIME   SHARE\n",
+            total
+        );
+        for (label, time) in [
+            ("interactive", goodput.interactive),
+            ("batch", goodput.batch),
+            ("other", goodput.other),
+            ("switch overhead", goodput.switch_overhead),
+            ("idle", self.idle_time),
+        ] {
+            report.push_str(&format!("{:<16} {:>6}ms  {:>5.1}%\n", label, time, share(time)));
+        }
+        report.push_str(&format!("Goodput: {:.1}% of CPU did useful work\n", share(goodput.useful())));
         report
     }
 
+    /// Zero one process's metrics. Its arrival tick and terminated flag are
+    /// kept so turnaround is still measured from creation. System totals are
+    /// left alone: they describe the whole run, not the processes currently
+    /// tracked, so the zeroed execution time becomes unattributed. Returns
+    /// false if the PID has no metrics.
+    pub fn reset_process(&mut self, pid: u32) -> bool {
+        match self.process_metrics.get_mut(&pid) {
+            Some(metrics) => {
+                self.unattributed_execution_time += metrics.execution_time;
+                *metrics = ProcessMetrics {
+                    arrival_tick: metrics.arrival_tick,
+                    terminated: metrics.terminated,
+                    ..ProcessMetrics::new(pid)
+                };
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Reset all statistics
     pub fn reset(&mut self) {
         self.process_metrics.clear();
         self.total_context_switches = 0;
+        self.switches_per_queue = [0; 4];
         self.total_ticks = 0;
         self.processes_created = 0;
         self.processes_terminated = 0;
         self.total_execution_time = 0;
+        self.unattributed_execution_time = 0;
         self.total_waiting_time = 0;
+        self.idle_time = 0;
         self.queue_depth_samples.clear();
+        self.max_queue_depth = [0; 4];
+        self.queue_occupancy = QueueOccupancy::default();
+        self.state_breakdown = StateBreakdown::default();
+        self.goodput = Goodput::default();
+        self.execution_log.clear();
+        self.switch_log.clear();
         self.start_time = std::time::Instant::now();
+        self.last_sample = None;
+        self.cpu_samples.clear();
+        self.cycles_seen = 0;
+        self.last_queue_change_cycle = 0;
+        self.boosted_from.clear();
     }
 }
 
@@ -310,6 +1231,84 @@ mod tests {
         assert!(stats.process_metrics.contains_key(&2));
     }
 
+    #[test]
+    fn test_process_metrics_iterate_in_pid_order() {
+        let mut stats = SchedulerStats::new();
+        for pid in [5, 1, 3, 2] {
+            stats.record_process_created(pid);
+        }
+
+        let pids: Vec<u32> = stats.process_metrics.keys().copied().collect();
+        assert_eq!(pids, vec![1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_converged_after_quiet_window() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        assert!(!stats.is_converged(3));
+
+        stats.record_cycle();
+        stats.record_queue_change(1, QueueChange::Demotion);
+        for _ in 0..2 {
+            stats.record_cycle();
+        }
+        assert!(!stats.is_converged(3));
+        assert_eq!(stats.convergence_insight(3), "Not converged: 2 of 3 quiet cycles needed");
+
+        stats.record_cycle();
+        assert!(stats.is_converged(3));
+        assert_eq!(stats.convergence_insight(3), "Converged: no queue changes for 3 cycles");
+    }
+
+    #[test]
+    fn test_global_boost_and_resinking_keep_convergence() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        for _ in 0..5 {
+            stats.record_cycle();
+        }
+
+        // Boosted from Q3, then sinks straight back: still quiet
+        stats.record_global_boost(1, 3);
+        for level in 1..=3 {
+            stats.record_cycle();
+            stats.record_queue_change_to(1, QueueChange::Demotion, level);
+        }
+        assert_eq!(stats.cycles_since_queue_change(), 8);
+        assert_eq!(stats.get_process_metrics(1).unwrap().queue_changes, 4);
+        assert!(stats.boosted_from.is_empty());
+
+        // Boosted from Q2; sinking to Q1 is re-settling, but a promotion
+        // back up is a real change
+        stats.record_global_boost(1, 2);
+        stats.record_cycle();
+        stats.record_queue_change_to(1, QueueChange::Demotion, 1);
+        assert_eq!(stats.cycles_since_queue_change(), 9);
+        stats.record_cycle();
+        stats.record_queue_change_to(1, QueueChange::Promotion, 0);
+        assert_eq!(stats.cycles_since_queue_change(), 0);
+        assert!(stats.boosted_from.is_empty());
+    }
+
+    #[test]
+    fn test_window_report_counts_from_tick() {
+        let mut stats = SchedulerStats::new();
+        stats.record_context_switch_at(1, 0);
+        stats.record_execution_slice(1, 0, 20, 0);
+        stats.record_context_switch_at(2, 20);
+        stats.record_execution_slice(2, 20, 20, 0);
+
+        // PID 1's slice ends before tick 30 and PID 2's straddles it
+        let report = stats.window_report(30);
+        assert!(report.contains("Context Switches:         0\n"), "{}", report);
+        assert!(report.contains("Execution Time:           10ms\n"), "{}", report);
+
+        let report = stats.window_report(10);
+        assert!(report.contains("Context Switches:         1\n"), "{}", report);
+        assert!(report.contains("Execution Time:           30ms\n"), "{}", report);
+    }
+
     #[test]
     fn test_record_context_switch() {
         let mut stats = SchedulerStats::new();
@@ -385,6 +1384,146 @@ mod tests {
         assert_eq!(avg, 15.0);
     }
 
+    #[test]
+    fn test_state_time_buckets() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        stats.record_state_time(1, ProcessState::Ready, 5);
+        stats.record_state_time(1, ProcessState::Running, 8);
+        stats.record_state_time(1, ProcessState::Sleeping, 3);
+        stats.record_state_time(1, ProcessState::Blocked, 4);
+        stats.record_state_time(1, ProcessState::Terminated, 100);
+
+        let metrics = &stats.process_metrics[&1];
+        assert_eq!((metrics.ready_time, metrics.running_time, metrics.blocked_time), (5, 8, 7));
+    }
+
+    #[test]
+    fn test_warmup_cycles_excluded_from_totals() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        stats.set_warmup(2);
+
+        for _ in 0..3 {
+            stats.record_cycle();
+            stats.record_context_switch(1);
+            stats.record_queue_switch(0);
+            stats.record_execution_time(1, 10);
+            stats.record_tick();
+        }
+
+        assert!(!stats.in_warmup());
+        assert_eq!(stats.total_context_switches, 1);
+        assert_eq!(stats.switches_per_queue[0], 1);
+        assert_eq!(stats.total_execution_time, 10);
+        assert_eq!(stats.total_ticks, 1);
+        assert_eq!(stats.process_metrics[&1].execution_time, 10);
+    }
+
+    #[test]
+    fn test_in_flight_metrics_use_sim_clock() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created_at(2, 100);
+        stats.record_process_created_at(3, 100);
+        stats.record_execution_time(2, 30);
+        stats.record_process_terminated(3, 50, 0);
+
+        assert_eq!(stats.current_turnaround(2, 150), Some(50));
+        assert_eq!(stats.current_waiting(2, 150), Some(20));
+        assert_eq!(stats.current_turnaround(3, 150), None);
+        assert_eq!(stats.in_flight_processes().len(), 1);
+        assert!(stats.in_flight_report(150).contains("In-Flight Processes (1 at 150ms)"));
+    }
+
+    #[test]
+    fn test_sparkline_rises_with_depth() {
+        let mut stats = SchedulerStats::new();
+        assert_eq!(stats.queue_sparkline(0), "");
+
+        for depth in 0..8 {
+            stats.sample_queue_depths([depth, 0, 0, 0]);
+        }
+        assert_eq!(stats.queue_sparkline(0), "▁▂▃▄▅▆▇█");
+        assert_eq!(stats.queue_sparkline(1), "▁▁▁▁▁▁▁▁");
+    }
+
+    #[test]
+    fn test_queue_depth_window_drops_oldest() {
+        let mut stats = SchedulerStats::new();
+        stats.set_queue_depth_window(2);
+        stats.sample_queue_depths([10, 0, 0, 0]);
+        stats.sample_queue_depths([2, 0, 0, 0]);
+        stats.sample_queue_depths([4, 0, 0, 0]);
+
+        assert_eq!(stats.queue_depth_samples.len(), 2);
+        assert_eq!(stats.queue_depth_samples.front(), Some(&[2, 0, 0, 0]));
+        assert_eq!(stats.avg_queue_depth(0), 3.0);
+
+        stats.set_queue_depth_window(1);
+        assert_eq!(stats.avg_queue_depth(0), 4.0);
+    }
+
+    #[test]
+    fn test_queue_occupancy_fractions() {
+        let mut stats = SchedulerStats::new();
+        let queues = [vec![2, 3], vec![], vec![], vec![4]];
+        let program_type = |pid| match pid {
+            2 => Some(ProgramType::Interactive),
+            4 => Some(ProgramType::CpuBound),
+            _ => None,
+        };
+        stats.sample_queue_occupancy(&queues, program_type);
+        stats.sample_queue_occupancy(&queues, program_type);
+
+        let occupancy = stats.queue_occupancy;
+        assert_eq!(occupancy.samples, 2);
+        assert_eq!(occupancy.io_fraction(0), 0.5);
+        assert_eq!(occupancy.other_fraction(0), 0.5);
+        assert_eq!(occupancy.cpu_fraction(3), 1.0);
+        assert_eq!(occupancy.cpu_fraction(1), 0.0);
+    }
+
+    #[test]
+    fn test_max_queue_depth_catches_transient_spike() {
+        let mut stats = SchedulerStats::new();
+        stats.set_queue_depth_window(4);
+        stats.sample_queue_depths([1, 0, 0, 10]);
+        for _ in 0..20 {
+            stats.sample_queue_depths([1, 0, 0, 1]);
+        }
+
+        // The spike has aged out of the window and barely moved the average
+        assert_eq!(stats.avg_queue_depth(3), 1.0);
+        assert_eq!(stats.max_queue_depth, [1, 0, 0, 10]);
+        assert!(stats.summary_report().contains("Avg Q3 Depth:             1.00 (peak 10)"));
+
+        stats.reset();
+        assert_eq!(stats.max_queue_depth, [0; 4]);
+    }
+
+    #[test]
+    fn test_goodput_by_workload_class() {
+        let mut stats = SchedulerStats::new();
+        stats.record_goodput(Some(ProgramType::Interactive), 30);
+        stats.record_goodput(Some(ProgramType::IoBound), 10);
+        stats.record_goodput(Some(ProgramType::Batch), 20);
+        stats.record_goodput(None, 15);
+        stats.record_switch_overhead(5);
+        stats.record_idle_time(20);
+
+        assert_eq!(stats.goodput.interactive, 40);
+        assert_eq!(stats.goodput.batch, 20);
+        assert_eq!(stats.goodput.useful(), 75);
+
+        let report = stats.goodput_report();
+        assert!(report.contains("(100ms total CPU)"));
+        assert!(report.contains("interactive          40ms   40.0%"), "{}", report);
+        assert!(report.contains("switch overhead       5ms    5.0%"), "{}", report);
+        assert!(report.contains("Goodput: 75.0% of CPU did useful work"));
+        assert_eq!(stats.cpu_split(), (75.0, 20.0, 5.0));
+        assert_eq!(SchedulerStats::new().cpu_split(), (0.0, 0.0, 0.0));
+    }
+
     #[test]
     fn test_avg_queue_depth() {
         let mut stats = SchedulerStats::new();
@@ -405,16 +1544,59 @@ mod tests {
         assert_eq!(rate, 0.25);
     }
 
+    #[test]
+    fn test_record_queue_switch() {
+        let mut stats = SchedulerStats::new();
+        stats.record_queue_switch(0);
+        stats.record_queue_switch(0);
+        stats.record_queue_switch(3);
+        stats.record_queue_switch(7);
+
+        assert_eq!(stats.switches_per_queue, [2, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_process_timeline() {
+        let mut stats = SchedulerStats::new();
+        stats.record_execution_slice(1, 0, 64, 3);
+        stats.record_execution_slice(2, 64, 8, 0);
+        stats.record_execution_slice(1, 72, 32, 2);
+
+        let timeline = stats.process_timeline(1);
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].start_tick, 0);
+        assert_eq!(timeline[1].start_tick, 72);
+        assert_eq!(timeline[1].queue, 2);
+    }
+
     #[test]
     fn test_record_queue_change() {
         let mut stats = SchedulerStats::new();
         stats.record_process_created(1);
 
-        stats.record_queue_change(1);
-        stats.record_queue_change(1);
-        stats.record_queue_change(1);
+        stats.record_queue_change(1, QueueChange::Demotion);
+        stats.record_queue_change(1, QueueChange::Demotion);
+        stats.record_queue_change(1, QueueChange::Boost);
+
+        let metrics = stats.process_metrics.get(&1).unwrap();
+        assert_eq!(metrics.queue_changes, 3);
+        assert_eq!((metrics.demotions, metrics.promotions, metrics.boosts), (2, 0, 1));
+    }
+
+    #[test]
+    fn test_cpu_sampling_is_opt_in() {
+        let mut stats = SchedulerStats::new();
+        stats.record_tick();
+        assert!(stats.cpu_series().is_empty());
+
+        stats.begin_sampling(std::time::Duration::ZERO);
+        stats.record_tick();
+        stats.record_tick();
+        assert_eq!(stats.cpu_series().len(), 2);
 
-        assert_eq!(stats.process_metrics.get(&1).unwrap().queue_changes, 3);
+        stats.end_sampling();
+        stats.record_tick();
+        assert_eq!(stats.cpu_series().len(), 2);
     }
 
     #[test]
@@ -430,6 +1612,127 @@ mod tests {
         assert!(stats.process_metrics.is_empty());
     }
 
+    #[test]
+    fn test_busiest_queue() {
+        let mut stats = SchedulerStats::new();
+        assert_eq!(stats.busiest_queue(), None);
+
+        stats.sample_queue_depths([1, 0, 2, 4]);
+        stats.sample_queue_depths([0, 1, 1, 5]);
+        stats.sample_queue_depths([1, 0, 0, 3]);
+
+        assert_eq!(stats.busiest_queue(), Some((3, 4.0)));
+        assert_eq!(stats.busiest_queue_insight(), "Most contended: Q3, avg 4.0 procs");
+        assert!(stats.summary_report().contains("Most contended: Q3, avg 4.0 procs"));
+    }
+
+    #[test]
+    fn test_load_average_sums_levels() {
+        let mut stats = SchedulerStats::new();
+        assert_eq!(stats.load_average(), 0.0);
+
+        stats.sample_queue_depths([1, 0, 2, 1]);
+        stats.sample_queue_depths([0, 1, 0, 1]);
+        assert_eq!(stats.load_average(), 3.0);
+    }
+
+    #[test]
+    fn test_heatmap_shades_only_queues_used() {
+        let mut stats = SchedulerStats::new();
+        stats.record_execution_slice(2, 0, 64, 3);
+        stats.record_execution_slice(2, 64, 64, 3);
+        stats.record_execution_slice(3, 128, 8, 0);
+        stats.record_execution_slice(3, 136, 32, 2);
+
+        assert_eq!(stats.queue_time_by_process()[&2], [0, 0, 0, 128]);
+        let heatmap = stats.queue_heatmap(4);
+        let row = heatmap.lines().find(|line| line.starts_with("2 ")).unwrap();
+        assert_eq!(row, "2             0          0          0 ███    128");
+        let row = heatmap.lines().find(|line| line.starts_with("3 ")).unwrap();
+        assert!(row.contains("░░░      8") && row.contains("███     32"));
+    }
+
+    #[test]
+    fn test_execution_time_invariant_holds() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        stats.record_process_created(2);
+        stats.record_execution_time(1, 40);
+        stats.record_execution_time(2, 25);
+        stats.record_execution_time(7, 5); // never created
+        stats.record_process_terminated(2, 80, 0);
+        stats.reset_process(1);
+
+        assert_eq!(stats.validate(), Ok(()));
+        assert_eq!(stats.unattributed_execution_time, 45);
+    }
+
+    #[test]
+    fn test_validate_detects_drift() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        stats.record_execution_time(1, 40);
+
+        stats.process_metrics.get_mut(&1).unwrap().execution_time += 3;
+        let err = stats.validate().unwrap_err();
+        assert!(err.contains("40ms but processes account for 43ms"), "{}", err);
+    }
+
+    #[test]
+    fn test_to_json_parses_with_every_process() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        stats.record_process_created(2);
+        stats.record_process_created(3);
+        stats.record_execution_time(2, 30);
+
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+        let processes = json["processes"].as_array().unwrap();
+        assert_eq!(processes.len(), 3);
+        assert_eq!(processes[1]["pid"], 2);
+        assert_eq!(processes[1]["execution_time"], 30);
+        assert_eq!(json["totals"]["processes_created"], 3);
+        assert!(json["averages"]["queue_depth"].is_array());
+    }
+
+    #[test]
+    fn test_reset_one_process() {
+        let mut stats = SchedulerStats::new();
+        stats.record_process_created(1);
+        stats.record_process_created(2);
+        stats.record_execution_time(1, 40);
+        stats.record_execution_time(2, 25);
+        stats.record_context_switch(1);
+
+        assert!(stats.reset_process(1));
+        assert!(!stats.reset_process(9));
+
+        let reset = stats.get_process_metrics(1).unwrap();
+        assert_eq!((reset.execution_time, reset.context_switches), (0, 0));
+        assert_eq!(stats.get_process_metrics(2).unwrap().execution_time, 25);
+        assert_eq!(stats.total_execution_time, 65);
+    }
+
+    #[test]
+    fn test_sorted_process_metrics() {
+        let mut stats = SchedulerStats::new();
+        for pid in [3, 1, 2] {
+            stats.record_process_created(pid);
+        }
+        stats.record_process_terminated(1, 100, 0);
+        stats.record_process_terminated(2, 300, 0);
+        stats.record_process_terminated(3, 200, 0);
+
+        let by_pid: Vec<u32> = stats.sorted_process_metrics(MetricSortKey::Pid).iter().map(|m| m.pid).collect();
+        assert_eq!(by_pid, vec![1, 2, 3]);
+
+        let by_waiting: Vec<u32> = stats.sorted_process_metrics(MetricSortKey::Waiting).iter().map(|m| m.pid).collect();
+        assert_eq!(by_waiting, vec![2, 3, 1]);
+
+        assert_eq!("WAITING".parse::<MetricSortKey>(), Ok(MetricSortKey::Waiting));
+        assert!("bogus".parse::<MetricSortKey>().is_err());
+    }
+
     #[test]
     fn test_summary_report() {
         let mut stats = SchedulerStats::new();
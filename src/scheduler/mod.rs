@@ -1,15 +1,79 @@
 // src/scheduler/mod.rs - Restructured with Metrics, Test Suite, and Programs
 
+pub mod bench;
 pub mod metrics;
 pub mod test_suite;
 pub mod programs;
+pub mod replay;
 
-pub use metrics::{SchedulerStats, ProcessMetrics};
+pub use metrics::{SchedulerStats, ProcessMetrics, ExecutionSlice, MetricSortKey, QueueChange, StateBreakdown, SwitchKind};
 pub use test_suite::TestResults;
 pub use programs::{Program, ProgramRegistry, ProgramType};
 
 use std::collections::VecDeque;
 
+use serde::Deserialize;
+
+use crate::process::SchedClass;
+
+/// Returned by `ticks_until_boost` when the priority boost is disabled
+pub const BOOST_DISABLED: u32 = u32::MAX;
+
+/// Most queue levels a scheduler can be configured with
+pub const MAX_LEVELS: usize = 4;
+
+/// PID of the idle task, dispatched when no queued process is runnable.
+/// It never sits in a queue and has no entry in the process table.
+pub const IDLE_PID: u32 = 0;
+
+/// Quantum outcomes remembered per process
+pub const QUANTUM_HISTORY_LEN: usize = 8;
+
+/// Highest nice value. Priorities stop at 3; the two values past that give
+/// automatic penalties room to act on a process at the default priority.
+pub const MAX_NICE: u8 = 5;
+
+/// How a process's last time slice ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuantumOutcome {
+    /// Ran until the quantum expired
+    Full,
+    /// Gave up the CPU before the quantum expired
+    Yielded,
+}
+
+impl QuantumOutcome {
+    pub fn symbol(&self) -> char {
+        match self {
+            QuantumOutcome::Full => 'F',
+            QuantumOutcome::Yielded => 'Y',
+        }
+    }
+}
+
+/// Shape of an MLFQ scheduler: one quantum per level, highest priority first
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    pub quantums: Vec<u32>,
+    pub boost_interval: u32,
+    /// Consecutive full quanta required before a demotion
+    pub demote_after: usize,
+    /// Smallest quantum any level grants (0 = no floor)
+    pub min_quantum: u32,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig {
+            quantums: vec![8, 16, 32, 64],
+            boost_interval: 100,
+            demote_after: 1,
+            min_quantum: 0,
+        }
+    }
+}
+
 /// Multi-Level Feedback Queue (MLFQ) Scheduler
 ///
 /// A sophisticated CPU scheduler that uses multiple priority queues.
@@ -18,47 +82,313 @@ use std::collections::VecDeque;
 #[derive(Debug, Clone)]
 pub struct MLFQScheduler {
     queues: [VecDeque<u32>; 4],
+    levels: usize,
     time_quantums: [u32; 4],
     process_queue_map: std::collections::HashMap<u32, usize>,
     boost_interval: u32,
     current_ticks: u32,
     current_pid: Option<u32>,
     time_remaining: u32,
+    min_share: f64,
+    share_window: u32,
+    window_elapsed: u32,
+    window_usage: std::collections::HashMap<u32, u32>,
+    nice_values: std::collections::HashMap<u32, u8>,
+    sched_classes: std::collections::HashMap<u32, SchedClass>,
+    level_allotments: [u32; 4],
+    level_usage: std::collections::HashMap<u32, u32>,
+    history: std::collections::HashMap<u32, VecDeque<QuantumOutcome>>,
+    demote_after: usize,
+    min_quantum: u32,
+    boosted: Vec<(u32, usize)>,
 }
 
 impl MLFQScheduler {
     pub fn new() -> Self {
         MLFQScheduler {
             queues: [VecDeque::new(), VecDeque::new(), VecDeque::new(), VecDeque::new()],
+            levels: MAX_LEVELS,
             time_quantums: [8, 16, 32, 64],
             process_queue_map: std::collections::HashMap::new(),
             boost_interval: 100,
             current_ticks: 0,
             current_pid: None,
             time_remaining: 0,
+            min_share: 0.0,
+            share_window: 256,
+            window_elapsed: 0,
+            window_usage: std::collections::HashMap::new(),
+            nice_values: std::collections::HashMap::new(),
+            sched_classes: std::collections::HashMap::new(),
+            level_allotments: [8, 16, 32, 64],
+            level_usage: std::collections::HashMap::new(),
+            history: std::collections::HashMap::new(),
+            demote_after: 1,
+            min_quantum: 0,
+            boosted: Vec::new(),
+        }
+    }
+
+    /// Build a scheduler with `config.quantums.len()` levels (1 to `MAX_LEVELS`).
+    /// Each level's demotion allotment starts at one quantum.
+    pub fn with_config(config: &SchedulerConfig) -> Result<Self, String> {
+        let levels = config.quantums.len();
+        if levels == 0 || levels > MAX_LEVELS {
+            return Err(format!("Scheduler needs 1-{} levels, got {}", MAX_LEVELS, levels));
+        }
+        if config.quantums.contains(&0) {
+            return Err("Quantums must be greater than 0".to_string());
         }
+
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.levels = levels;
+        for level in 0..MAX_LEVELS {
+            // Unused levels stay empty; pad with the last quantum
+            let quantum = config.quantums[level.min(levels - 1)];
+            scheduler.time_quantums[level] = quantum;
+            scheduler.level_allotments[level] = quantum;
+        }
+        scheduler.boost_interval = config.boost_interval;
+        scheduler.set_demote_after(config.demote_after);
+        scheduler.set_min_quantum(config.min_quantum);
+        Ok(scheduler)
     }
 
+    /// A fresh scheduler with this one's tunables (levels, quantums,
+    /// allotments, boost interval, demotion and share policies) but no
+    /// processes or run history
+    pub fn clone_config(&self) -> Self {
+        MLFQScheduler {
+            levels: self.levels,
+            time_quantums: self.time_quantums,
+            boost_interval: self.boost_interval,
+            min_share: self.min_share,
+            share_window: self.share_window,
+            level_allotments: self.level_allotments,
+            demote_after: self.demote_after,
+            min_quantum: self.min_quantum,
+            ..MLFQScheduler::new()
+        }
+    }
+
+    /// Number of queue levels in use
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Lowest-priority level, where new processes are admitted
+    pub fn lowest_level(&self) -> usize {
+        self.levels - 1
+    }
+
+    /// Time quantum for `level`, if the level exists
+    pub fn quantum(&self, level: usize) -> Option<u32> {
+        (level < self.levels).then(|| self.granted_quantum(level))
+    }
+
+    /// The quantum a dispatch from `level` actually grants, after the floor
+    fn granted_quantum(&self, level: usize) -> u32 {
+        self.time_quantums[level].max(self.min_quantum)
+    }
+
+    /// Never grant less than `ms` per dispatch, whatever the level's own
+    /// quantum. Raising it trades responsiveness for fewer context switches;
+    /// 0 (the default) disables the floor.
+    pub fn set_min_quantum(&mut self, ms: u32) {
+        self.min_quantum = ms;
+    }
+
+    pub fn min_quantum(&self) -> u32 {
+        self.min_quantum
+    }
+
+    /// Admit a new process at the lowest level, which is never above its
+    /// nice floor
     pub fn add_process(&mut self, pid: u32) {
-        self.queues[3].push_back(pid);
-        self.process_queue_map.insert(pid, 3);
+        self.admit_to_queue(pid, self.lowest_level());
     }
 
+    /// Admit a process at `queue`, clamped to its nice floor. Only admission
+    /// is clamped: re-enqueueing a parked process goes through
+    /// `add_process_to_queue` so it keeps the level it earned.
+    pub fn admit_to_queue(&mut self, pid: u32, queue: usize) {
+        self.add_process_to_queue(pid, queue.max(self.nice_floor(pid)));
+    }
+
+    /// Put a process on `queue` as-is, ignoring its nice floor
     pub fn add_process_to_queue(&mut self, pid: u32, queue: usize) {
-        if queue < 4 {
+        if queue < self.levels {
             self.queues[queue].push_back(pid);
             self.process_queue_map.insert(pid, queue);
         }
     }
 
+    /// Record a process's nice value (0 = highest priority, up to
+    /// `MAX_NICE`). Nicer processes are admitted no higher than `nice - 1`,
+    /// so a process at the lowest priority (3) never starts above Q2.
+    pub fn set_nice(&mut self, pid: u32, nice: u8) {
+        self.nice_values.insert(pid, nice.min(MAX_NICE));
+    }
+
+    /// A process's nice value (0 if never set)
+    pub fn nice(&self, pid: u32) -> u8 {
+        self.nice_values.get(&pid).copied().unwrap_or(0)
+    }
+
+    /// Highest queue level a process may be admitted to given its nice value
+    pub fn nice_floor(&self, pid: u32) -> usize {
+        self.nice_values
+            .get(&pid)
+            .map_or(0, |&nice| (nice.saturating_sub(1) as usize).min(self.lowest_level()))
+    }
+
+    /// Move an already-queued process straight to `queue`, ignoring its
+    /// nice floor (used for priority inheritance). Returns false if the
+    /// process isn't queued.
+    pub fn move_to_queue(&mut self, pid: u32, queue: usize) -> bool {
+        if queue >= self.levels || !self.process_queue_map.contains_key(&pid) {
+            return false;
+        }
+        self.move_process_to_queue(pid, queue);
+        true
+    }
+
+    /// Like `move_to_queue`, but puts the process at the head of the level
+    /// so it runs before everything already waiting there
+    pub fn move_to_front(&mut self, pid: u32, queue: usize) -> bool {
+        if !self.move_to_queue(pid, queue) {
+            return false;
+        }
+        self.queues[queue].retain(|&p| p != pid);
+        self.queues[queue].push_front(pid);
+        true
+    }
+
+    /// Set a process's scheduling class. Classes are strict tiers: any
+    /// queued real-time process runs before every normal one, and idle
+    /// processes run only when nothing else is queued.
+    pub fn set_sched_class(&mut self, pid: u32, class: SchedClass) {
+        self.sched_classes.insert(pid, class);
+    }
+
+    pub fn sched_class(&self, pid: u32) -> SchedClass {
+        self.sched_classes.get(&pid).copied().unwrap_or_default()
+    }
+
     pub fn remove_process(&mut self, pid: u32) {
         if let Some(queue_idx) = self.process_queue_map.remove(&pid) {
             self.queues[queue_idx].retain(|&p| p != pid);
         }
+        self.window_usage.remove(&pid);
+        self.level_usage.remove(&pid);
+        self.history.remove(&pid);
+    }
+
+    /// Drop a process that has exited, including its nice value and
+    /// scheduling class, which `remove_process` keeps so a parked process
+    /// comes back with the same floor and class
+    pub fn release_process(&mut self, pid: u32) {
+        self.remove_process(pid);
+        self.nice_values.remove(&pid);
+        self.sched_classes.remove(&pid);
+    }
+
+    /// Reconcile `process_queue_map` with what the queues actually hold and
+    /// release spare deque capacity. The dispatched process is out of its
+    /// deque until its outcome is applied, so its entry is kept. Returns
+    /// (phantom entries removed, entries re-synced to their real level).
+    pub fn rebalance(&mut self) -> (usize, usize) {
+        let mut seen = std::collections::HashSet::new();
+        let mut resynced = 0;
+        for (level, queue) in self.queues.iter_mut().enumerate() {
+            // A PID queued twice would be dispatched twice; keep the first
+            queue.retain(|&pid| seen.insert(pid));
+            queue.shrink_to_fit();
+
+            for &pid in queue.iter() {
+                if self.process_queue_map.insert(pid, level) != Some(level) {
+                    resynced += 1;
+                }
+            }
+        }
+
+        let current = self.current_pid;
+        let before = self.process_queue_map.len();
+        self.process_queue_map.retain(|pid, _| seen.contains(pid) || Some(*pid) == current);
+        self.process_queue_map.shrink_to_fit();
+        (before - self.process_queue_map.len(), resynced)
+    }
+
+    /// Guarantee every queued process at least `fraction` of the CPU time
+    /// handed out within each fairness window. Processes that fall short are
+    /// boosted to Q0 when the window closes. A fraction of 0 disables the policy.
+    pub fn set_min_share(&mut self, fraction: f64) {
+        self.min_share = fraction.clamp(0.0, 1.0);
+        self.window_usage.clear();
+        self.window_elapsed = 0;
+    }
+
+    /// Set the length of the fairness window in ms of dispatched quantum
+    pub fn set_share_window(&mut self, window: u32) {
+        self.share_window = window.max(1);
+    }
+
+    pub fn min_share(&self) -> f64 {
+        self.min_share
+    }
+
+    /// Set how many ticks a process may accumulate at `level` before it is
+    /// demoted. The default allotment is one quantum, so a single full
+    /// quantum demotes.
+    pub fn set_level_allotment(&mut self, level: usize, ticks: u32) {
+        if level < self.levels {
+            self.level_allotments[level] = ticks;
+        }
+    }
+
+    pub fn level_allotment(&self, level: usize) -> Option<u32> {
+        self.level_allotments.get(level).copied().filter(|_| level < self.levels)
+    }
+
+    /// Require `count` consecutive full quanta (on top of the level allotment)
+    /// before demoting, so a process that only occasionally burns its whole
+    /// slice keeps its priority. The default of 1 demotes on the allotment alone.
+    pub fn set_demote_after(&mut self, count: usize) {
+        self.demote_after = count.clamp(1, QUANTUM_HISTORY_LEN);
+    }
+
+    pub fn demote_after(&self) -> usize {
+        self.demote_after
+    }
+
+    /// The last `QUANTUM_HISTORY_LEN` outcomes for `pid`, oldest first
+    pub fn quantum_history(&self, pid: u32) -> Vec<QuantumOutcome> {
+        self.history.get(&pid).map_or_else(Vec::new, |h| h.iter().copied().collect())
+    }
+
+    fn record_outcome(&mut self, pid: u32, outcome: QuantumOutcome) {
+        let history = self.history.entry(pid).or_default();
+        if history.len() == QUANTUM_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(outcome);
+    }
+
+    /// Full quanta at the end of `pid`'s history with no yield in between
+    fn consecutive_full(&self, pid: u32) -> usize {
+        self.history.get(&pid).map_or(0, |h| {
+            h.iter().rev().take_while(|&&o| o == QuantumOutcome::Full).count()
+        })
+    }
+
+    /// Ticks `pid` has used at its current level
+    pub fn level_usage(&self, pid: u32) -> u32 {
+        self.level_usage.get(&pid).copied().unwrap_or(0)
     }
 
     fn move_process_to_queue(&mut self, pid: u32, new_queue: usize) {
-        if new_queue < 4 {
+        if new_queue < self.levels {
+            self.level_usage.remove(&pid);
             if let Some(old_queue) = self.process_queue_map.remove(&pid) {
                 self.queues[old_queue].retain(|&p| p != pid);
             }
@@ -68,26 +398,97 @@ impl MLFQScheduler {
     }
 
     fn priority_boost(&mut self) {
-        for queue_idx in 1..4 {
+        self.level_usage.clear();
+        for queue_idx in 1..self.levels {
             while let Some(pid) = self.queues[queue_idx].pop_front() {
                 self.queues[0].push_back(pid);
                 self.process_queue_map.insert(pid, 0);
+                self.boosted.push((pid, queue_idx));
             }
         }
     }
 
+    /// PIDs lifted to Q0 by a priority boost or the starvation guard since
+    /// the last call, each with the level it was lifted from
+    pub fn take_boosted(&mut self) -> Vec<(u32, usize)> {
+        std::mem::take(&mut self.boosted)
+    }
+
+    fn enforce_min_share(&mut self) {
+        let guaranteed = (self.window_elapsed as f64 * self.min_share) as u32;
+
+        let mut starved: Vec<(u32, usize)> = self.process_queue_map
+            .iter()
+            .filter(|&(pid, &queue)| {
+                queue > 0
+                    && self.queues[queue].contains(pid)
+                    && self.window_usage.get(pid).copied().unwrap_or(0) < guaranteed
+            })
+            .map(|(&pid, &queue)| (pid, queue))
+            .collect();
+        starved.sort_unstable();
+
+        for &(pid, _) in &starved {
+            self.move_process_to_queue(pid, 0);
+        }
+        self.boosted.extend(starved);
+
+        self.window_usage.clear();
+        self.window_elapsed = 0;
+    }
+
     pub fn next_process(&mut self) -> Option<(u32, u32)> {
+        self.next_process_where(|_| true)
+    }
+
+    /// Like `next_process`, but selects the idle task for one top-level
+    /// quantum when nothing is queued, so there is always something to run
+    pub fn next_or_idle(&mut self) -> (u32, u32) {
+        self.next_or_idle_where(|_| true)
+    }
+
+    /// `next_or_idle` that skips queued PIDs for which `runnable` is false
+    pub fn next_or_idle_where<F: Fn(u32) -> bool>(&mut self, runnable: F) -> (u32, u32) {
+        self.next_process_where(runnable)
+            .unwrap_or((IDLE_PID, self.granted_quantum(0)))
+    }
+
+    /// Like `next_process`, but skips queued PIDs for which `runnable` is
+    /// false. Skipped processes keep their place in line.
+    pub fn next_process_where<F: Fn(u32) -> bool>(&mut self, runnable: F) -> Option<(u32, u32)> {
         self.current_ticks = self.current_ticks.wrapping_add(1);
 
-        if self.current_ticks > 0 && self.current_ticks % self.boost_interval == 0 {
+        if self.boost_interval > 0
+            && self.current_ticks > 0
+            && self.current_ticks.is_multiple_of(self.boost_interval)
+        {
             self.priority_boost();
         }
 
-        for queue_idx in 0..4 {
-            if let Some(pid) = self.queues[queue_idx].pop_front() {
-                let quantum = self.time_quantums[queue_idx];
+        if self.min_share > 0.0 && self.window_elapsed >= self.share_window {
+            self.enforce_min_share();
+        }
+
+        // Highest class first; within a class, highest queue first
+        for class in [SchedClass::RealTime, SchedClass::Normal, SchedClass::Idle] {
+            for queue_idx in 0..self.levels {
+                let Some(pos) = self.queues[queue_idx]
+                    .iter()
+                    .position(|&pid| self.sched_class(pid) == class && runnable(pid))
+                else {
+                    continue;
+                };
+                let Some(pid) = self.queues[queue_idx].remove(pos) else {
+                    continue;
+                };
+
+                let quantum = self.granted_quantum(queue_idx);
                 self.current_pid = Some(pid);
                 self.time_remaining = quantum;
+                if self.min_share > 0.0 {
+                    *self.window_usage.entry(pid).or_insert(0) += quantum;
+                    self.window_elapsed += quantum;
+                }
                 return Some((pid, quantum));
             }
         }
@@ -98,16 +499,24 @@ impl MLFQScheduler {
 
     pub fn process_used_full_quantum(&mut self, pid: u32) {
         if let Some(&current_queue) = self.process_queue_map.get(&pid) {
-            if current_queue < 3 {
+            self.record_outcome(pid, QuantumOutcome::Full);
+            let sustained = self.consecutive_full(pid) >= self.demote_after;
+
+            let granted = self.granted_quantum(current_queue);
+            let used = self.level_usage.entry(pid).or_insert(0);
+            *used += granted;
+
+            if current_queue + 1 < self.levels && sustained && *used >= self.level_allotments[current_queue] {
                 self.move_process_to_queue(pid, current_queue + 1);
             } else {
-                self.queues[3].push_back(pid);
+                self.queues[current_queue].push_back(pid);
             }
         }
     }
 
     pub fn process_yielded_early(&mut self, pid: u32) {
         if let Some(&current_queue) = self.process_queue_map.get(&pid) {
+            self.record_outcome(pid, QuantumOutcome::Yielded);
             if current_queue > 0 {
                 self.move_process_to_queue(pid, current_queue - 1);
             } else {
@@ -116,6 +525,38 @@ impl MLFQScheduler {
         }
     }
 
+    /// Credit one early-yield outcome to a process that is still sitting in
+    /// its queue (not dispatched), as if it had just given up the CPU
+    pub fn credit_yield(&mut self, pid: u32) {
+        if let Some(queue) = self.get_process_queue(pid) {
+            self.queues[queue].retain(|&p| p != pid);
+            self.process_yielded_early(pid);
+        }
+    }
+
+    /// Move a queued process down exactly one level, clamped at the lowest.
+    /// Allotments, `demote_after` and the quantum history don't come into
+    /// it; usage at the old level is reset. Returns its (before, after) queue.
+    pub fn demote(&mut self, pid: u32) -> Option<(usize, usize)> {
+        let before = self.get_process_queue(pid)?;
+        let after = (before + 1).min(self.lowest_level());
+        if after != before {
+            self.move_to_queue(pid, after);
+        }
+        Some((before, after))
+    }
+
+    /// Move a queued process up exactly one level, clamped at Q0. Returns
+    /// its (before, after) queue.
+    pub fn promote(&mut self, pid: u32) -> Option<(usize, usize)> {
+        let before = self.get_process_queue(pid)?;
+        let after = before.saturating_sub(1);
+        if after != before {
+            self.move_to_queue(pid, after);
+        }
+        Some((before, after))
+    }
+
     pub fn tick(&mut self, ticks: u32) {
         self.time_remaining = self.time_remaining.saturating_sub(ticks);
     }
@@ -137,14 +578,53 @@ impl MLFQScheduler {
         ]
     }
 
+    /// Set how many `next_process` calls pass between priority boosts.
+    /// An interval of 0 disables the boost.
+    pub fn set_boost_interval(&mut self, interval: u32) {
+        self.boost_interval = interval;
+    }
+
+    pub fn boost_interval(&self) -> u32 {
+        self.boost_interval
+    }
+
+    /// `next_process` calls remaining until the next priority boost fires,
+    /// or `BOOST_DISABLED` if boosting is off
+    pub fn ticks_until_boost(&self) -> u32 {
+        if self.boost_interval == 0 {
+            return BOOST_DISABLED;
+        }
+        self.boost_interval - (self.current_ticks % self.boost_interval)
+    }
+
+    /// PIDs in each queue, front (next to run) first
+    pub fn snapshot_queues(&self) -> [Vec<u32>; 4] {
+        [
+            self.queues[0].iter().copied().collect(),
+            self.queues[1].iter().copied().collect(),
+            self.queues[2].iter().copied().collect(),
+            self.queues[3].iter().copied().collect(),
+        ]
+    }
+
     pub fn get_process_queue(&self, pid: u32) -> Option<usize> {
         self.process_queue_map.get(&pid).copied()
     }
 
+    /// Whether the process is enrolled in any queue
+    pub fn contains(&self, pid: u32) -> bool {
+        self.process_queue_map.contains_key(&pid)
+    }
+
     pub fn time_remaining(&self) -> u32 {
         self.time_remaining
     }
 
+    /// Quantum left for `pid` after `tick`s so far, if it is the current process
+    pub fn remaining_for(&self, pid: u32) -> Option<u32> {
+        (self.current_pid == Some(pid)).then_some(self.time_remaining)
+    }
+
     pub fn reset(&mut self) {
         for queue in &mut self.queues {
             queue.clear();
@@ -153,6 +633,12 @@ impl MLFQScheduler {
         self.current_pid = None;
         self.time_remaining = 0;
         self.current_ticks = 0;
+        self.window_usage.clear();
+        self.window_elapsed = 0;
+        self.nice_values.clear();
+        self.sched_classes.clear();
+        self.level_usage.clear();
+        self.history.clear();
     }
 }
 
@@ -197,6 +683,263 @@ mod tests {
         assert_eq!(scheduler.current_process(), Some(1));
     }
 
+    #[test]
+    fn test_clone_config_keeps_tunables_not_processes() {
+        let config = SchedulerConfig { quantums: vec![10, 20, 40], boost_interval: 50, min_quantum: 15, ..Default::default() };
+        let mut scheduler = MLFQScheduler::with_config(&config).unwrap();
+        scheduler.set_min_share(0.1);
+        for pid in 1..=5 {
+            scheduler.add_process(pid);
+        }
+        scheduler.next_process();
+
+        let fresh = scheduler.clone_config();
+        assert_eq!(fresh.levels(), 3);
+        assert_eq!((0..3).map(|l| fresh.quantum(l)).collect::<Vec<_>>(), vec![Some(15), Some(20), Some(40)]);
+        assert_eq!(fresh.boost_interval(), 50);
+        assert_eq!(fresh.min_quantum(), 15);
+        assert_eq!(fresh.min_share(), 0.1);
+        assert_eq!(fresh.queue_lengths(), [0; 4]);
+        assert_eq!(fresh.current_process(), None);
+        assert_eq!(fresh.get_process_queue(2), None);
+    }
+
+    #[test]
+    fn test_two_level_config() {
+        let config = SchedulerConfig { quantums: vec![10, 40], boost_interval: 0, ..Default::default() };
+        let mut scheduler = MLFQScheduler::with_config(&config).unwrap();
+        assert_eq!(scheduler.levels(), 2);
+        assert_eq!(scheduler.quantum(1), Some(40));
+        assert_eq!(scheduler.quantum(2), None);
+
+        scheduler.add_process(1);
+        assert_eq!(scheduler.get_process_queue(1), Some(1));
+        assert_eq!(scheduler.next_process(), Some((1, 40)));
+        scheduler.process_used_full_quantum(1);
+        assert_eq!(scheduler.get_process_queue(1), Some(1));
+
+        assert!(MLFQScheduler::with_config(&SchedulerConfig { quantums: vec![], boost_interval: 0, ..Default::default() }).is_err());
+        assert!(MLFQScheduler::with_config(&SchedulerConfig { quantums: vec![8; 5], boost_interval: 0, ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_realtime_runs_before_normal() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(1, 0);
+        scheduler.add_process_to_queue(2, 3);
+        scheduler.set_sched_class(2, SchedClass::RealTime);
+
+        // Real-time wins even from the bottom queue, with that queue's quantum
+        assert_eq!(scheduler.next_process(), Some((2, 64)));
+        assert_eq!(scheduler.next_process(), Some((1, 8)));
+    }
+
+    #[test]
+    fn test_idle_runs_only_when_alone() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(1, 0);
+        scheduler.add_process_to_queue(2, 3);
+        scheduler.set_sched_class(1, SchedClass::Idle);
+
+        assert_eq!(scheduler.next_process().map(|(pid, _)| pid), Some(2));
+        scheduler.remove_process(2);
+        assert_eq!(scheduler.next_process().map(|(pid, _)| pid), Some(1));
+    }
+
+    #[test]
+    fn test_idle_task_selected_when_nothing_queued() {
+        let mut scheduler = MLFQScheduler::new();
+        assert_eq!(scheduler.next_process(), None);
+        assert_eq!(scheduler.next_or_idle(), (IDLE_PID, 8));
+
+        scheduler.add_process_to_queue(4, 2);
+        assert_eq!(scheduler.next_or_idle(), (4, 32));
+        assert_eq!(scheduler.next_or_idle_where(|pid| pid != 4), (IDLE_PID, 8));
+    }
+
+    #[test]
+    fn test_remaining_for_tracks_ticks() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(3, 1);
+        scheduler.add_process_to_queue(4, 1);
+        assert_eq!(scheduler.remaining_for(3), None);
+
+        assert_eq!(scheduler.next_process(), Some((3, 16)));
+        scheduler.tick(5);
+        assert_eq!(scheduler.remaining_for(3), Some(11));
+        assert_eq!(scheduler.remaining_for(4), None);
+
+        scheduler.tick(20);
+        assert_eq!(scheduler.remaining_for(3), Some(0));
+        assert!(scheduler.is_quantum_expired());
+    }
+
+    #[test]
+    fn test_move_to_front_jumps_the_line() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(2, 0);
+        scheduler.add_process_to_queue(3, 0);
+        scheduler.add_process_to_queue(4, 2);
+        scheduler.set_nice(4, 3);
+
+        assert!(scheduler.move_to_front(4, 0));
+        assert_eq!(scheduler.snapshot_queues()[0], vec![4, 2, 3]);
+        assert_eq!(scheduler.get_process_queue(4), Some(0));
+        assert!(!scheduler.move_to_front(9, 0));
+    }
+
+    #[test]
+    fn test_min_quantum_floor() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(2, 0);
+        scheduler.add_process_to_queue(3, 1);
+        scheduler.set_min_quantum(10);
+
+        assert_eq!(scheduler.quantum(0), Some(10));
+        assert_eq!(scheduler.next_process(), Some((2, 10)));
+        // Levels already above the floor keep their own quantum
+        assert_eq!(scheduler.next_process(), Some((3, 16)));
+
+        scheduler.set_min_quantum(0);
+        assert_eq!(scheduler.quantum(0), Some(8));
+    }
+
+    #[test]
+    fn test_rebalance_drops_phantoms_and_shrinks() {
+        let mut scheduler = MLFQScheduler::new();
+        for pid in 1..=200 {
+            scheduler.add_process(pid);
+        }
+        for pid in 1..=195 {
+            scheduler.remove_process(pid);
+        }
+        // Simulate removal paths that bypassed the map or the deques
+        scheduler.process_queue_map.insert(500, 2);
+        scheduler.queues[1].push_back(199);
+
+        // 199 is now queued twice; the copy in the higher queue wins
+        assert_eq!(scheduler.rebalance(), (1, 1));
+        assert_eq!(scheduler.snapshot_queues(), [vec![], vec![199], vec![], vec![196, 197, 198, 200]]);
+        assert_eq!(scheduler.process_queue_map.len(), 5);
+        for (level, pids) in scheduler.snapshot_queues().iter().enumerate() {
+            for pid in pids {
+                assert_eq!(scheduler.get_process_queue(*pid), Some(level));
+            }
+        }
+        assert!(scheduler.queues[3].capacity() < 200);
+
+        // The process being dispatched is out of its deque but keeps its level
+        let (pid, _) = scheduler.next_process().unwrap();
+        assert_eq!(scheduler.rebalance(), (0, 0));
+        assert_eq!(scheduler.get_process_queue(pid), Some(1));
+    }
+
+    #[test]
+    fn test_snapshot_queues() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(4, 1);
+        scheduler.add_process(2);
+        scheduler.add_process_to_queue(3, 1);
+        scheduler.add_process(5);
+        scheduler.add_process_to_queue(6, 0);
+
+        assert_eq!(
+            scheduler.snapshot_queues(),
+            [vec![6], vec![4, 3], vec![], vec![2, 5]]
+        );
+
+        // Taking a snapshot doesn't disturb dispatch order
+        assert_eq!(scheduler.next_process().map(|(pid, _)| pid), Some(6));
+        assert_eq!(scheduler.snapshot_queues()[0], Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_boost_countdown() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.set_boost_interval(3);
+        scheduler.add_process_to_queue(1, 2);
+        assert_eq!(scheduler.ticks_until_boost(), 3);
+
+        scheduler.next_process();
+        scheduler.process_used_full_quantum(1);
+        assert_eq!(scheduler.ticks_until_boost(), 2);
+
+        scheduler.next_process();
+        scheduler.process_used_full_quantum(1);
+        assert_eq!(scheduler.ticks_until_boost(), 1);
+        assert_eq!(scheduler.get_process_queue(1), Some(3));
+
+        // Third call fires the boost and the countdown wraps
+        assert_eq!(scheduler.next_process(), Some((1, 8)));
+        assert_eq!(scheduler.ticks_until_boost(), 3);
+
+        scheduler.set_boost_interval(0);
+        assert_eq!(scheduler.ticks_until_boost(), BOOST_DISABLED);
+    }
+
+    #[test]
+    fn test_alternating_process_demotes_slower() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.set_demote_after(2);
+        scheduler.add_process_to_queue(1, 0); // always burns its quantum
+        scheduler.add_process_to_queue(2, 0); // alternates full / yielded
+
+        for round in 0..4 {
+            scheduler.process_used_full_quantum(1);
+            if round % 2 == 0 {
+                scheduler.process_used_full_quantum(2);
+            } else {
+                scheduler.process_yielded_early(2);
+            }
+        }
+
+        assert_eq!(scheduler.get_process_queue(1), Some(3));
+        assert_eq!(scheduler.get_process_queue(2), Some(0));
+        assert_eq!(
+            scheduler.quantum_history(2),
+            vec![QuantumOutcome::Full, QuantumOutcome::Yielded, QuantumOutcome::Full, QuantumOutcome::Yielded]
+        );
+    }
+
+    #[test]
+    fn test_quantum_history_is_bounded() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process(1);
+        for _ in 0..QUANTUM_HISTORY_LEN + 3 {
+            scheduler.process_used_full_quantum(1);
+        }
+        scheduler.process_yielded_early(1);
+
+        let history = scheduler.quantum_history(1);
+        assert_eq!(history.len(), QUANTUM_HISTORY_LEN);
+        assert_eq!(history.last(), Some(&QuantumOutcome::Yielded));
+    }
+
+    #[test]
+    fn test_demotion_waits_for_level_allotment() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.set_level_allotment(0, 24);
+        scheduler.add_process_to_queue(1, 0);
+
+        // Three 8ms quanta exhaust the 24-tick allotment
+        for used in [8, 16] {
+            scheduler.next_process();
+            scheduler.process_used_full_quantum(1);
+            assert_eq!(scheduler.get_process_queue(1), Some(0));
+            assert_eq!(scheduler.level_usage(1), used);
+        }
+
+        scheduler.next_process();
+        scheduler.process_used_full_quantum(1);
+        assert_eq!(scheduler.get_process_queue(1), Some(1));
+        assert_eq!(scheduler.level_usage(1), 0);
+
+        // Q1 still uses the default one-quantum allotment
+        scheduler.next_process();
+        scheduler.process_used_full_quantum(1);
+        assert_eq!(scheduler.get_process_queue(1), Some(2));
+    }
+
     #[test]
     fn test_priority_levels() {
         let mut scheduler = MLFQScheduler::new();
@@ -274,6 +1017,54 @@ mod tests {
         assert_eq!(scheduler.get_process_queue(1), None);
     }
 
+    #[test]
+    fn test_manual_demote_and_promote_move_one_level() {
+        let mut scheduler = MLFQScheduler::new();
+        // Neither gate holds up a manual demotion
+        scheduler.set_level_allotment(0, 24);
+        scheduler.set_demote_after(3);
+        scheduler.add_process_to_queue(1, 0);
+
+        assert_eq!(scheduler.demote(1), Some((0, 1)));
+        assert!(scheduler.quantum_history(1).is_empty());
+        assert_eq!(scheduler.demote(1), Some((1, 2)));
+        assert_eq!(scheduler.promote(1), Some((2, 1)));
+        assert_eq!(scheduler.queue_lengths(), [0, 1, 0, 0]);
+
+        scheduler.move_to_queue(1, 3);
+        assert_eq!(scheduler.demote(1), Some((3, 3)));
+        scheduler.move_to_queue(1, 0);
+        assert_eq!(scheduler.promote(1), Some((0, 0)));
+        assert_eq!(scheduler.queue_lengths(), [1, 0, 0, 0]);
+        assert_eq!(scheduler.demote(9), None);
+    }
+
+    #[test]
+    fn test_next_process_where_skips_without_dequeuing() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(1, 0);
+        scheduler.add_process_to_queue(2, 0);
+
+        assert_eq!(scheduler.next_process_where(|pid| pid != 1), Some((2, 8)));
+        assert_eq!(scheduler.queue_lengths(), [1, 0, 0, 0]);
+        assert_eq!(scheduler.next_process_where(|pid| pid != 1), None);
+        assert_eq!(scheduler.next_process(), Some((1, 8)));
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process(1);
+        scheduler.add_process_to_queue(2, 0);
+
+        assert!(scheduler.contains(1));
+        assert!(scheduler.contains(2));
+        assert!(!scheduler.contains(3));
+
+        scheduler.remove_process(2);
+        assert!(!scheduler.contains(2));
+    }
+
     #[test]
     fn test_multiple_processes_fifo_order() {
         let mut scheduler = MLFQScheduler::new();
@@ -300,4 +1091,70 @@ mod tests {
         assert_eq!(scheduler.queue_lengths(), [0, 0, 0, 0]);
         assert_eq!(scheduler.next_process(), None);
     }
+
+    #[test]
+    fn test_nice_clamps_initial_placement() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.set_nice(1, 3);
+        scheduler.set_nice(2, 0);
+
+        scheduler.admit_to_queue(1, 0);
+        scheduler.admit_to_queue(2, 0);
+        scheduler.admit_to_queue(3, 0);
+
+        assert_eq!(scheduler.get_process_queue(1), Some(2));
+        assert_eq!(scheduler.get_process_queue(2), Some(0));
+        assert_eq!(scheduler.get_process_queue(3), Some(0));
+
+        // Re-admission is clamped too
+        scheduler.remove_process(1);
+        scheduler.admit_to_queue(1, 1);
+        assert_eq!(scheduler.get_process_queue(1), Some(2));
+
+        // A re-enqueue keeps the level the process already held
+        scheduler.remove_process(1);
+        scheduler.add_process_to_queue(1, 0);
+        assert_eq!(scheduler.get_process_queue(1), Some(0));
+
+        // Only an exit forgets the nice value
+        assert_eq!(scheduler.nice(1), 3);
+        scheduler.release_process(1);
+        assert_eq!(scheduler.nice(1), 0);
+        assert!(!scheduler.nice_values.contains_key(&1));
+    }
+
+    #[test]
+    fn test_min_share_boosts_starved_process() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.set_min_share(0.1);
+        scheduler.set_share_window(64);
+        scheduler.add_process_to_queue(1, 0);
+        scheduler.add_process_to_queue(2, 3);
+
+        // PID 1 keeps yielding in Q0 and monopolises the CPU for a full window
+        for _ in 0..8 {
+            let (pid, _) = scheduler.next_process().unwrap();
+            assert_eq!(pid, 1);
+            scheduler.process_yielded_early(pid);
+        }
+        assert_eq!(scheduler.get_process_queue(2), Some(3));
+
+        // The window has closed: PID 2 got 0ms out of 64ms and is boosted
+        let _ = scheduler.next_process();
+        assert_eq!(scheduler.get_process_queue(2), Some(0));
+    }
+
+    #[test]
+    fn test_min_share_disabled_by_default() {
+        let mut scheduler = MLFQScheduler::new();
+        scheduler.add_process_to_queue(1, 0);
+        scheduler.add_process_to_queue(2, 3);
+
+        for _ in 0..50 {
+            let (pid, _) = scheduler.next_process().unwrap();
+            scheduler.process_yielded_early(pid);
+        }
+        assert_eq!(scheduler.min_share(), 0.0);
+        assert_eq!(scheduler.get_process_queue(2), Some(3));
+    }
 }
\ No newline at end of file
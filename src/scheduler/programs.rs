@@ -3,6 +3,10 @@
 
 use std::collections::HashMap;
 
+use rand::Rng;
+
+use crate::process::SchedClass;
+
 /// Program type classification
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProgramType {
@@ -11,6 +15,8 @@ pub enum ProgramType {
     Interactive,
     Mixed,
     Batch,
+    /// Latency-bound work such as audio; runs in the real-time class
+    Realtime,
 }
 
 /// Mock program definition
@@ -21,6 +27,11 @@ pub struct Program {
     pub description: String,
     pub typical_quantum_usage: f32,
     pub expected_priority: u8,
+    pub estimated_length: u64,
+    /// Scripted quantum decisions (true = use the full quantum), replayed in
+    /// order before falling back to `typical_quantum_usage`
+    pub behavior_script: Vec<bool>,
+    script_cursor: usize,
 }
 
 impl Program {
@@ -31,6 +42,16 @@ impl Program {
             ProgramType::Interactive => 0,
             ProgramType::Mixed => 1,
             ProgramType::Batch => 2,
+            ProgramType::Realtime => 0,
+        };
+
+        let estimated_length = match program_type {
+            ProgramType::CpuBound => 2000,
+            ProgramType::IoBound => 400,
+            ProgramType::Interactive => 300,
+            ProgramType::Mixed => 800,
+            ProgramType::Batch => 1500,
+            ProgramType::Realtime => 600,
         };
 
         Program {
@@ -39,11 +60,44 @@ impl Program {
             description: description.to_string(),
             typical_quantum_usage: usage,
             expected_priority,
+            estimated_length,
+            behavior_script: Vec::new(),
+            script_cursor: 0,
+        }
+    }
+
+    /// Scheduling class a process running this program is admitted with
+    pub fn sched_class(&self) -> SchedClass {
+        match self.program_type {
+            ProgramType::Realtime => SchedClass::RealTime,
+            _ => SchedClass::Normal,
         }
     }
 
-    pub fn execute_quantum(&self) -> bool {
-        rand::random::<f32>() < self.typical_quantum_usage
+    /// Replace the behavior script and start replaying it from the beginning
+    pub fn set_behavior_script(&mut self, script: Vec<bool>) {
+        self.behavior_script = script;
+        self.script_cursor = 0;
+    }
+
+    /// Scripted decisions not yet consumed
+    pub fn script_remaining(&self) -> usize {
+        self.behavior_script.len().saturating_sub(self.script_cursor)
+    }
+
+    pub fn execute_quantum<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        if let Some(&full) = self.behavior_script.get(self.script_cursor) {
+            self.script_cursor += 1;
+            return full;
+        }
+        rng.gen::<f32>() < self.typical_quantum_usage
+    }
+
+    /// How long (ms) the program runs before yielding early: its typical
+    /// share of the quantum, always at least 1ms and short of the full slice
+    pub fn early_yield_time(&self, quantum: u32) -> u32 {
+        let ran = (quantum as f32 * self.typical_quantum_usage).round() as u32;
+        ran.clamp(1, quantum.saturating_sub(1).max(1))
     }
 
     pub fn behavior_description(&self) -> String {
@@ -63,11 +117,15 @@ impl Program {
             ProgramType::Batch => {
                 "Mostly CPU with occasional I/O (background)".to_string()
             }
+            ProgramType::Realtime => {
+                "Short deadline-bound bursts ahead of normal work (real-time)".to_string()
+            }
         }
     }
 }
 
 /// Program registry
+#[derive(Debug, Clone)]
 pub struct ProgramRegistry {
     programs: HashMap<String, Program>,
 }
@@ -196,6 +254,16 @@ impl ProgramRegistry {
             ),
         );
 
+        programs.insert(
+            "audio".to_string(),
+            Program::new(
+                "audio",
+                ProgramType::Realtime,
+                "Audio mixer filling sound buffers on a deadline",
+                0.30,
+            ),
+        );
+
         ProgramRegistry { programs }
     }
 
@@ -203,10 +271,41 @@ impl ProgramRegistry {
         self.programs.get(name).cloned()
     }
 
+    /// Remove a program from the catalog. Returns false if it wasn't registered.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.programs.remove(name).is_some()
+    }
+
+    /// Replace the definition registered under `name`. Returns false if it wasn't registered.
+    pub fn update(&mut self, name: &str, program: Program) -> bool {
+        match self.programs.get_mut(name) {
+            Some(existing) => {
+                *existing = program;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn list_programs(&self) -> Vec<&Program> {
         self.programs.values().collect()
     }
 
+    /// Programs whose name or description contains `query`, ignoring case,
+    /// sorted by name
+    pub fn search(&self, query: &str) -> Vec<&Program> {
+        let query = query.to_lowercase();
+        let mut matches: Vec<&Program> = self.programs
+            .values()
+            .filter(|p| {
+                p.name.to_lowercase().contains(&query)
+                    || p.description.to_lowercase().contains(&query)
+            })
+            .collect();
+        matches.sort_by(|a, b| a.name.cmp(&b.name));
+        matches
+    }
+
     pub fn get_by_type(&self, program_type: ProgramType) -> Vec<&Program> {
         self.programs
             .values()
@@ -271,6 +370,16 @@ impl ProgramRegistry {
             ));
         }
 
+        output.push_str("\nReal-Time Programs (Bounded Latency):\n");
+        output.push_str("────────────────────────────────────────────────────────────\n");
+        for prog in self.get_by_type(ProgramType::Realtime) {
+            output.push_str(&format!(
+                "  {} - {}\n    Usage: {:.0}% quantum\n",
+                prog.name, prog.description,
+                prog.typical_quantum_usage * 100.0
+            ));
+        }
+
         output.push_str("\nUsage: run_program <program_name>\n");
         output
     }
@@ -293,6 +402,53 @@ mod tests {
         assert_eq!(prog.program_type, ProgramType::CpuBound);
     }
 
+    #[test]
+    fn test_realtime_programs_use_realtime_class() {
+        let registry = ProgramRegistry::new();
+        let audio = registry.get_program("audio").unwrap();
+        assert_eq!(audio.program_type, ProgramType::Realtime);
+        assert_eq!(audio.sched_class(), SchedClass::RealTime);
+        assert_eq!(registry.get_program("compiler").unwrap().sched_class(), SchedClass::Normal);
+        assert!(registry.print_catalog().contains("Real-Time Programs"));
+    }
+
+    #[test]
+    fn test_behavior_script_replays_before_random() {
+        let mut prog = Program::new("demo", ProgramType::Mixed, "Scripted demo", 0.0);
+        prog.set_behavior_script(vec![true, false, true]);
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+
+        let decisions: Vec<bool> = (0..3).map(|_| prog.execute_quantum(&mut rng)).collect();
+        assert_eq!(decisions, vec![true, false, true]);
+        assert_eq!(prog.script_remaining(), 0);
+        // Script exhausted: 0% usage never takes the full quantum
+        assert!(!prog.execute_quantum(&mut rng));
+    }
+
+    #[test]
+    fn test_search_matches_name_or_description() {
+        let registry = ProgramRegistry::new();
+        let names = |query| -> Vec<String> {
+            registry.search(query).iter().map(|p| p.name.clone()).collect()
+        };
+
+        assert_eq!(names("network"), vec!["file_transfer", "web_browser"]);
+        assert_eq!(names("COMPILER"), vec!["compiler"]);
+        assert!(names("spreadsheet").is_empty());
+    }
+
+    #[test]
+    fn test_early_yield_time_stays_inside_quantum() {
+        let io = Program::new("io", ProgramType::IoBound, "I/O program", 0.25);
+        assert_eq!(io.early_yield_time(16), 4);
+        assert_eq!(io.early_yield_time(1), 1);
+
+        let idle = Program::new("idle", ProgramType::IoBound, "Never computes", 0.0);
+        assert_eq!(idle.early_yield_time(8), 1);
+        let hog = Program::new("hog", ProgramType::CpuBound, "Always computes", 1.0);
+        assert_eq!(hog.early_yield_time(8), 7);
+    }
+
     #[test]
     fn test_program_registry() {
         let registry = ProgramRegistry::new();
@@ -300,10 +456,33 @@ mod tests {
         assert!(prog.is_some());
     }
 
+    #[test]
+    fn test_remove_program() {
+        let mut registry = ProgramRegistry::new();
+        assert!(registry.remove("backup"));
+        assert!(registry.get_program("backup").is_none());
+        assert!(!registry.remove("backup"));
+        assert!(!registry.remove("does_not_exist"));
+    }
+
+    #[test]
+    fn test_update_program() {
+        let mut registry = ProgramRegistry::new();
+        let mut compiler = registry.get_program("compiler").unwrap();
+        compiler.typical_quantum_usage = 0.5;
+
+        assert!(registry.update("compiler", compiler));
+        assert_eq!(registry.get_program("compiler").unwrap().typical_quantum_usage, 0.5);
+
+        let ghost = Program::new("ghost", ProgramType::Batch, "Not registered", 0.5);
+        assert!(!registry.update("ghost", ghost));
+        assert!(registry.get_program("ghost").is_none());
+    }
+
     #[test]
     fn test_get_programs_by_type() {
         let registry = ProgramRegistry::new();
         let cpu_programs = registry.get_by_type(ProgramType::CpuBound);
-        assert!(cpu_programs.len() > 0);
+        assert!(!cpu_programs.is_empty());
     }
 }
\ No newline at end of file
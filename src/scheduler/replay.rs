@@ -0,0 +1,132 @@
+// src/scheduler/replay.rs
+// Real-time playback of a recorded execution log
+
+use std::time::Duration;
+
+use super::metrics::ExecutionSlice;
+
+/// Source of delays between replayed events and watch frames, swappable so
+/// tests don't sleep
+pub trait Sleeper {
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// Sleeps the current thread for real
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadSleeper;
+
+impl Sleeper for ThreadSleeper {
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Parse a JSON array of execution slices
+pub fn load_trace(json: &str) -> Result<Vec<ExecutionSlice>, String> {
+    serde_json::from_str(json).map_err(|e| format!("invalid trace: {}", e))
+}
+
+/// Wall-clock delay before an event, given the previous event's tick.
+/// One tick is one simulated ms, so speed 1.0 plays back in real time.
+/// Fails if the speed is so slow the delay doesn't fit in a `Duration`.
+pub fn delay_between(previous_tick: u64, tick: u64, speed: f64) -> Result<Duration, String> {
+    let gap_ms = tick.saturating_sub(previous_tick);
+    Duration::try_from_secs_f64(gap_ms as f64 / speed / 1000.0)
+        .map_err(|_| format!("speed is too slow to replay a {}ms gap", gap_ms))
+}
+
+/// Fire every event in tick order, sleeping between them in proportion to
+/// their tick gaps. Returns the number of events replayed; nothing fires
+/// if any of the delays can't be represented.
+pub fn replay<F>(
+    events: &[ExecutionSlice],
+    speed: f64,
+    sleeper: &mut dyn Sleeper,
+    mut on_event: F,
+) -> Result<usize, String>
+where
+    F: FnMut(&ExecutionSlice),
+{
+    let mut ordered = events.to_vec();
+    ordered.sort_by_key(|slice| slice.start_tick);
+
+    let mut previous_tick = ordered.first().map_or(0, |slice| slice.start_tick);
+    let mut delays = Vec::with_capacity(ordered.len());
+    for slice in &ordered {
+        delays.push(delay_between(previous_tick, slice.start_tick, speed)?);
+        previous_tick = slice.start_tick;
+    }
+
+    for (slice, delay) in ordered.iter().zip(delays) {
+        if !delay.is_zero() {
+            sleeper.sleep(delay);
+        }
+        on_event(slice);
+    }
+
+    Ok(ordered.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Advances a shared virtual clock instead of sleeping
+    struct MockClock {
+        now: Rc<Cell<Duration>>,
+    }
+
+    impl Sleeper for MockClock {
+        fn sleep(&mut self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+        }
+    }
+
+    fn slice(pid: u32, start_tick: u64) -> ExecutionSlice {
+        ExecutionSlice { pid, start_tick, duration: 8, queue: 0 }
+    }
+
+    #[test]
+    fn test_events_fire_in_order_with_scaled_delays() {
+        let now = Rc::new(Cell::new(Duration::ZERO));
+        let mut clock = MockClock { now: Rc::clone(&now) };
+        let events = vec![slice(2, 8), slice(1, 0), slice(3, 24)];
+
+        let mut fired = Vec::new();
+        let count = replay(&events, 2.0, &mut clock, |event| {
+            fired.push((event.pid, now.get()));
+        });
+
+        assert_eq!(count, Ok(3));
+        assert_eq!(
+            fired,
+            vec![
+                (1, Duration::ZERO),
+                (2, Duration::from_millis(4)),
+                (3, Duration::from_millis(12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_too_slow_speed_is_an_error() {
+        assert!(delay_between(0, 8, 1e-300).is_err());
+        assert_eq!(delay_between(0, 8, 2.0), Ok(Duration::from_millis(4)));
+
+        let mut clock = MockClock { now: Rc::new(Cell::new(Duration::ZERO)) };
+        let mut fired = 0;
+        let result = replay(&[slice(1, 0), slice(2, 8)], 1e-300, &mut clock, |_| fired += 1);
+        assert!(result.unwrap_err().contains("too slow"));
+        assert_eq!(fired, 0);
+    }
+
+    #[test]
+    fn test_load_trace_round_trip() {
+        let events = vec![slice(1, 0), slice(2, 8)];
+        let json = serde_json::to_string(&events).unwrap();
+        assert_eq!(load_trace(&json).unwrap(), events);
+        assert!(load_trace("not json").is_err());
+    }
+}
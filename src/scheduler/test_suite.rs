@@ -0,0 +1,432 @@
+// src/scheduler/test_suite.rs
+// Canned scheduling scenarios that check the MLFQ behaves as advertised
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::metrics::SchedulerStats;
+use super::MLFQScheduler;
+
+/// Aggregated outcome of a self-test run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestResults {
+    pub passed: u32,
+    pub failed: u32,
+    pub details: Vec<String>,
+}
+
+impl TestResults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the outcome of one scenario
+    pub fn record(&mut self, name: &str, outcome: Result<(), String>) {
+        match outcome {
+            Ok(()) => {
+                self.passed += 1;
+                self.details.push(format!("✓ {}", name));
+            }
+            Err(reason) => {
+                self.failed += 1;
+                self.details.push(format!("✗ {}: {}", name, reason));
+            }
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.passed + self.failed
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// Render a grading-style report of every scenario
+    pub fn to_report(&self) -> String {
+        let mut report = format!(
+            "Scheduler Self-Test: {} passed, {} failed\n\
+             ────────────────────────────────────\n",
+            self.passed, self.failed
+        );
+        for detail in &self.details {
+            report.push_str(detail);
+            report.push('\n');
+        }
+        report
+    }
+}
+
+/// How a simulated process uses each quantum it is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Behavior {
+    CpuBound,
+    /// Runs a quarter of each quantum, then yields for I/O (interactive)
+    IoBound,
+    /// Uses the full quantum this percentage of the time, otherwise yields
+    /// like `IoBound`; drawn from the scenario's seeded RNG
+    Mixed(u8),
+}
+
+impl Behavior {
+    /// Whether this dispatch uses the whole quantum
+    fn uses_full_quantum(self, rng: &mut StdRng) -> bool {
+        match self {
+            Behavior::CpuBound => true,
+            Behavior::IoBound => false,
+            Behavior::Mixed(percent) => rng.gen_range(0..100) < percent,
+        }
+    }
+}
+
+/// A quantity measured over a scenario run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    /// Number of times the PID was dispatched
+    Dispatches(u32),
+    /// Fraction of all CPU time the PID received (0.0-1.0)
+    CpuShare(u32),
+    /// Average cycle at which processes were first dispatched
+    AvgFirstDispatch,
+    /// Average depth of a queue across all cycles
+    AvgQueueDepth(usize),
+    /// Context switches per tick
+    ContextSwitchRate,
+    /// Average sim time (ms) an `IoBound` process waited between becoming
+    /// ready and being dispatched
+    AvgIoResponse,
+}
+
+/// Inclusive bounds a metric must fall within
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricBound {
+    pub metric: Metric,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// State left behind by one simulated scenario, for checking and measuring
+struct ScenarioRun {
+    scheduler: MLFQScheduler,
+    stats: SchedulerStats,
+    dispatched: Vec<u32>,
+    io_waits: Vec<u64>,
+}
+
+/// A workload plus the outcomes it is expected to produce
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    /// (pid, starting queue, behavior)
+    pub processes: Vec<(u32, usize, Behavior)>,
+    pub cycles: u32,
+    /// (pid, queue) placements expected when the run ends
+    pub expected_queues: Vec<(u32, usize)>,
+    pub metric_bounds: Vec<MetricBound>,
+    /// Seeds the RNG behind `Behavior::Mixed`
+    pub seed: u64,
+}
+
+impl Scenario {
+    pub fn new(name: &str, cycles: u32) -> Self {
+        Scenario {
+            name: name.to_string(),
+            processes: Vec::new(),
+            cycles,
+            expected_queues: Vec::new(),
+            metric_bounds: Vec::new(),
+            seed: 0,
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn process(mut self, pid: u32, queue: usize, behavior: Behavior) -> Self {
+        self.processes.push((pid, queue, behavior));
+        self
+    }
+
+    pub fn expect_queue(mut self, pid: u32, queue: usize) -> Self {
+        self.expected_queues.push((pid, queue));
+        self
+    }
+
+    pub fn expect_metric(mut self, metric: Metric, min: f64, max: f64) -> Self {
+        self.metric_bounds.push(MetricBound { metric, min, max });
+        self
+    }
+
+    /// Simulate the workload and check every expectation. On failure the
+    /// error lists each mismatch with its expected and actual values.
+    pub fn run(&self) -> Result<(), String> {
+        let run = self.simulate();
+        let mut failures = Vec::new();
+
+        for &(pid, expected) in &self.expected_queues {
+            let actual = run.scheduler.get_process_queue(pid);
+            if actual != Some(expected) {
+                failures.push(format!(
+                    "PID {} queue: expected Q{}, got {}",
+                    pid,
+                    expected,
+                    actual.map_or("none".to_string(), |q| format!("Q{}", q))
+                ));
+            }
+        }
+
+        for bound in &self.metric_bounds {
+            let actual = self.measure(bound.metric, &run);
+            if actual < bound.min || actual > bound.max {
+                failures.push(format!(
+                    "{:?}: expected {:.2}..={:.2}, got {:.2}",
+                    bound.metric, bound.min, bound.max, actual
+                ));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures.join("; "))
+        }
+    }
+
+    /// Simulate the workload and return the measured value of every metric
+    /// the scenario has a bound for, in the order the bounds were added
+    pub fn measure_all(&self) -> Vec<(Metric, f64)> {
+        let run = self.simulate();
+        self.metric_bounds
+            .iter()
+            .map(|bound| (bound.metric, self.measure(bound.metric, &run)))
+            .collect()
+    }
+
+    fn simulate(&self) -> ScenarioRun {
+        let mut scheduler = MLFQScheduler::new();
+        let mut stats = SchedulerStats::new();
+
+        for &(pid, queue, _) in &self.processes {
+            scheduler.add_process_to_queue(pid, queue);
+            stats.record_process_created(pid);
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut clock = 0;
+        let mut ready_since: HashMap<u32, u64> = HashMap::new();
+        let mut io_waits = Vec::new();
+
+        let mut dispatched = Vec::new();
+        for _ in 0..self.cycles {
+            stats.sample_queue_depths(scheduler.queue_lengths());
+            let Some((pid, quantum)) = scheduler.next_process() else {
+                break;
+            };
+            dispatched.push(pid);
+            stats.record_context_switch(pid);
+            stats.record_execution_time(pid, quantum as u64);
+            stats.record_tick();
+
+            let behavior = self.behavior_of(pid);
+            if behavior == Behavior::IoBound {
+                io_waits.push(clock - ready_since.get(&pid).copied().unwrap_or(0));
+            }
+
+            if behavior.uses_full_quantum(&mut rng) {
+                clock += quantum as u64;
+                scheduler.process_used_full_quantum(pid);
+            } else {
+                clock += (quantum as u64 / 4).max(1);
+                scheduler.process_yielded_early(pid);
+            }
+            ready_since.insert(pid, clock);
+        }
+
+        ScenarioRun { scheduler, stats, dispatched, io_waits }
+    }
+
+    fn behavior_of(&self, pid: u32) -> Behavior {
+        self.processes
+            .iter()
+            .find(|(p, _, _)| *p == pid)
+            .map_or(Behavior::CpuBound, |(_, _, b)| *b)
+    }
+
+    fn measure(&self, metric: Metric, run: &ScenarioRun) -> f64 {
+        let ScenarioRun { stats, dispatched, io_waits, .. } = run;
+        match metric {
+            Metric::Dispatches(pid) => dispatched.iter().filter(|&&p| p == pid).count() as f64,
+            Metric::CpuShare(pid) => {
+                if stats.total_execution_time == 0 {
+                    return 0.0;
+                }
+                let used = stats.get_process_metrics(pid).map_or(0, |m| m.execution_time);
+                used as f64 / stats.total_execution_time as f64
+            }
+            Metric::AvgFirstDispatch => {
+                if self.processes.is_empty() {
+                    return 0.0;
+                }
+                // Processes that never ran count as waiting the whole run
+                let total: usize = self.processes
+                    .iter()
+                    .map(|(pid, _, _)| {
+                        dispatched
+                            .iter()
+                            .position(|p| p == pid)
+                            .unwrap_or(self.cycles as usize)
+                    })
+                    .sum();
+                total as f64 / self.processes.len() as f64
+            }
+            Metric::AvgQueueDepth(queue) => stats.avg_queue_depth(queue),
+            Metric::ContextSwitchRate => stats.context_switch_rate(),
+            Metric::AvgIoResponse => {
+                if io_waits.is_empty() {
+                    return 0.0;
+                }
+                io_waits.iter().sum::<u64>() as f64 / io_waits.len() as f64
+            }
+        }
+    }
+}
+
+/// A Q3 process stuck behind Q0 yielders must still run once the boost fires
+pub fn starvation_prevented() -> Scenario {
+    Scenario::new("starvation prevented by priority boost", 150)
+        .process(1, 3, Behavior::CpuBound)
+        .process(2, 0, Behavior::IoBound)
+        .process(3, 0, Behavior::IoBound)
+        .expect_metric(Metric::Dispatches(1), 1.0, f64::MAX)
+}
+
+/// A process that always yields early stays in the top queue
+pub fn io_bound_stays_high() -> Scenario {
+    Scenario::new("I/O-bound process stays in Q0", 50)
+        .process(1, 3, Behavior::IoBound)
+        .process(2, 3, Behavior::CpuBound)
+        .expect_queue(1, 0)
+        .expect_metric(Metric::Dispatches(1), 40.0, 50.0)
+}
+
+/// A process that always burns its full quantum sinks to the bottom queue
+pub fn cpu_bound_sinks() -> Scenario {
+    Scenario::new("CPU-bound process sinks to Q3", 20)
+        .process(1, 0, Behavior::CpuBound)
+        .process(2, 0, Behavior::CpuBound)
+        .expect_queue(1, 3)
+        .expect_queue(2, 3)
+        .expect_metric(Metric::CpuShare(1), 0.4, 0.6)
+        .expect_metric(Metric::ContextSwitchRate, 1.0, 1.0)
+}
+
+/// Interactive processes keep responding quickly under a long, seeded mix
+/// of CPU hogs and bursty workers. This is the core promise of MLFQ.
+pub fn interactive_response_stays_low() -> Scenario {
+    Scenario::new("interactive response stays low under mixed load", 1000)
+        .seed(1988)
+        .process(1, 3, Behavior::IoBound)
+        .process(2, 3, Behavior::IoBound)
+        .process(3, 0, Behavior::CpuBound)
+        .process(4, 0, Behavior::CpuBound)
+        .process(5, 0, Behavior::CpuBound)
+        .process(6, 0, Behavior::Mixed(50))
+        .process(7, 0, Behavior::Mixed(80))
+        .expect_queue(1, 0)
+        .expect_queue(2, 0)
+        .expect_metric(Metric::AvgIoResponse, 0.0, IO_RESPONSE_LIMIT_MS)
+}
+
+/// Ceiling for `interactive_response_stays_low`: under a quarter of the Q3 quantum
+pub const IO_RESPONSE_LIMIT_MS: f64 = 16.0;
+
+/// Every canned scenario, in the order they are reported
+pub fn builtin_scenarios() -> Vec<Scenario> {
+    vec![
+        starvation_prevented(),
+        io_bound_stays_high(),
+        cpu_bound_sinks(),
+        interactive_response_stays_low(),
+    ]
+}
+
+/// Run a set of scenarios and aggregate the results
+pub fn run_scenarios(scenarios: &[Scenario]) -> TestResults {
+    let mut results = TestResults::new();
+    for scenario in scenarios {
+        results.record(&scenario.name, scenario.run());
+    }
+    results
+}
+
+/// Run every canned scenario and aggregate the results
+pub fn run_all() -> TestResults {
+    run_scenarios(&builtin_scenarios())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_record() {
+        let mut results = TestResults::new();
+        results.record("ok", Ok(()));
+        results.record("bad", Err("broken".to_string()));
+
+        assert_eq!(results.passed, 1);
+        assert_eq!(results.failed, 1);
+        assert_eq!(results.total(), 2);
+        assert!(!results.all_passed());
+        assert_eq!(results.details[1], "✗ bad: broken");
+    }
+
+    #[test]
+    fn test_scenarios_pass_on_correct_scheduler() {
+        assert_eq!(starvation_prevented().run(), Ok(()));
+        assert_eq!(io_bound_stays_high().run(), Ok(()));
+        assert_eq!(cpu_bound_sinks().run(), Ok(()));
+        assert_eq!(interactive_response_stays_low().run(), Ok(()));
+    }
+
+    #[test]
+    fn test_mixed_workload_is_deterministic_and_responsive() {
+        let scenario = interactive_response_stays_low();
+
+        // Same seed, same run; the measured response is well under the limit
+        let first = scenario.measure_all();
+        assert_eq!(first, scenario.measure_all());
+        let [(Metric::AvgIoResponse, response)] = first[..] else {
+            panic!("unexpected metrics: {:?}", first);
+        };
+        assert!(response > 0.0 && response < IO_RESPONSE_LIMIT_MS, "{}", response);
+    }
+
+    #[test]
+    fn test_run_all() {
+        let results = run_all();
+        assert_eq!(results.total(), 4);
+        assert!(results.all_passed(), "{:?}", results.details);
+    }
+
+    #[test]
+    fn test_broken_expectation_reports_mismatch() {
+        let broken = Scenario::new("CPU-bound wrongly expected in Q0", 20)
+            .process(1, 0, Behavior::CpuBound)
+            .expect_queue(1, 0)
+            .expect_metric(Metric::CpuShare(1), 0.0, 0.5);
+
+        let results = run_scenarios(&[broken, cpu_bound_sinks()]);
+        assert_eq!(results.passed, 1);
+        assert_eq!(results.failed, 1);
+
+        let report = results.to_report();
+        assert!(report.contains("1 passed, 1 failed"));
+        assert!(report.contains("PID 1 queue: expected Q0, got Q3"));
+        assert!(report.contains("CpuShare(1): expected 0.00..=0.50, got 1.00"));
+    }
+}
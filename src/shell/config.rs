@@ -0,0 +1,74 @@
+// src/shell/config.rs
+// Everything needed to reproduce a shell environment, loadable from TOML
+
+use serde::Deserialize;
+
+use crate::scheduler::SchedulerConfig;
+
+/// Settings for `Shell::from_config`. Every field is optional in a config
+/// file; missing ones keep the `Default` value.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default)]
+pub struct ShellConfig {
+    pub scheduler: SchedulerConfig,
+    /// Most live (non-terminated) processes allowed at once
+    pub process_limit: Option<usize>,
+    /// Bytes of heap and stack all live processes may reserve in total
+    pub memory_budget: Option<u64>,
+    /// RNG seed; None seeds from entropy
+    pub seed: Option<u64>,
+    /// Color success and error lines with ANSI escapes
+    pub colorize: bool,
+}
+
+impl ShellConfig {
+    /// Parse a config file such as:
+    ///
+    /// ```toml
+    /// seed = 42
+    /// process_limit = 16
+    ///
+    /// [scheduler]
+    /// quantums = [10, 40]
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| format!("Invalid config: {}", e))
+    }
+
+    /// Read and parse a config file
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read config '{}': {}", path, e))?;
+        Self::from_toml(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_partial_config() {
+        let config = ShellConfig::from_toml(
+            "seed = 7\n\
+             colorize = true\n\
+             [scheduler]\n\
+             quantums = [10, 40]\n\
+             min_quantum = 12\n"
+        )
+        .unwrap();
+
+        assert_eq!(config.seed, Some(7));
+        assert!(config.colorize);
+        assert_eq!(config.scheduler.quantums, vec![10, 40]);
+        assert_eq!(config.scheduler.boost_interval, 100);
+        assert_eq!(config.scheduler.min_quantum, 12);
+        assert_eq!(config.process_limit, None);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(ShellConfig::from_toml("seed = \"abc\"").is_err());
+        assert_eq!(ShellConfig::from_toml("").unwrap(), ShellConfig::default());
+    }
+}
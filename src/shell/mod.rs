@@ -1,7 +1,26 @@
 // src/shell/mod.rs
 
-use crate::process::{ProcessManager, ProcessState};
-use crate::scheduler::MLFQScheduler;
+pub mod config;
+pub mod pipeline;
+pub mod watch;
+
+pub use config::ShellConfig;
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::ipc::{self, LockOutcome, LockTable, SemaphoreTable};
+use crate::process::{CgroupTable, MemoryContext, Process, ProcessManager, ProcessState, SchedClass};
+use crate::scheduler::{IDLE_PID, MAX_NICE, MLFQScheduler, MetricSortKey, Program, ProgramRegistry, QueueChange, SwitchKind};
+use crate::scheduler::bench;
+use crate::scheduler::replay::{self, Sleeper, ThreadSleeper};
+use watch::{CapturedScreen, Screen, TerminalScreen};
 
 /// Command enum for shell commands
 #[derive(Debug, Clone, PartialEq)]
@@ -9,37 +28,98 @@ pub enum Command {
     // Process Management
     Fork { ppid: u32 },
     Ps,
+    PsState { state: ProcessState },
     Run { pid: u32 },
     Block { pid: u32 },
     Unblock { pid: u32 },
-    Kill { pid: u32 },
+    Kill { pid: u32, force: bool },
+    KillTree { pid: u32 },
+    Sleep { pid: u32, ms: u64 },
+    Stop { pid: u32 },
+    Cont { pid: u32 },
     Info { pid: u32 },
+    Stat { pid: u32 },
+    Rename { pid: u32, name: String },
+    Su { user: String },
 
     // Scheduler Operations
     Queues,
-    Schedule { cycles: u32 },
+    Schedule { cycles: u32, no_boost: bool },
+    ScheduleFine { ms: u64 },
+    WaitAll { max_cycles: Option<u32> },
 
     // Scheduler Control
     Nice { pid: u32, priority: u8 },
+    Demote { pid: u32 },
+    Promote { pid: u32 },
+    Chrt { pid: u32, class: SchedClass },
     SchedStats,
+    SchedStatsWatch { frames: u32 },
+    Occupancy,
+    Goodput,
+    SwitchCost { ms: u32 },
+    Verbose { level: u8 },
+    Rebalance,
+    At { tick: u64, line: String },
+    AtList,
+    Sparkline { queue: Option<usize> },
+    Heatmap,
+    Freeze,
+    Thaw,
+    CgroupCreate { name: String, cpu_limit: u64 },
+    CgroupAdd { name: String, pid: u32 },
+    CgroupStats { name: String },
+
+    // Synchronization
+    Lock { pid: u32, resource: String },
+    Unlock { pid: u32, resource: String },
+    Inherit { enabled: bool },
+    Autonice { enabled: bool },
+    SemCreate { name: String, count: u32 },
+    SemWait { name: String, pid: u32 },
+    SemSignal { name: String },
+    Deadlock,
 
     // Programs
     Programs,
-    RunProgram { program_name: String },
+    ProgramSearch { query: String },
+    RunProgram { program_name: String, count: u32 },
+    UnregisterProgram { program_name: String },
+    EditProgram { program_name: String, usage: f32 },
+    ScriptProgram { program_name: String, script: Vec<bool> },
 
     // Statistics
     Stats,
+    StatsSorted { key: MetricSortKey },
+    StatsJson,
+    StatsSince { tick: u64 },
     Metrics { pid: u32 },
+    Usage,
     ResetStats,
+    ResetMetrics { pid: u32 },
+    Validate,
+    FindOrphans,
+    Warmup { cycles: u32 },
 
     // System
+    Bench { csv: bool, repeat: u32 },
+    Replay { path: String, speed: f64 },
+    TraceExport { path: String },
+    History,
+    HistorySave { path: String },
+    HistoryLoad { path: String },
+    SelfTest,
+    Seed { value: u64 },
+    Uptime,
+    Subshell,
+    ExitSubshell { merge: bool },
     Help,
     Exit,
 }
 
 /// Parse command from user input
 pub fn parse_command(input: &str) -> Option<Command> {
-    let parts: Vec<&str> = input.trim().split_whitespace().collect();
+    let parts: Vec<&str> = input.split_whitespace().collect();
 
     if parts.is_empty() {
         return None;
@@ -53,7 +133,10 @@ pub fn parse_command(input: &str) -> Option<Command> {
                 Some(Command::Fork { ppid: 1 })
             }
         }
-        "ps" => Some(Command::Ps),
+        "ps" => match parts.get(1) {
+            Some(state) => state.parse().ok().map(|state| Command::PsState { state }),
+            None => Some(Command::Ps),
+        },
         "run" => {
             parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Run { pid })
         }
@@ -63,43 +146,332 @@ pub fn parse_command(input: &str) -> Option<Command> {
         "unblock" => {
             parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Unblock { pid })
         }
+        "sleep" => {
+            let pid = parts.get(1)?.parse::<u32>().ok()?;
+            let ms = parts.get(2)?.parse::<u64>().ok()?;
+            Some(Command::Sleep { pid, ms })
+        }
+        "stop" => parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Stop { pid }),
+        "cont" => parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Cont { pid }),
+        "kill" if parts.get(1) == Some(&"-r") => {
+            parts.get(2)?.parse::<u32>().ok().map(|pid| Command::KillTree { pid })
+        }
         "kill" => {
-            parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Kill { pid })
+            let force = parts[1..].contains(&"--now");
+            let pid = parts[1..].iter().find(|p| **p != "--now")?.parse::<u32>().ok()?;
+            Some(Command::Kill { pid, force })
         }
         "info" => {
             parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Info { pid })
         }
+        "stat" => parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Stat { pid }),
+        "rename" if parts.len() == 3 => {
+            let pid = parts[1].parse::<u32>().ok()?;
+            Some(Command::Rename { pid, name: parts[2].to_string() })
+        }
+        "su" => parts.get(1).map(|user| Command::Su { user: user.to_string() }),
         "queues" => Some(Command::Queues),
         "schedule" => {
-            parts.get(1)?.parse::<u32>().ok().map(|cycles| Command::Schedule { cycles })
+            let cycles = parts.get(1)?.parse::<u32>().ok()?;
+            let no_boost = match parts.get(2) {
+                Some(&"--no-boost") => true,
+                Some(_) => return None,
+                None => false,
+            };
+            Some(Command::Schedule { cycles, no_boost })
         }
+        "schedule_fine" => parts.get(1)?.parse::<u64>().ok().map(|ms| Command::ScheduleFine { ms }),
+        "waitall" => match parts.get(1) {
+            Some(arg) => arg.parse::<u32>().ok().map(|max| Command::WaitAll { max_cycles: Some(max) }),
+            None => Some(Command::WaitAll { max_cycles: None }),
+        },
         "nice" => {
             let pid = parts.get(1)?.parse::<u32>().ok()?;
             let priority = parts.get(2)?.parse::<u8>().ok()?;
             Some(Command::Nice { pid, priority })
         }
+        "demote" => parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Demote { pid }),
+        "promote" => parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Promote { pid }),
+        "chrt" => {
+            let pid = parts.get(1)?.parse::<u32>().ok()?;
+            let class = parts.get(2)?.parse::<SchedClass>().ok()?;
+            Some(Command::Chrt { pid, class })
+        }
+        "sched_stats" if parts.get(1) == Some(&"--watch") => {
+            let frames = parts.get(2)?.parse::<u32>().ok().filter(|&n| n > 0)?;
+            Some(Command::SchedStatsWatch { frames })
+        }
         "sched_stats" => Some(Command::SchedStats),
+        "occupancy" => Some(Command::Occupancy),
+        "goodput" => Some(Command::Goodput),
+        "verbose" => parts.get(1)?.parse::<u8>().ok().map(|level| Command::Verbose { level }),
+        "rebalance" => Some(Command::Rebalance),
+        "switch_cost" => parts.get(1)?.parse::<u32>().ok().map(|ms| Command::SwitchCost { ms }),
+        "heatmap" => Some(Command::Heatmap),
+        "freeze" => Some(Command::Freeze),
+        "thaw" => Some(Command::Thaw),
+        "at" if parts.len() == 1 => Some(Command::AtList),
+        "at" => {
+            let tick = parts.get(1)?.parse::<u64>().ok()?;
+            let line = parts.get(2..)?.join(" ");
+            parse_command(&line)?;
+            Some(Command::At { tick, line })
+        }
+        "sparkline" => match parts.get(1) {
+            Some(queue) => queue.parse::<usize>().ok().map(|q| Command::Sparkline { queue: Some(q) }),
+            None => Some(Command::Sparkline { queue: None }),
+        },
+        "lock" => {
+            let pid = parts.get(1)?.parse::<u32>().ok()?;
+            let resource = parts.get(2)?.to_string();
+            Some(Command::Lock { pid, resource })
+        }
+        "unlock" => {
+            let pid = parts.get(1)?.parse::<u32>().ok()?;
+            let resource = parts.get(2)?.to_string();
+            Some(Command::Unlock { pid, resource })
+        }
+        "inherit" => match parts.get(1) {
+            Some(&"on") => Some(Command::Inherit { enabled: true }),
+            Some(&"off") => Some(Command::Inherit { enabled: false }),
+            _ => None,
+        },
+        "autonice" => match parts.get(1) {
+            Some(&"on") => Some(Command::Autonice { enabled: true }),
+            Some(&"off") => Some(Command::Autonice { enabled: false }),
+            _ => None,
+        },
+        "cgroup_create" => {
+            let name = parts.get(1)?.to_string();
+            let cpu_limit = parts.get(2)?.parse::<u64>().ok()?;
+            Some(Command::CgroupCreate { name, cpu_limit })
+        }
+        "cgroup_add" => {
+            let name = parts.get(1)?.to_string();
+            let pid = parts.get(2)?.parse::<u32>().ok()?;
+            Some(Command::CgroupAdd { name, pid })
+        }
+        "cgroup_stats" => parts.get(1).map(|name| Command::CgroupStats { name: name.to_string() }),
+        "sem_create" => {
+            let name = parts.get(1)?.to_string();
+            let count = parts.get(2)?.parse::<u32>().ok()?;
+            Some(Command::SemCreate { name, count })
+        }
+        "sem_wait" => {
+            let name = parts.get(1)?.to_string();
+            let pid = parts.get(2)?.parse::<u32>().ok()?;
+            Some(Command::SemWait { name, pid })
+        }
+        "sem_signal" => parts.get(1).map(|name| Command::SemSignal { name: name.to_string() }),
+        "deadlock" => Some(Command::Deadlock),
+        "programs" if parts.len() > 1 => Some(Command::ProgramSearch { query: parts[1..].join(" ") }),
         "programs" => Some(Command::Programs),
         "run_program" => {
-            parts.get(1).map(|s| Command::RunProgram { program_name: s.to_string() })
+            let program_name = parts.get(1)?.to_string();
+            let count = match parts.get(2) {
+                Some(n) => n.parse::<u32>().ok().filter(|&n| n > 0)?,
+                None => 1,
+            };
+            Some(Command::RunProgram { program_name, count })
+        }
+        "unregister_program" => {
+            parts.get(1).map(|s| Command::UnregisterProgram { program_name: s.to_string() })
+        }
+        "edit_program" => {
+            let program_name = parts.get(1)?.to_string();
+            let usage = parts.get(2)?.parse::<f32>().ok()?;
+            Some(Command::EditProgram { program_name, usage })
+        }
+        "script_program" => {
+            let program_name = parts.get(1)?.to_string();
+            let script = parts.get(2)?
+                .chars()
+                .map(|c| match c.to_ascii_uppercase() {
+                    'T' => Some(true),
+                    'F' => Some(false),
+                    _ => None,
+                })
+                .collect::<Option<Vec<bool>>>()?;
+            Some(Command::ScriptProgram { program_name, script })
         }
-        "stats" => Some(Command::Stats),
+        "stats" => match parts.get(1) {
+            Some(&"--sort") => parts.get(2)?.parse().ok().map(|key| Command::StatsSorted { key }),
+            Some(&"--json") if parts.len() == 2 => Some(Command::StatsJson),
+            Some(&"--since") => parts.get(2)?.parse::<u64>().ok().map(|tick| Command::StatsSince { tick }),
+            Some(_) => None,
+            None => Some(Command::Stats),
+        },
         "metrics" => {
             parts.get(1)?.parse::<u32>().ok().map(|pid| Command::Metrics { pid })
         }
+        "usage" => Some(Command::Usage),
         "reset_stats" => Some(Command::ResetStats),
+        "validate" => Some(Command::Validate),
+        "find_orphans" => Some(Command::FindOrphans),
+        "reset_metrics" => parts.get(1)?.parse::<u32>().ok().map(|pid| Command::ResetMetrics { pid }),
+        "warmup" => parts.get(1)?.parse::<u32>().ok().map(|cycles| Command::Warmup { cycles }),
+        "bench" => {
+            let mut csv = false;
+            let mut repeat = 1;
+            let mut args = parts[1..].iter();
+            while let Some(&arg) = args.next() {
+                match arg {
+                    "--csv" => csv = true,
+                    "--repeat" => repeat = args.next()?.parse::<u32>().ok().filter(|&n| n > 0)?,
+                    _ => return None,
+                }
+            }
+            Some(Command::Bench { csv, repeat })
+        }
+        "replay" => {
+            let path = parts.get(1)?.to_string();
+            let speed = match parts.get(2) {
+                Some(&"--speed") => parts.get(3)?.parse::<f64>().ok().filter(|s| *s > 0.0)?,
+                Some(_) => return None,
+                None => 1.0,
+            };
+            Some(Command::Replay { path, speed })
+        }
+        "trace_export" if parts.len() == 2 => Some(Command::TraceExport { path: parts[1].to_string() }),
+        "history" => match parts[1..] {
+            [] => Some(Command::History),
+            ["--save", path] => Some(Command::HistorySave { path: path.to_string() }),
+            ["--load", path] => Some(Command::HistoryLoad { path: path.to_string() }),
+            _ => None,
+        },
+        "selftest" => Some(Command::SelfTest),
+        "seed" => parts.get(1)?.parse::<u64>().ok().map(|value| Command::Seed { value }),
+        "uptime" => Some(Command::Uptime),
         "help" => Some(Command::Help),
-        "exit" | "quit" => Some(Command::Exit),
+        "subshell" => Some(Command::Subshell),
+        "exit" | "quit" => match parts.get(1) {
+            Some(&"merge") => Some(Command::ExitSubshell { merge: true }),
+            Some(&"discard") => Some(Command::ExitSubshell { merge: false }),
+            Some(_) => None,
+            None => Some(Command::Exit),
+        },
         _ => None,
     }
 }
 
+/// Color success lines green and error lines red for terminal display
+fn paint(output: &str) -> String {
+    let mut painted = String::new();
+    for line in output.split_inclusive('\n') {
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        let color = if text.starts_with("✓ ") {
+            Some("32")
+        } else if text.starts_with("Error: ") {
+            Some("31")
+        } else {
+            None
+        };
+
+        match color {
+            Some(code) => painted.push_str(&format!("\x1b[{}m{}\x1b[0m{}", code, text, newline)),
+            None => painted.push_str(line),
+        }
+    }
+    painted
+}
+
+/// Default cycle cap for `waitall` so workloads that never finish can't hang the shell
+pub const WAITALL_MAX_CYCLES: u32 = 10_000;
+
+/// Most instances a single `run_program` may start; larger counts are rejected
+pub const RUN_PROGRAM_MAX_INSTANCES: u32 = 1_000;
+
+/// Most cycles a single `schedule` runs; larger requests are clamped
+pub const SCHEDULE_MAX_CYCLES: u32 = 100_000;
+
+/// Most frames a single `sched_stats --watch` draws; larger requests are clamped
+pub const WATCH_MAX_FRAMES: u32 = 1_000;
+
+/// Wall-clock pause between `sched_stats --watch` frames (ms)
+pub const WATCH_FRAME_INTERVAL_MS: u64 = 500;
+
+/// Simulated time between preemption and wakeup checks in `schedule_fine` (ms)
+pub const FINE_SLICE_MS: u64 = 2;
+
+/// Default number of scheduling cycles a process gets to exit after SIGTERM
+pub const DEFAULT_KILL_GRACE_CYCLES: u32 = 3;
+
+/// Default number of PIDs `queues` lists per level before truncating
+pub const DEFAULT_QUEUE_DISPLAY_LIMIT: usize = 8;
+
+/// How much `schedule` prints: 0 warnings only, 1 a closing summary, 2 every
+/// dispatch (the default), 3 every dispatch plus queue depths after it
+pub const DEFAULT_VERBOSITY: u8 = 2;
+
+/// Highest level `verbose` accepts
+pub const MAX_VERBOSITY: u8 = 3;
+
+/// With `autonice on`, nice values are recomputed every this many cycles
+pub const AUTONICE_INTERVAL: u64 = 5;
+
+/// Sim time a process must spend blocked on I/O before `unblock` gives it
+/// interactive credit: a place at the very front of Q0
+pub const INTERACTIVE_CREDIT_MS: u64 = 200;
+
+/// One process's recorded metrics, for callers that want numbers rather
+/// than the formatted `stats` report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessMetricsRow {
+    pub pid: u32,
+    pub name: String,
+    pub terminated: bool,
+    pub turnaround_time: u64,
+    pub response_time: u64,
+    pub waiting_time: u64,
+    pub execution_time: u64,
+    pub context_switches: u32,
+    pub queue_changes: u32,
+}
+
 /// OS Shell
 pub struct Shell {
     manager: ProcessManager,
     scheduler: MLFQScheduler,
     stats: crate::scheduler::metrics::SchedulerStats,
+    registry: ProgramRegistry,
+    process_programs: BTreeMap<u32, Program>,
+    current_user: Option<String>,
+    pending_kills: BTreeMap<u32, u32>,
+    kill_grace_cycles: u32,
+    switch_cost: u32,
+    verbosity: u8,
+    queue_display_limit: usize,
+    clock: u64,
+    locks: LockTable,
+    priority_inheritance: bool,
+    autonice: bool,
+    inherited: BTreeMap<u32, usize>,
+    semaphores: SemaphoreTable,
+    cgroups: CgroupTable,
+    parked: BTreeMap<u32, usize>,
+    blocked_since: BTreeMap<u32, u64>,
+    sleepers: BTreeMap<u32, u64>,
+    sleeper: Box<dyn Sleeper>,
+    screen: Box<dyn Screen>,
+    /// What `sched_stats --watch` does between frames
+    watch_step: fn(&mut Shell),
+    rng: StdRng,
+    seed: Option<u64>,
+    timed: Vec<(u64, String)>,
+    process_limit: Option<usize>,
+    memory_budget: Option<u64>,
+    colorize: bool,
+    config: ShellConfig,
+    history: Vec<String>,
+    cancel: Arc<AtomicBool>,
     running: bool,
+    /// While set, nothing is dispatched and the session state stays put
+    frozen: bool,
+    /// The session a `subshell` was opened from, restored on `exit discard`
+    parent: Option<Box<Shell>>,
 }
 
 impl Shell {
@@ -109,6 +481,10 @@ impl Shell {
         let mut stats = crate::scheduler::metrics::SchedulerStats::new();
 
         let init_pid = manager.create_process(0);
+        if let Some(init) = manager.get_process_mut(init_pid) {
+            init.name = "init".to_string();
+            scheduler.set_nice(init_pid, init.priority);
+        }
         scheduler.add_process(init_pid);
         stats.record_process_created(init_pid);
 
@@ -116,29 +492,347 @@ impl Shell {
             manager,
             scheduler,
             stats,
+            registry: ProgramRegistry::new(),
+            process_programs: BTreeMap::new(),
+            current_user: None,
+            pending_kills: BTreeMap::new(),
+            kill_grace_cycles: DEFAULT_KILL_GRACE_CYCLES,
+            switch_cost: 0,
+            verbosity: DEFAULT_VERBOSITY,
+            queue_display_limit: DEFAULT_QUEUE_DISPLAY_LIMIT,
+            clock: 0,
+            locks: LockTable::new(),
+            priority_inheritance: false,
+            autonice: false,
+            inherited: BTreeMap::new(),
+            semaphores: SemaphoreTable::new(),
+            cgroups: CgroupTable::new(),
+            parked: BTreeMap::new(),
+            blocked_since: BTreeMap::new(),
+            sleepers: BTreeMap::new(),
+            sleeper: Box::new(ThreadSleeper),
+            screen: Box::new(TerminalScreen),
+            watch_step: |shell| {
+                shell.cmd_schedule(1, false);
+            },
+            rng: StdRng::from_entropy(),
+            seed: None,
+            timed: Vec::new(),
+            process_limit: None,
+            memory_budget: None,
+            colorize: false,
+            config: ShellConfig::default(),
+            history: Vec::new(),
+            cancel: Arc::new(AtomicBool::new(false)),
             running: true,
+            frozen: false,
+            parent: None,
+        }
+    }
+
+    /// A shell built entirely from `config`, so a saved config file
+    /// reproduces the same environment
+    pub fn from_config(config: &ShellConfig) -> Result<Self, String> {
+        let mut shell = match config.seed {
+            Some(seed) => Shell::with_seed(seed),
+            None => Shell::new(),
+        };
+
+        // Re-admit init under the configured scheduler
+        shell.scheduler = MLFQScheduler::with_config(&config.scheduler)?;
+        shell.admit(1);
+
+        shell.process_limit = config.process_limit;
+        shell.memory_budget = config.memory_budget;
+        shell.colorize = config.colorize;
+        shell.config = config.clone();
+        Ok(shell)
+    }
+
+    /// A shell whose scheduling decisions are reproducible from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        let mut shell = Shell::new();
+        shell.rng = StdRng::seed_from_u64(seed);
+        shell.seed = Some(seed);
+        shell.config.seed = Some(seed);
+        shell
+    }
+
+    /// Flag that stops a running `schedule` or `waitall` at the next cycle
+    /// boundary when set (e.g. from a signal handler). It is cleared once
+    /// a run has stopped for it.
+    pub fn cancel_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancel)
+    }
+
+    /// Lines run through `execute_line` that parsed as commands, oldest first
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Re-run this shell's command history against `target`
+    pub fn replay_into(&self, target: &mut Shell) {
+        for line in &self.history {
+            target.execute_line(line);
+        }
+    }
+
+    /// A fresh shell built from the same config, with this shell's history
+    /// replayed into it. The result only matches this shell if it was seeded.
+    pub fn replay_history(&self) -> Shell {
+        let mut replayed = Shell::from_config(&self.config)
+            .expect("config was valid when this shell was built");
+        self.replay_into(&mut replayed);
+        replayed
+    }
+
+    /// How many subshells deep the session is (0 = the top-level shell)
+    pub fn subshell_depth(&self) -> usize {
+        match &self.parent {
+            Some(parent) => parent.subshell_depth() + 1,
+            None => 0,
+        }
+    }
+
+    /// A copy of the whole simulation to run a subshell against. The
+    /// replay sleeper and watch screen can't be cloned, so the caller swaps
+    /// them across.
+    fn session_copy(&self) -> Shell {
+        Shell {
+            manager: self.manager.clone(),
+            scheduler: self.scheduler.clone(),
+            stats: self.stats.clone(),
+            registry: self.registry.clone(),
+            process_programs: self.process_programs.clone(),
+            current_user: self.current_user.clone(),
+            pending_kills: self.pending_kills.clone(),
+            kill_grace_cycles: self.kill_grace_cycles,
+            switch_cost: self.switch_cost,
+            verbosity: self.verbosity,
+            queue_display_limit: self.queue_display_limit,
+            clock: self.clock,
+            locks: self.locks.clone(),
+            priority_inheritance: self.priority_inheritance,
+            autonice: self.autonice,
+            inherited: self.inherited.clone(),
+            semaphores: self.semaphores.clone(),
+            cgroups: self.cgroups.clone(),
+            parked: self.parked.clone(),
+            blocked_since: self.blocked_since.clone(),
+            sleepers: self.sleepers.clone(),
+            sleeper: Box::new(ThreadSleeper),
+            screen: Box::new(TerminalScreen),
+            watch_step: self.watch_step,
+            rng: self.rng.clone(),
+            seed: self.seed,
+            timed: self.timed.clone(),
+            process_limit: self.process_limit,
+            memory_budget: self.memory_budget,
+            colorize: self.colorize,
+            config: self.config.clone(),
+            history: self.history.clone(),
+            cancel: Arc::clone(&self.cancel),
+            running: self.running,
+            frozen: self.frozen,
+            parent: None,
+        }
+    }
+
+    /// Continue in a copy of the current state; the original is kept aside
+    /// until `exit merge` or `exit discard`
+    fn cmd_subshell(&mut self) -> String {
+        let mut child = self.session_copy();
+        std::mem::swap(&mut child.sleeper, &mut self.sleeper);
+        std::mem::swap(&mut child.screen, &mut self.screen);
+        let parent = std::mem::replace(self, child);
+        self.parent = Some(Box::new(parent));
+        format!(
+            "✓ Entered subshell (depth {}); 'exit merge' keeps changes, 'exit discard' drops them",
+            self.subshell_depth()
+        )
+    }
+
+    fn cmd_exit_subshell(&mut self, merge: bool) -> String {
+        let Some(mut parent) = self.parent.take() else {
+            return "Error: Not in a subshell".to_string();
+        };
+        std::mem::swap(&mut parent.sleeper, &mut self.sleeper);
+        std::mem::swap(&mut parent.screen, &mut self.screen);
+
+        if merge {
+            // Keep this session's state and history; only the nesting unwinds
+            self.parent = parent.parent.take();
+            return format!("✓ Subshell changes merged (depth {})", self.subshell_depth());
+        }
+
+        *self = *parent;
+        // The `subshell` line that opened the discarded session goes with it,
+        // so replaying the history doesn't reopen it
+        if self.history.last().is_some_and(|line| line == "subshell") {
+            self.history.pop();
+        }
+        format!("✓ Subshell discarded (depth {})", self.subshell_depth())
+    }
+
+    /// Run a full input line: a command optionally followed by `| filter`
+    /// stages and a trailing `> file` or `>> file` redirection
+    pub fn execute_line(&mut self, line: &str) -> String {
+        let line_with_redirect = line.trim();
+        let (line, redirect) = match pipeline::split_redirect(line) {
+            Ok(split) => split,
+            Err(e) => return format!("Error: {}", e),
+        };
+
+        let mut stages = line.split('|');
+        let command = stages.next().unwrap_or("").trim();
+
+        let Some(cmd) = parse_command(command) else {
+            return format!("Error: Unknown command '{}'. Type 'help' for available commands.", command);
+        };
+
+        let mut filters = Vec::new();
+        for stage in stages {
+            match pipeline::parse_filter(stage) {
+                Some(filter) => filters.push(filter),
+                None => return format!("Error: Unknown filter '{}'", stage.trim()),
+            }
+        }
+
+        // History commands aren't recorded, so a saved history never
+        // reloads itself when replayed
+        if !matches!(cmd, Command::History | Command::HistorySave { .. } | Command::HistoryLoad { .. }) {
+            self.history.push(line_with_redirect.to_string());
+        }
+        // A live view draws straight to the screen; when its output is piped
+        // or redirected, capture what it draws and treat that as output
+        let capture = (!filters.is_empty() || redirect.is_some())
+            && matches!(cmd, Command::Replay { .. } | Command::SchedStatsWatch { .. });
+        let captured = capture.then(|| {
+            let screen = CapturedScreen::default();
+            let buffer = Rc::clone(&screen.buffer);
+            (buffer, std::mem::replace(&mut self.screen, Box::new(screen)))
+        });
+
+        let mut output = self.execute(cmd);
+        if let Some((buffer, screen)) = captured {
+            self.screen = screen;
+            output.insert_str(0, &buffer.borrow());
+        }
+        for filter in &filters {
+            output = filter.apply(&output);
+        }
+
+        let output = match redirect {
+            Some(redirect) => match redirect.write(&output) {
+                Ok(()) => format!("✓ Output written to {}", redirect.path()),
+                Err(e) => format!("Error: Cannot write {}: {}", redirect.path(), e),
+            },
+            None => output,
+        };
+
+        if self.colorize {
+            paint(&output)
+        } else {
+            output
         }
     }
 
     pub fn execute(&mut self, cmd: Command) -> String {
         match cmd {
             Command::Fork { ppid } => self.cmd_fork(ppid),
-            Command::Ps => self.cmd_ps(),
-            Command::Run { pid } => self.cmd_run(pid),
+            Command::Ps => self.cmd_ps(None),
+            Command::PsState { state } => self.cmd_ps(Some(state)),
+            Command::Run { pid } => self.cmd_run(pid).unwrap_or_else(|e| e),
             Command::Block { pid } => self.cmd_block(pid),
-            Command::Unblock { pid } => self.cmd_unblock(pid),
-            Command::Kill { pid } => self.cmd_kill(pid),
-            Command::Info { pid } => self.cmd_info(pid),
+            Command::Unblock { pid } => self.cmd_unblock(pid).unwrap_or_else(|e| e),
+            Command::Kill { pid, force } => self.cmd_kill(pid, force).unwrap_or_else(|e| e),
+            Command::KillTree { pid } => self.cmd_kill_tree(pid).unwrap_or_else(|e| e),
+            Command::Sleep { pid, ms } => self.cmd_sleep(pid, ms),
+            Command::Stop { pid } => self.cmd_stop(pid),
+            Command::Cont { pid } => self.cmd_cont(pid).unwrap_or_else(|e| e),
+            Command::Info { pid } => self.cmd_info(pid).unwrap_or_else(|e| e),
+            Command::Stat { pid } => self.cmd_stat(pid).unwrap_or_else(|e| e),
+            Command::Rename { pid, name } => self.cmd_rename(pid, &name).unwrap_or_else(|e| e),
+            Command::Su { user } => self.cmd_su(user),
             Command::Queues => self.cmd_queues(),
-            Command::Schedule { cycles } => self.cmd_schedule(cycles),
-            Command::Nice { pid, priority } => self.cmd_nice(pid, priority),
+            Command::Schedule { cycles, no_boost } => self.cmd_schedule(cycles, no_boost),
+            Command::ScheduleFine { ms } => self.cmd_schedule_fine(ms),
+            Command::WaitAll { max_cycles } => self.cmd_waitall(max_cycles.unwrap_or(WAITALL_MAX_CYCLES)),
+            Command::Demote { pid } => self.cmd_shift(pid, true).unwrap_or_else(|e| e),
+            Command::Promote { pid } => self.cmd_shift(pid, false).unwrap_or_else(|e| e),
+            Command::Nice { pid, priority } => self.cmd_nice(pid, priority).unwrap_or_else(|e| e),
+            Command::Chrt { pid, class } => self.cmd_chrt(pid, class).unwrap_or_else(|e| e),
             Command::SchedStats => self.cmd_sched_stats(),
+            Command::SchedStatsWatch { frames } => self.cmd_sched_stats_watch(frames),
+            Command::Occupancy => self.stats.occupancy_report(),
+            Command::Goodput => self.stats.goodput_report(),
+            Command::SwitchCost { ms } => {
+                self.set_switch_cost(ms);
+                format!("✓ Each context switch now costs {}ms", ms)
+            }
+            Command::Verbose { level } => self.cmd_verbose(level),
+            Command::Rebalance => {
+                let (removed, resynced) = self.scheduler.rebalance();
+                format!(
+                    "✓ Scheduler rebalanced: removed {} phantom entries, re-synced {}",
+                    removed, resynced
+                )
+            }
+            Command::At { tick, line } => self.cmd_at(tick, line),
+            Command::AtList => self.cmd_at_list(),
+            Command::Sparkline { queue } => self.cmd_sparkline(queue),
+            Command::Heatmap => self.cmd_heatmap(),
+            Command::Freeze => self.cmd_freeze(true),
+            Command::Thaw => self.cmd_freeze(false),
+            Command::CgroupCreate { name, cpu_limit } => self.cmd_cgroup_create(&name, cpu_limit),
+            Command::CgroupAdd { name, pid } => self.cmd_cgroup_add(&name, pid).unwrap_or_else(|e| e),
+            Command::CgroupStats { name } => self.cmd_cgroup_stats(&name).unwrap_or_else(|e| e),
+            Command::Lock { pid, resource } => self.cmd_lock(pid, &resource),
+            Command::Unlock { pid, resource } => self.cmd_unlock(pid, &resource),
+            Command::Inherit { enabled } => self.cmd_inherit(enabled),
+            Command::Autonice { enabled } => self.cmd_autonice(enabled),
+            Command::SemCreate { name, count } => self.cmd_sem_create(&name, count),
+            Command::SemWait { name, pid } => self.cmd_sem_wait(&name, pid),
+            Command::SemSignal { name } => self.cmd_sem_signal(&name),
+            Command::Deadlock => self.cmd_deadlock(),
             Command::Programs => self.cmd_programs(),
-            Command::RunProgram { program_name } => self.cmd_run_program(&program_name),
+            Command::ProgramSearch { query } => self.cmd_program_search(&query),
+            Command::RunProgram { program_name, count } => self.cmd_run_program(&program_name, count),
+            Command::UnregisterProgram { program_name } => self.cmd_unregister_program(&program_name),
+            Command::EditProgram { program_name, usage } => self.cmd_edit_program(&program_name, usage),
+            Command::ScriptProgram { program_name, script } => self.cmd_script_program(&program_name, script),
             Command::Stats => self.cmd_stats(),
+            Command::StatsSorted { key } => {
+                self.stats.summary_report_sorted(key) + &self.stats.in_flight_report(self.clock)
+            }
+            Command::StatsJson => self.stats.to_json(),
+            Command::StatsSince { tick } => self.cmd_stats_since(tick),
             Command::Metrics { pid } => self.cmd_metrics(pid),
+            Command::Usage => self.cmd_usage(),
             Command::ResetStats => self.cmd_reset_stats(),
+            Command::ResetMetrics { pid } => self.cmd_reset_metrics(pid),
+            Command::Validate => match self.stats.validate() {
+                Ok(()) => "✓ Accounting invariants hold".to_string(),
+                Err(e) => format!("Error: {}", e),
+            },
+            Command::FindOrphans => self.cmd_find_orphans(),
+            Command::Warmup { cycles } => self.cmd_warmup(cycles),
+            Command::Bench { csv, repeat } => self.cmd_bench(csv, repeat),
+            Command::Replay { path, speed } => self.cmd_replay(&path, speed),
+            Command::TraceExport { path } => self.cmd_trace_export(&path),
+            Command::History => self.cmd_history(),
+            Command::HistorySave { path } => self.cmd_history_save(&path),
+            Command::HistoryLoad { path } => self.cmd_history_load(&path),
+            Command::SelfTest => self.cmd_selftest(),
+            Command::Seed { value } => self.cmd_seed(value),
+            Command::Uptime => self.cmd_uptime(),
             Command::Help => self.cmd_help(),
+            Command::Subshell => self.cmd_subshell(),
+            Command::ExitSubshell { merge } => self.cmd_exit_subshell(merge),
+            Command::Exit if self.parent.is_some() => format!(
+                "Subshell (depth {}) still open: 'exit merge' keeps its changes, 'exit discard' drops them",
+                self.subshell_depth()
+            ),
             Command::Exit => {
                 self.running = false;
                 "Exiting OS simulator...".to_string()
@@ -154,138 +848,493 @@ impl Shell {
         if self.manager.get_process(ppid).is_none() && ppid != 1 {
             return format!("Error: Parent process {} does not exist", ppid);
         }
+        if let Err(e) = self.check_capacity() {
+            return e;
+        }
 
         let new_pid = self.manager.create_process(ppid);
-        self.scheduler.add_process(new_pid);
-        self.stats.record_process_created(new_pid);
+        self.apply_current_user(new_pid);
+        self.admit(new_pid);
+        self.stats.record_process_created_at(new_pid, self.clock);
 
         format!("✓ Process created: PID {} (parent: {})", new_pid, ppid)
     }
 
-    fn cmd_ps(&self) -> String {
-        let mut output = String::from(
-            "PID  PPID STATE       PRIORITY QUEUE TOTAL_TIME\n\
-             ─────────────────────────────────────────────────\n"
-        );
+    fn cmd_ps(&self, filter: Option<ProcessState>) -> String {
+        let processes = self.manager
+            .all_processes()
+            .into_iter()
+            .filter(|process| filter.is_none_or(|state| process.state == state));
+        self.process_table(processes)
+    }
 
-        for process in self.manager.all_processes() {
-            let queue = self.scheduler
-                .get_process_queue(process.pid)
-                .map_or("N/A".to_string(), |q| format!("Q{}", q));
+    /// Render processes as a table whose columns grow to fit the widest
+    /// value, so large PIDs and long state names stay aligned
+    fn process_table<'a>(&self, processes: impl Iterator<Item = &'a Process>) -> String {
+        const HEADERS: [&str; 7] = ["PID", "PPID", "STATE", "PRIORITY", "QUEUE", "TOTAL_TIME", "NAME"];
+        const MIN_WIDTHS: [usize; 7] = [4, 4, 11, 8, 5, 10, 4];
 
-            output.push_str(&format!(
-                "{:<4} {:<4} {:<11?} {:<8} {:<6} {:<10}\n",
-                process.pid,
-                process.ppid,
-                process.state,
-                process.priority,
-                queue,
-                process.total_time
-            ));
+        let rows: Vec<[String; 7]> = processes
+            .map(|process| {
+                let queue = self.scheduler
+                    .get_process_queue(process.pid)
+                    .map_or("N/A".to_string(), |q| format!("Q{}", q));
+                [
+                    process.pid.to_string(),
+                    process.ppid.to_string(),
+                    process.state.name().to_string(),
+                    process.priority.to_string(),
+                    queue,
+                    process.total_time.to_string(),
+                    process.name.clone(),
+                ]
+            })
+            .collect();
+
+        let mut widths = MIN_WIDTHS;
+        for (i, header) in HEADERS.iter().enumerate() {
+            let widest = rows.iter().map(|row| row[i].chars().count()).max().unwrap_or(0);
+            widths[i] = widths[i].max(header.len()).max(widest);
+        }
+
+        let format_row = |cells: &[&str]| {
+            let line = cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("{}\n", line.trim_end())
+        };
+
+        let mut output = format_row(&HEADERS);
+        let total_width = widths.iter().sum::<usize>() + widths.len() - 1;
+        output.push_str(&"─".repeat(total_width));
+        output.push('\n');
+        for row in &rows {
+            let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            output.push_str(&format_row(&cells));
         }
 
         output
     }
 
-    fn cmd_run(&mut self, pid: u32) -> String {
-        match self.manager.get_process_mut(pid) {
-            Some(process) => {
-                if process.state == ProcessState::Terminated {
-                    return format!("Error: Cannot run terminated process {}", pid);
-                }
-                process.set_state(ProcessState::Running);
-                self.manager.set_running_process(pid);
-                self.stats.record_context_switch(pid);
-                format!("✓ Process {} is now running", pid)
-            }
-            None => format!("Error: Process {} not found", pid),
+    fn cmd_run(&mut self, pid: u32) -> Result<String, String> {
+        let process = self.manager.get_mut_or_error(pid)?;
+        if process.state == ProcessState::Terminated {
+            return Err(format!("Error: Cannot run terminated process {}", pid));
+        }
+
+        let priority = process.priority;
+        process.set_state(ProcessState::Running);
+        self.manager.set_running_process(pid);
+        self.stats.record_context_switch_at(pid, self.clock);
+
+        let mut output = format!("✓ Process {} is now running", pid);
+        if !self.scheduler.contains(pid) {
+            // Dropped from the scheduler (or parked): put it back so it gets
+            // dispatched again, at the level it was parked at if any
+            let queue = self.parked.remove(&pid).unwrap_or(priority as usize);
+            self.sleepers.remove(&pid);
+            self.scheduler.add_process_to_queue(pid, queue);
+            output.push_str(&format!("\n  • Re-enrolled in scheduler at Q{}", queue));
         }
+        Ok(output)
+    }
+
+    /// Note for a live process the manager knows about but no queue holds,
+    /// so it will never be dispatched
+    fn unscheduled_warning(&self, pid: u32) -> String {
+        format!("\n  ⚠ Process {} is not in any scheduler queue and will not be scheduled", pid)
     }
 
     fn cmd_block(&mut self, pid: u32) -> String {
-        match self.manager.get_process_mut(pid) {
-            Some(process) => {
-                process.set_state(ProcessState::Blocked);
-                format!("✓ Process {} blocked (waiting for I/O)", pid)
-            }
-            None => format!("Error: Process {} not found", pid),
+        if let Err(e) = self.check_can_park(pid) {
+            return e;
         }
+
+        self.park(pid, ProcessState::Blocked);
+        self.blocked_since.insert(pid, self.clock);
+        format!("✓ Process {} blocked (waiting for I/O)", pid)
     }
 
-    fn cmd_unblock(&mut self, pid: u32) -> String {
-        match self.manager.get_process_mut(pid) {
-            Some(process) => {
-                if process.state == ProcessState::Blocked {
-                    process.set_state(ProcessState::Ready);
-                    self.scheduler.process_yielded_early(pid);
-                    format!("✓ Process {} unblocked (promoted in scheduler)", pid)
-                } else {
-                    format!("Error: Process {} is not blocked", pid)
-                }
-            }
-            None => format!("Error: Process {} not found", pid),
+    fn cmd_unblock(&mut self, pid: u32) -> Result<String, String> {
+        if self.locks.waiting_on(pid).is_some() || self.semaphores.waiting_on(pid).is_some() {
+            return Err(format!("Error: Process {} is waiting on a lock or semaphore", pid));
         }
+        if self.manager.get_or_error(pid)?.state != ProcessState::Blocked {
+            return Err(format!("Error: Process {} is not blocked", pid));
+        }
+
+        // Re-enqueue at the level it blocked from, then credit the early
+        // yield; `credit_yield` takes it out of line first so it isn't queued twice
+        let blocked_for = self.blocked_since.remove(&pid).map_or(0, |since| self.clock - since);
+        self.unpark(pid);
+        if blocked_for < INTERACTIVE_CREDIT_MS {
+            self.scheduler.credit_yield(pid);
+            return Ok(format!("✓ Process {} unblocked (promoted in scheduler)", pid));
+        }
+
+        let before = self.scheduler.get_process_queue(pid);
+        self.scheduler.move_to_front(pid, 0);
+        if before != Some(0) {
+            self.stats.record_queue_change(pid, QueueChange::Boost);
+        }
+        Ok(format!(
+            "✓ Process {} unblocked after {}ms of I/O (interactive credit: front of Q0)",
+            pid, blocked_for
+        ))
+    }
+
+    fn cmd_sleep(&mut self, pid: u32, ms: u64) -> String {
+        if let Err(e) = self.check_can_park(pid) {
+            return e;
+        }
+
+        let wake_at = self.clock + ms;
+        self.park(pid, ProcessState::Sleeping);
+        self.sleepers.insert(pid, wake_at);
+        format!("✓ Process {} sleeping for {}ms (wakes at {}ms)", pid, ms, wake_at)
+    }
+
+    fn cmd_stop(&mut self, pid: u32) -> String {
+        if let Err(e) = self.check_can_park(pid) {
+            return e;
+        }
+
+        self.park(pid, ProcessState::Stopped);
+        format!("✓ Process {} stopped", pid)
+    }
+
+    fn cmd_cont(&mut self, pid: u32) -> Result<String, String> {
+        if self.manager.get_or_error(pid)?.state != ProcessState::Stopped {
+            return Err(format!("Error: Process {} is not stopped", pid));
+        }
+
+        self.unpark(pid);
+        Ok(format!("✓ Process {} continued", pid))
+    }
+
+    /// A process can only leave the run queues if it is alive and not already waiting
+    fn check_can_park(&self, pid: u32) -> Result<(), String> {
+        let process = self.manager.get_or_error(pid)?;
+        if process.state == ProcessState::Terminated {
+            return Err(format!("Error: Process {} is terminated", pid));
+        }
+        if self.parked.contains_key(&pid) {
+            return Err(format!("Error: Process {} is already {}", pid, process.state.name()));
+        }
+        Ok(())
+    }
+
+    /// A process can only queue on a lock or semaphore if it is alive and not already waiting
+    fn check_can_wait(&self, pid: u32) -> Result<(), String> {
+        if self.manager.get_or_error(pid)?.state == ProcessState::Terminated {
+            return Err(format!("Error: Process {} is terminated", pid));
+        }
+        if self.parked.contains_key(&pid) {
+            return Err(format!("Error: Process {} is already waiting", pid));
+        }
+        Ok(())
+    }
+
+    fn cmd_kill(&mut self, pid: u32, force: bool) -> Result<String, String> {
+        if pid == IDLE_PID {
+            return Err(format!("Error: Cannot kill the idle process (PID {})", IDLE_PID));
+        }
+        if pid == 1 {
+            return Err("Error: Cannot kill init process (PID 1)".to_string());
+        }
+        if self.manager.get_or_error(pid)?.state == ProcessState::Terminated {
+            return Err(format!("Error: Process {} is already terminated", pid));
+        }
+
+        if force || self.kill_grace_cycles == 0 {
+            self.terminate(pid);
+            return Ok(format!("✓ Process {} terminated (SIGKILL)", pid));
+        }
+
+        if let Some(remaining) = self.pending_kills.get(&pid) {
+            return Err(format!(
+                "Error: SIGTERM already pending for process {} ({} cycles left, use --now to force)",
+                pid, remaining
+            ));
+        }
+        self.pending_kills.insert(pid, self.kill_grace_cycles);
+        Ok(format!(
+            "✓ SIGTERM sent to process {} ({} cycles to exit before SIGKILL)",
+            pid, self.kill_grace_cycles
+        ))
     }
 
-    fn cmd_kill(&mut self, pid: u32) -> String {
+    /// SIGKILL `pid` and everything below it, deepest processes first
+    fn cmd_kill_tree(&mut self, pid: u32) -> Result<String, String> {
+        if pid == IDLE_PID {
+            return Err(format!("Error: Cannot kill the idle process (PID {})", IDLE_PID));
+        }
         if pid == 1 {
-            return "Error: Cannot kill init process (PID 1)".to_string();
+            return Err("Error: Cannot kill init process (PID 1)".to_string());
+        }
+        if self.manager.get_or_error(pid)?.state == ProcessState::Terminated {
+            return Err(format!("Error: Process {} is already terminated", pid));
+        }
+
+        let mut victims = vec![pid];
+        victims.extend(self.manager.descendants(pid));
+        victims.retain(|&p| p != 1);
+        for &victim in victims.iter().rev() {
+            self.terminate(victim);
+        }
+
+        victims.sort_unstable();
+        let list: Vec<String> = victims.iter().map(|p| p.to_string()).collect();
+        Ok(format!("✓ Terminated {} processes (SIGKILL): {}", victims.len(), list.join(", ")))
+    }
+
+    /// Count down SIGTERM grace windows, force-killing processes that outlive theirs
+    fn expire_kill_grace(&mut self, output: &mut String) {
+        let mut expired = Vec::new();
+        for (&pid, remaining) in self.pending_kills.iter_mut() {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                expired.push(pid);
+            }
+        }
+
+        for pid in expired {
+            self.pending_kills.remove(&pid);
+            let alive = self.manager
+                .get_process(pid)
+                .is_some_and(|p| p.state != ProcessState::Terminated);
+            if alive {
+                self.terminate(pid);
+                output.push_str(&format!("         • PID {} ignored SIGTERM → SIGKILL\n", pid));
+            }
         }
+    }
+
+    /// Set how many scheduling cycles a process gets to exit after SIGTERM (0 = immediate)
+    pub fn set_kill_grace(&mut self, cycles: u32) {
+        self.kill_grace_cycles = cycles;
+    }
+
+    /// Set how much CPU (ms) each dispatch spends switching before the
+    /// process runs (0 = free switches)
+    pub fn set_switch_cost(&mut self, ms: u32) {
+        self.switch_cost = ms;
+    }
 
+    /// Terminate a process, record its final metrics and drop it from the scheduler.
+    /// Execution time is already credited as the process runs, so only the
+    /// turnaround/response figures are recorded here.
+    fn terminate(&mut self, pid: u32) {
         if let Some(process) = self.manager.get_process(pid) {
             let turnaround = process.turnaround_time();
             let response = process.response_time().unwrap_or(0);
-            let execution = process.total_time as u64;
-
-            self.stats.record_execution_time(pid, execution);
             self.stats.record_process_terminated(pid, turnaround, response);
         }
 
-        if self.manager.terminate_process(pid) {
-            self.scheduler.remove_process(pid);
-            format!("✓ Process {} terminated", pid)
+        self.manager.terminate_process(pid);
+        self.manager.reparent_children(pid);
+        self.scheduler.release_process(pid);
+        self.pending_kills.remove(&pid);
+        self.parked.remove(&pid);
+        self.blocked_since.remove(&pid);
+        self.sleepers.remove(&pid);
+        self.inherited.remove(&pid);
+        self.semaphores.remove_waiter(pid);
+
+        // A dead process can't keep holding locks
+        for (_, next) in self.locks.release_all(pid) {
+            if let Some(next) = next {
+                self.unpark(next);
+            }
+        }
+    }
+
+    /// List live processes whose parent is gone; a correct kill path
+    /// reparents them to init, so any hit here is a bookkeeping bug
+    fn cmd_find_orphans(&self) -> String {
+        let orphans = self.manager.orphans();
+        if orphans.is_empty() {
+            return "✓ No orphaned processes".to_string();
+        }
+
+        let mut output = format!("Found {} orphaned process(es):\n", orphans.len());
+        for process in orphans {
+            let parent = match self.manager.get_process(process.ppid) {
+                Some(_) => "terminated",
+                None => "missing",
+            };
+            output.push_str(&format!(
+                "  PID {} ({}) -> parent {} ({})\n",
+                process.pid, process.name, process.ppid, parent
+            ));
+        }
+        output
+    }
+
+    /// One dense line per process: `PID PPID STATE Qn pc=0x.. cpu=..ms wait=..ms`.
+    /// Wait is sim time spent ready in a queue; `Q-` means not enrolled.
+    fn cmd_stat(&self, pid: u32) -> Result<String, String> {
+        let process = self.manager.get_or_error(pid)?;
+        let queue = self.scheduler
+            .get_process_queue(pid)
+            .map_or("Q-".to_string(), |q| format!("Q{}", q));
+        let wait = self.stats.get_process_metrics(pid).map_or(0, |m| m.ready_time);
+
+        Ok(format!(
+            "{} {} {} {} pc=0x{:x} cpu={}ms wait={}ms",
+            process.pid,
+            process.ppid,
+            process.state.name(),
+            queue,
+            process.program_counter,
+            process.total_time,
+            wait
+        ))
+    }
+
+    fn cmd_info(&self, pid: u32) -> Result<String, String> {
+        let process = self.manager.get_or_error(pid)?;
+        let queue = self.scheduler
+            .get_process_queue(pid)
+            .map_or("N/A".to_string(), |q| format!("Q{}", q));
+
+        let turnaround = process.turnaround_time();
+        let waiting = process.waiting_time();
+        let progress = match process.program_length {
+            Some(length) if process.is_complete() => format!("{}/{} (complete)", process.program_counter, length),
+            Some(length) => format!("{}/{}", process.program_counter, length),
+            None => "unbounded".to_string(),
+        };
+        let state_time = self.stats.get_process_metrics(pid).map_or("N/A".to_string(), |m| {
+            format!("ready {}ms, running {}ms, blocked {}ms", m.ready_time, m.running_time, m.blocked_time)
+        });
+        let history: String = self.scheduler.quantum_history(pid).iter().map(|o| o.symbol()).collect();
+        let history = if history.is_empty() { "none".to_string() } else { format!("{} (F=full, Y=yielded)", history) };
+        let quantum_left = self.scheduler
+            .remaining_for(pid)
+            .map_or("N/A (not running)".to_string(), |ms| format!("{}ms", ms));
+        let children = self.manager.children(pid);
+        let children = if children.is_empty() {
+            "none".to_string()
         } else {
-            format!("Error: Process {} not found", pid)
+            children.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ")
+        };
+        let parent_program = self.process_programs
+            .get(&process.ppid)
+            .map_or("none".to_string(), |program| program.name.clone());
+        let held = self.locks.held_by(pid);
+        let locks_held = if held.is_empty() { "none".to_string() } else { held.join(", ") };
+        let waiting_on = self.locks
+            .waiting_on(pid)
+            .or_else(|| self.semaphores.waiting_on(pid).map(|name| format!("semaphore {}", name)))
+            .unwrap_or_else(|| "none".to_string());
+
+        Ok(format!(
+            "Process Information (PID: {})\n\
+             ────────────────────────────────────\n\
+             Name:                 {}\n\
+             Parent PID (PPID):    {}\n\
+             Parent Program:       {}\n\
+             Children:             {}\n\
+             Owner:                {}\n\
+             State:                {}\n\
+             Priority:             {}\n\
+             Sched Class:          {:?}\n\
+             Scheduler Queue:      {}\n\
+             Quantum History:      {}\n\
+             Quantum Left:         {}\n\
+             Program Counter:      0x{:x}\n\
+             Program Progress:     {}\n\
+             Locks Held:           {}\n\
+             Waiting On:           {}\n\
+             Total Execution Time: {}ms\n\
+             Turnaround Time:      {}ms\n\
+             Waiting Time:         {}ms\n\
+             Time in States:       {}\n\
+             Stack Pointer:        0x{:x}\n\
+             Heap Start:           0x{:x}\n",
+            process.pid,
+            process.name,
+            process.ppid,
+            parent_program,
+            children,
+            process.owner,
+            process.state.name(),
+            process.priority,
+            process.sched_class,
+            queue,
+            history,
+            quantum_left,
+            process.program_counter,
+            progress,
+            locks_held,
+            waiting_on,
+            process.total_time,
+            turnaround,
+            waiting,
+            state_time,
+            process.registers.rsp,
+            process.memory_context.heap_start
+        ))
+    }
+
+    fn cmd_rename(&mut self, pid: u32, name: &str) -> Result<String, String> {
+        let old = self.manager.get_or_error(pid)?.name.clone();
+        self.manager.rename(pid, name)?;
+        Ok(format!("✓ Process {} renamed from '{}' to '{}'", pid, old, name))
+    }
+
+    fn cmd_su(&mut self, user: String) -> String {
+        let message = format!("✓ New processes will run as '{}'", user);
+        self.current_user = Some(user);
+        message
+    }
+
+    /// Hand a new process to the scheduler, which places it according to its nice value
+    fn admit(&mut self, pid: u32) {
+        if let Some(process) = self.manager.get_process(pid) {
+            self.scheduler.set_nice(pid, process.priority);
+            self.scheduler.set_sched_class(pid, process.sched_class);
         }
+        self.scheduler.add_process(pid);
     }
 
-    fn cmd_info(&self, pid: u32) -> String {
-        match self.manager.get_process(pid) {
-            Some(process) => {
-                let queue = self.scheduler
-                    .get_process_queue(pid)
-                    .map_or("N/A".to_string(), |q| format!("Q{}", q));
+    /// Refuse a new process if it would exceed the process limit or memory budget
+    fn check_capacity(&self) -> Result<(), String> {
+        let live: Vec<_> = self.manager
+            .all_processes()
+            .into_iter()
+            .filter(|p| p.state != ProcessState::Terminated)
+            .collect();
 
-                let turnaround = process.turnaround_time();
-                let waiting = process.waiting_time();
+        if let Some(limit) = self.process_limit {
+            if live.len() >= limit {
+                return Err(format!("Error: Process limit of {} reached", limit));
+            }
+        }
 
-                format!(
-                    "Process Information (PID: {})\n\
-                     ────────────────────────────────────\n\
-                     Parent PID (PPID):    {}\n\
-                     State:                {:?}\n\
-                     Priority:             {}\n\
-                     Scheduler Queue:      {}\n\
-                     Program Counter:      0x{:x}\n\
-                     Total Execution Time: {}ms\n\
-                     Turnaround Time:      {}ms\n\
-                     Waiting Time:         {}ms\n\
-                     Stack Pointer:        0x{:x}\n\
-                     Heap Start:           0x{:x}\n",
-                    process.pid,
-                    process.ppid,
-                    process.state,
-                    process.priority,
-                    queue,
-                    process.program_counter,
-                    process.total_time,
-                    turnaround,
-                    waiting,
-                    process.registers.rsp,
-                    process.memory_context.heap_start
-                )
+        if let Some(budget) = self.memory_budget {
+            let footprint = |m: &MemoryContext| (m.heap_size + m.stack_size) as u64;
+            let reserved: u64 = live.iter().map(|p| footprint(&p.memory_context)).sum();
+            let needed = footprint(&MemoryContext::default());
+            if reserved + needed > budget {
+                return Err(format!(
+                    "Error: Memory budget exhausted ({} of {} bytes reserved, {} needed)",
+                    reserved, budget, needed
+                ));
             }
-            None => format!("Error: Process {} not found", pid),
+        }
+
+        Ok(())
+    }
+
+    /// Give a freshly created process the owner chosen with `su`, if any
+    fn apply_current_user(&mut self, pid: u32) {
+        if let (Some(user), Some(process)) = (&self.current_user, self.manager.get_process_mut(pid)) {
+            process.owner = user.clone();
         }
     }
 
@@ -294,7 +1343,7 @@ impl Shell {
     // ========================================================================
 
     fn cmd_queues(&self) -> String {
-        let lengths = self.scheduler.queue_lengths();
+        let snapshot = self.scheduler.snapshot_queues();
         let current = self.scheduler.current_process();
 
         let mut output = String::from(
@@ -302,10 +1351,21 @@ impl Shell {
              ────────────────────────────────────\n"
         );
 
-        output.push_str(&format!("Q0 (8ms):   {} processes\n", lengths[0]));
-        output.push_str(&format!("Q1 (16ms):  {} processes\n", lengths[1]));
-        output.push_str(&format!("Q2 (32ms):  {} processes\n", lengths[2]));
-        output.push_str(&format!("Q3 (64ms):  {} processes\n", lengths[3]));
+        for (level, pids) in snapshot.iter().enumerate().take(self.scheduler.levels()) {
+            let quantum = self.scheduler.quantum(level).unwrap_or(0);
+            let label = format!("{:<10}", format!("Q{} ({}ms):", level, quantum));
+            let mut shown: Vec<String> = pids
+                .iter()
+                .take(self.queue_display_limit)
+                .map(|pid| pid.to_string())
+                .collect();
+            if pids.len() > self.queue_display_limit {
+                shown.push("...".to_string());
+                output.push_str(&format!("{}  [{}] ({} processes)\n", label, shown.join(", "), pids.len()));
+            } else {
+                output.push_str(&format!("{}  [{}]\n", label, shown.join(", ")));
+            }
+        }
         output.push_str(&format!(
             "Currently Running: {}\n",
             current.map_or("None".to_string(), |p| p.to_string())
@@ -314,379 +1374,3814 @@ impl Shell {
             "Time Remaining:   {}ms\n",
             self.scheduler.time_remaining()
         ));
+        output.push_str(&format!("Priority Boost:   {}\n", self.boost_countdown()));
 
         output
     }
 
-    fn cmd_schedule(&mut self, cycles: u32) -> String {
-        let mut output = format!("Simulating {} scheduling cycles:\n\n", cycles);
+    fn boost_countdown(&self) -> String {
+        match self.scheduler.ticks_until_boost() {
+            crate::scheduler::BOOST_DISABLED => "disabled".to_string(),
+            ticks => format!("next boost in {} ticks", ticks),
+        }
+    }
 
-        for cycle in 1..=cycles {
-            if let Some((pid, quantum)) = self.scheduler.next_process() {
-                if let Some(process) = self.manager.get_process_mut(pid) {
-                    process.set_state(ProcessState::Running);
-                    process.total_time = process.total_time.saturating_add(quantum);
-
-                    self.stats.record_context_switch(pid);
-                    self.stats.record_execution_time(pid, quantum as u64);
-                    self.stats.record_tick();
-
-                    output.push_str(&format!("Cycle {}: PID {} ran for {}ms in Q{}\n",
-                                             cycle,
-                                             pid,
-                                             quantum,
-                                             self.scheduler.get_process_queue(pid).unwrap_or(3)
-                    ));
-
-                    let use_full_quantum = rand::random::<f32>() < 0.7;
-
-                    if use_full_quantum {
-                        self.scheduler.process_used_full_quantum(pid);
-                        self.stats.record_queue_change(pid);
-                        let new_queue = self.scheduler.get_process_queue(pid).unwrap_or(3);
-                        output.push_str(&format!("         • Used full quantum → Demoted to Q{}\n", new_queue));
-                    } else {
-                        self.scheduler.process_yielded_early(pid);
-                        self.stats.record_queue_change(pid);
-                        let new_queue = self.scheduler.get_process_queue(pid).unwrap_or(0);
-                        output.push_str(&format!("         • Yielded early → Promoted to Q{}\n", new_queue));
-                    }
-
-                    process.set_state(ProcessState::Ready);
-                }
+    fn cmd_freeze(&mut self, freeze: bool) -> String {
+        match (self.frozen, freeze) {
+            (true, true) => "Error: Scheduler is already frozen".to_string(),
+            (false, false) => "Error: Scheduler is not frozen".to_string(),
+            (_, true) => {
+                self.frozen = true;
+                format!("✓ Scheduler frozen at t={}ms; dispatch is disabled until 'thaw'", self.clock)
+            }
+            (_, false) => {
+                self.frozen = false;
+                format!("✓ Scheduler thawed at t={}ms", self.clock)
             }
         }
-
-        output
     }
 
-    // ========================================================================
-    // SCHEDULER CONTROL COMMANDS
-    // ========================================================================
+    fn check_not_frozen(&self) -> Result<(), String> {
+        if self.frozen {
+            return Err("Error: Scheduler is frozen (use 'thaw' to resume)".to_string());
+        }
+        Ok(())
+    }
 
-    fn cmd_nice(&mut self, pid: u32, priority: u8) -> String {
-        if priority > 3 {
-            return "Error: Priority must be 0-3 (0=highest, 3=lowest)".to_string();
+    /// Run `cycles` scheduling cycles. `no_boost` turns the priority boost
+    /// off for this run only; the configured interval is restored after.
+    fn cmd_schedule(&mut self, cycles: u32, no_boost: bool) -> String {
+        if let Err(e) = self.check_not_frozen() {
+            return e;
+        }
+        if cycles == 0 {
+            return "No cycles requested".to_string();
+        }
+        if no_boost {
+            let interval = self.scheduler.boost_interval();
+            self.scheduler.set_boost_interval(0);
+            let output = self.cmd_schedule(cycles, false);
+            self.scheduler.set_boost_interval(interval);
+            return format!("Priority boost disabled for this run\n{}", output);
         }
 
-        match self.manager.get_process_mut(pid) {
-            Some(process) => {
-                let old_priority = process.priority;
-                process.priority = priority;
+        let verbosity = self.verbosity;
+        let mut output = String::new();
+        if cycles > SCHEDULE_MAX_CYCLES {
+            output.push_str(&format!(
+                "Warning: {} cycles requested; capped at {}\n",
+                cycles, SCHEDULE_MAX_CYCLES
+            ));
+        }
+        let cycles = cycles.min(SCHEDULE_MAX_CYCLES);
+        // Verbosity 0 drops the header and trace, but warnings and an
+        // interruption are still reported
+        if verbosity >= 1 {
+            output.push_str(&format!("Simulating {} scheduling cycles:\n\n", cycles));
+        }
 
-                if let Some(_old_queue) = self.scheduler.get_process_queue(pid) {
-                    self.scheduler.remove_process(pid);
-                    self.scheduler.add_process_to_queue(pid, priority as usize);
-                    self.stats.record_queue_change(pid);
-                }
+        let (start_clock, start_slices) = (self.clock, self.stats.execution_log.len());
+        let (start_busy, start_idle) = (self.stats.total_execution_time, self.stats.idle_time);
+        let (start_useful, start_overhead) = (self.stats.goodput.useful(), self.stats.goodput.switch_overhead);
+        let mut ran = 0;
+        for cycle in 1..=cycles {
+            if self.cancel.swap(false, Ordering::Relaxed) {
+                output.push_str(&format!("Interrupted after {} cycles\n", cycle - 1));
+                break;
+            }
 
-                format!(
-                    "✓ Process {} priority changed from {} to {}",
-                    pid, old_priority, priority
-                )
+            let mut trace = String::new();
+            self.run_cycle(cycle, &mut trace);
+            ran += 1;
+            if verbosity >= 2 {
+                output.push_str(&trace);
             }
-            None => format!("Error: Process {} not found", pid),
+            if verbosity >= 3 {
+                output.push_str(&format!("         • Queue depths: {:?}\n", self.scheduler.queue_lengths()));
+            }
+        }
+
+        if verbosity == 1 {
+            let idle = self.stats.idle_time - start_idle;
+            let (_, idle_pct, overhead_pct) = crate::scheduler::metrics::SchedulerStats::split_percentages(
+                self.stats.goodput.useful() - start_useful,
+                idle,
+                self.stats.goodput.switch_overhead - start_overhead,
+            );
+            output.push_str(&format!(
+                "Ran {} cycles in {}ms: {} dispatches, {}ms busy, {}ms idle, idle={:.1}% ovh={:.1}%\n",
+                ran,
+                self.clock - start_clock,
+                self.stats.execution_log.len() - start_slices,
+                self.stats.total_execution_time - start_busy,
+                idle,
+                idle_pct,
+                overhead_pct
+            ));
         }
+        output
     }
 
-    fn cmd_sched_stats(&self) -> String {
-        let mut output = String::from(
-            "╔════════════════════════════════════════════════════════════════╗\n\
-             ║           DETAILED SCHEDULER STATISTICS                       ║\n\
-             ╚════════════════════════════════════════════════════════════════╝\n\n"
+    /// Advance the clock by exactly `ms`, in `FINE_SLICE_MS` steps, across
+    /// whatever processes run. A dispatch still in progress at the end is
+    /// preempted.
+    fn cmd_schedule_fine(&mut self, ms: u64) -> String {
+        if let Err(e) = self.check_not_frozen() {
+            return e;
+        }
+        if ms == 0 {
+            return "No time requested".to_string();
+        }
+
+        let deadline = self.clock + ms;
+        let mut output = format!("Simulating {}ms in {}ms slices:\n\n", ms, FINE_SLICE_MS);
+        let mut cycle = 0;
+        while self.clock < deadline && cycle < SCHEDULE_MAX_CYCLES {
+            if self.cancel.swap(false, Ordering::Relaxed) {
+                output.push_str(&format!("Interrupted at t={}ms\n", self.clock));
+                break;
+            }
+            cycle += 1;
+            self.run_cycle_sliced(cycle, Some((FINE_SLICE_MS, deadline)), &mut output);
+        }
+        output
+    }
+
+    fn cmd_verbose(&mut self, level: u8) -> String {
+        if level > MAX_VERBOSITY {
+            return format!("Error: Verbosity must be between 0 and {}", MAX_VERBOSITY);
+        }
+        self.verbosity = level;
+        let detail = match level {
+            0 => "warnings only",
+            1 => "summary only",
+            2 => "one line per dispatch",
+            _ => "per dispatch with queue depths",
+        };
+        format!("✓ Schedule verbosity set to {} ({})", level, detail)
+    }
+
+    /// Run a single scheduling cycle, appending its trace to `output`.
+    /// Returns false if there was nothing to dispatch.
+    fn run_cycle(&mut self, cycle: u32, output: &mut String) -> bool {
+        self.run_cycle_sliced(cycle, None, output)
+    }
+
+    /// `run_cycle` that advances time in `(slice, deadline)` steps (see
+    /// `dispatch`) and never runs past the deadline
+    fn run_cycle_sliced(&mut self, cycle: u32, slicing: Option<(u64, u64)>, output: &mut String) -> bool {
+        let deadline = slicing.map_or(u64::MAX, |(_, deadline)| deadline);
+        self.stats.record_cycle();
+        self.fire_timed(output);
+        self.wake_sleepers(output);
+        self.drop_dead_from_queues(output);
+        self.sample_process_states();
+        self.sample_queues();
+
+        let (mut pid, mut quantum) = self.next_runnable();
+        if pid == IDLE_PID {
+            // Nothing runnable: idle forward to the next timer expiry
+            if let Some(&wake_at) = self.sleepers.values().min() {
+                self.run_idle(cycle, wake_at.min(deadline).saturating_sub(self.clock), output);
+                self.wake_sleepers(output);
+                if self.clock < deadline {
+                    (pid, quantum) = self.next_runnable();
+                }
+            }
+        }
+        for (pid, from_level) in self.scheduler.take_boosted() {
+            self.stats.record_global_boost(pid, from_level);
+        }
+
+        let dispatched = pid != IDLE_PID;
+        if dispatched {
+            self.dispatch(cycle, pid, quantum, slicing, output);
+        } else {
+            self.run_idle(cycle, (quantum as u64).min(deadline.saturating_sub(self.clock)), output);
+        }
+
+        self.expire_kill_grace(output);
+        if self.autonice && self.stats.cycles_seen.is_multiple_of(AUTONICE_INTERVAL) {
+            self.apply_autonice(output);
+        }
+        self.fire_timed(output);
+        dispatched
+    }
+
+    /// Run every `at` command whose tick the clock has reached
+    fn fire_timed(&mut self, output: &mut String) {
+        while let Some(index) = self.timed.iter().position(|(tick, _)| *tick <= self.clock) {
+            let (tick, line) = self.timed.remove(index);
+            output.push_str(&format!("  • @{}ms (clock {}ms): {}\n", tick, self.clock, line));
+
+            let result = match parse_command(&line) {
+                Some(cmd) => self.execute(cmd),
+                None => format!("Error: Unknown command '{}'", line),
+            };
+            for result_line in result.lines() {
+                output.push_str(&format!("      {}\n", result_line));
+            }
+        }
+    }
+
+    /// Pull queued PIDs that are terminated or unknown to the manager out of
+    /// the scheduler, so a path that forgot `remove_process` can't get a
+    /// dead process dispatched
+    fn drop_dead_from_queues(&mut self, output: &mut String) {
+        for (queue, pids) in self.scheduler.snapshot_queues().iter().enumerate() {
+            for &pid in pids {
+                let alive = self.manager
+                    .get_process(pid)
+                    .is_some_and(|p| p.state != ProcessState::Terminated);
+                if !alive {
+                    self.scheduler.release_process(pid);
+                    output.push_str(&format!("         • Removed terminated PID {} lingering in Q{}\n", pid, queue));
+                }
+            }
+        }
+    }
+
+    /// Return every sleeper whose timer has expired to the run queues
+    fn wake_sleepers(&mut self, output: &mut String) {
+        let due: Vec<u32> = self.sleepers
+            .iter()
+            .filter(|&(_, &wake_at)| wake_at <= self.clock)
+            .map(|(&pid, _)| pid)
+            .collect();
+
+        for pid in due {
+            self.sleepers.remove(&pid);
+            self.unpark(pid);
+            output.push_str(&format!("  • PID {} woke up at {}ms\n", pid, self.clock));
+        }
+    }
+
+    fn sample_process_states(&mut self) {
+        let (mut blocked, mut sleeping, mut stopped) = (0, 0, 0);
+        for process in self.manager.all_processes() {
+            match process.state {
+                ProcessState::Blocked => blocked += 1,
+                ProcessState::Sleeping => sleeping += 1,
+                ProcessState::Stopped => stopped += 1,
+                _ => {}
+            }
+        }
+        self.stats.sample_process_states(blocked, sleeping, stopped);
+    }
+
+    /// Record queue depths and which kind of program sits in each queue
+    fn sample_queues(&mut self) {
+        let queues = self.scheduler.snapshot_queues();
+        self.stats.sample_queue_depths(queues.each_ref().map(Vec::len));
+
+        let programs = &self.process_programs;
+        self.stats.sample_queue_occupancy(&queues, |pid| {
+            programs.get(&pid).map(|program| program.program_type)
+        });
+    }
+
+    /// Next process to dispatch, passing over any queued PID the manager
+    /// doesn't consider runnable; the idle task if none is left
+    fn next_runnable(&mut self) -> (u32, u32) {
+        let manager = &self.manager;
+        let cgroups = &self.cgroups;
+        self.scheduler.next_or_idle_where(|pid| {
+            manager.get_process(pid).is_some_and(|process| process.is_runnable())
+                && !cgroups.is_throttled(pid)
+        })
+    }
+
+    /// Give `ms` of CPU to the idle task while everything else waits
+    fn run_idle(&mut self, cycle: u32, ms: u64, output: &mut String) {
+        if ms == 0 {
+            return;
+        }
+
+        output.push_str(&format!("@t={} Cycle {}: idle (PID {}) for {}ms\n", self.clock, cycle, IDLE_PID, ms));
+        self.account_state_time(ms, None);
+        self.stats.record_idle_time(ms);
+        self.clock += ms;
+    }
+
+    /// Run `pid` for up to `quantum` ms and apply the MLFQ feedback rules.
+    /// With `slicing`, the clock advances in `(slice, deadline)` steps:
+    /// between steps sleepers are woken, and the process is preempted if
+    /// something in a higher queue becomes runnable or the deadline arrives.
+    /// A preempted process keeps its level and goes back to the front of its
+    /// queue without any feedback applied.
+    fn dispatch(
+        &mut self,
+        cycle: u32,
+        pid: u32,
+        quantum: u32,
+        slicing: Option<(u64, u64)>,
+        output: &mut String,
+    ) {
+        // Programs follow their own CPU appetite; plain processes use 70%.
+        // An early yield only gets credit for the part of the slice it used.
+        let slice = match self.process_programs.get_mut(&pid) {
+            Some(program) => {
+                if program.execute_quantum(&mut self.rng) {
+                    quantum
+                } else {
+                    program.early_yield_time(quantum)
+                }
+            }
+            None if self.rng.gen::<f32>() < 0.7 => quantum,
+            None => self.rng.gen_range(1..quantum.max(2)),
+        };
+        // A cgroup near its cap only has the rest of its budget to give.
+        // Being cut off there is not a yield, so it earns no promotion.
+        let budget = self.cgroups.remaining_for(pid).filter(|&budget| budget < slice as u64);
+        let capped = budget.is_some();
+        let slice = budget.map_or(slice, |budget| budget as u32);
+
+        if self.switch_cost > 0 {
+            // The switch burns CPU before the process gets any of its slice
+            let cost = self.switch_cost as u64;
+            self.account_state_time(cost, None);
+            self.stats.record_switch_overhead(cost);
+            self.clock += cost;
+        }
+
+        let Some(process) = self.manager.get_process_mut(pid) else {
+            return;
+        };
+        // A process never runs past the end of its burst
+        let planned = match process.program_length {
+            Some(length) => (slice as u64).min(length.saturating_sub(process.program_counter)),
+            None => slice as u64,
+        };
+        process.set_state(ProcessState::Running);
+        process.time_allocated = quantum;
+        process.reset_quantum();
+
+        let queue = self.scheduler.get_process_queue(pid).unwrap_or(3);
+        self.stats.record_context_switch_at(pid, self.clock);
+        self.stats.record_queue_switch(queue);
+        self.stats.record_tick();
+        let started = self.clock;
+
+        let mut ran = 0;
+        let mut preempted = false;
+        while ran < planned {
+            let mut step = planned - ran;
+            if let Some((slice, deadline)) = slicing {
+                if self.clock >= deadline || (ran > 0 && self.higher_queue_runnable(queue)) {
+                    preempted = true;
+                    break;
+                }
+                step = step.min(slice).min(deadline - self.clock);
+            }
+
+            self.stats.record_execution_slice(pid, self.clock, step, queue);
+            self.clock += step;
+            self.scheduler.tick(step as u32);
+            self.account_state_time(step, Some(pid));
+            ran += step;
+
+            if slicing.is_some() {
+                self.wake_sleepers(output);
+            }
+        }
+
+        let executed = ran as u32;
+        self.cgroups.charge(pid, ran);
+        self.stats.record_execution_time(pid, ran);
+        self.stats.record_goodput(self.process_programs.get(&pid).map(|program| program.program_type), ran);
+
+        let Some(process) = self.manager.get_process_mut(pid) else {
+            return;
+        };
+        process.time_used = executed;
+        process.total_time = process.total_time.saturating_add(ran);
+        process.advance_program_counter(ran);
+
+        // The process's own quantum bookkeeping decides the feedback rule
+        let used_full_quantum = process.quantum_expired();
+        let completed = process.is_complete();
+        let outcome = if completed {
+            "exited"
+        } else if preempted {
+            "preempted"
+        } else if capped {
+            "throttled"
+        } else if used_full_quantum {
+            "full"
+        } else {
+            "yielded"
+        };
+        let kind = if !completed && (preempted || capped || used_full_quantum) {
+            SwitchKind::Involuntary
+        } else {
+            SwitchKind::Voluntary
+        };
+        self.stats.record_switch_out(pid, kind);
+        output.push_str(&format!("@t={} Cycle {}: PID {} ran {}/{}ms in Q{} ({})\n",
+                                 started,
+                                 cycle,
+                                 pid,
+                                 executed,
+                                 quantum,
+                                 queue,
+                                 outcome
+        ));
+
+        if completed {
+            output.push_str(&format!("         • Burst complete after {}ms → Terminated\n", process.total_time));
+            self.terminate(pid);
+            return;
+        }
+
+        if preempted {
+            self.scheduler.move_to_front(pid, queue);
+            output.push_str(&format!("         • Preempted → Back to the front of Q{}\n", queue));
+        } else if capped {
+            self.scheduler.move_to_queue(pid, queue);
+            output.push_str(&format!("         • Cgroup budget spent → Stays in Q{}\n", queue));
+        } else if used_full_quantum {
+            self.scheduler.process_used_full_quantum(pid);
+            let new_queue = self.scheduler.get_process_queue(pid).unwrap_or(3);
+            if new_queue != queue {
+                self.stats.record_queue_change_to(pid, QueueChange::Demotion, new_queue);
+                output.push_str(&format!("         • Used full quantum → Demoted to Q{}\n", new_queue));
+            } else {
+                output.push_str(&format!(
+                    "         • Used full quantum → Stays in Q{} (allotment {}/{}ms)\n",
+                    queue,
+                    self.scheduler.level_usage(pid),
+                    self.scheduler.level_allotment(queue).unwrap_or(0)
+                ));
+            }
+        } else {
+            self.scheduler.process_yielded_early(pid);
+            let new_queue = self.scheduler.get_process_queue(pid).unwrap_or(0);
+            if new_queue != queue {
+                self.stats.record_queue_change_to(pid, QueueChange::Promotion, new_queue);
+                output.push_str(&format!("         • Yielded early → Promoted to Q{}\n", new_queue));
+            } else {
+                output.push_str(&format!("         • Yielded early → Stays in Q{}\n", queue));
+            }
+        }
+        process.set_state(ProcessState::Ready);
+    }
+
+    /// True if a runnable process is waiting in a queue above `queue`
+    fn higher_queue_runnable(&self, queue: usize) -> bool {
+        self.scheduler.snapshot_queues()[..queue].iter().flatten().any(|&pid| {
+            self.manager.get_process(pid).is_some_and(|p| p.is_runnable()) && !self.cgroups.is_throttled(pid)
+        })
+    }
+
+    /// Credit `elapsed` ms to every live process: the dispatched one ran,
+    /// everyone else spent it in their current state
+    fn account_state_time(&mut self, elapsed: u64, running: Option<u32>) {
+        for process in self.manager.all_processes() {
+            let state = if Some(process.pid) == running { ProcessState::Running } else { process.state };
+            self.stats.record_state_time(process.pid, state, elapsed);
+        }
+    }
+
+    fn cmd_waitall(&mut self, max_cycles: u32) -> String {
+        if let Err(e) = self.check_not_frozen() {
+            return e;
+        }
+        let mut trace = String::new();
+        let mut cycles = 0;
+
+        while cycles < max_cycles && self.unfinished_processes() > 0 {
+            if self.cancel.swap(false, Ordering::Relaxed) {
+                break;
+            }
+            cycles += 1;
+            if !self.run_cycle(cycles, &mut trace) {
+                break;
+            }
+        }
+
+        let remaining = self.unfinished_processes();
+
+        let mut output = if remaining == 0 {
+            format!("✓ All processes terminated after {} cycles\n\n", cycles)
+        } else {
+            format!(
+                "Warning: Stopped after {} cycles with {} process(es) still active\n\n",
+                cycles, remaining
+            )
+        };
+        output.push_str(&self.stats.summary_report());
+        output
+    }
+
+    /// Number of non-init processes that have not terminated yet
+    fn unfinished_processes(&self) -> usize {
+        self.manager
+            .active_processes()
+            .iter()
+            .filter(|p| p.pid != 1)
+            .count()
+    }
+
+    // ========================================================================
+    // SYNCHRONIZATION COMMANDS
+    // ========================================================================
+
+    fn cmd_lock(&mut self, pid: u32, resource: &str) -> String {
+        if let Err(e) = self.check_can_wait(pid) {
+            return e;
+        }
+
+        match self.locks.acquire(resource, pid) {
+            LockOutcome::Acquired => format!("✓ Process {} acquired '{}'", pid, resource),
+            LockOutcome::AlreadyHeld => format!("Error: Process {} already holds '{}'", pid, resource),
+            LockOutcome::Waiting { holder } => {
+                self.park(pid, ProcessState::Blocked);
+                let mut output = format!(
+                    "✓ Process {} blocked waiting for '{}' (held by PID {})\n",
+                    pid, resource, holder
+                );
+                self.check_inversion(resource, &mut output);
+                output
+            }
+        }
+    }
+
+    fn cmd_unlock(&mut self, pid: u32, resource: &str) -> String {
+        let next = match self.locks.release(resource, pid) {
+            Ok(next) => next,
+            Err(e) => return format!("Error: {}", e),
+        };
+
+        let mut output = format!("✓ Process {} released '{}'\n", pid, resource);
+
+        // Drop any inherited priority once nobody is waiting on this holder
+        if self.locks.blocked_by(pid).is_empty() {
+            if let Some(original) = self.inherited.remove(&pid) {
+                if self.scheduler.move_to_queue(pid, original) {
+                    self.stats.record_queue_change(pid, QueueChange::Demotion);
+                    output.push_str(&format!("  • PID {} restored to Q{}\n", pid, original));
+                }
+            }
+        }
+
+        if let Some(next) = next {
+            self.unpark(next);
+            output.push_str(&format!("  • PID {} acquired '{}' and is ready\n", next, resource));
+            self.check_inversion(resource, &mut output);
+        }
+
+        output
+    }
+
+    fn cmd_inherit(&mut self, enabled: bool) -> String {
+        self.priority_inheritance = enabled;
+        format!(
+            "✓ Priority inheritance {}",
+            if enabled { "enabled" } else { "disabled" }
+        )
+    }
+
+    fn cmd_autonice(&mut self, enabled: bool) -> String {
+        self.autonice = enabled;
+        if !enabled {
+            // Drop any automatic penalty: back to each process's own priority
+            for process in self.manager.all_processes() {
+                self.scheduler.set_nice(process.pid, process.priority);
+            }
+        }
+        format!("✓ Automatic nice adjustment {}", if enabled { "enabled" } else { "disabled" })
+    }
+
+    /// Renice processes by their CPU time relative to the average of live
+    /// processes: 1.5x the average costs one nice level, 3x costs two. The
+    /// penalty is added to the priority set with `nice`, so it lifts again
+    /// once the process stops hogging. The sum may run past priority 3 (up
+    /// to `MAX_NICE`), so processes at the default priority are penalized too.
+    fn apply_autonice(&mut self, output: &mut String) {
+        let live: Vec<(u32, u8, u64)> = self.manager
+            .all_processes()
+            .into_iter()
+            .filter(|p| p.pid != 1 && p.state != ProcessState::Terminated)
+            .map(|p| (p.pid, p.priority, p.total_time))
+            .collect();
+        if live.len() < 2 {
+            return;
+        }
+
+        let average = live.iter().map(|&(_, _, cpu)| cpu).sum::<u64>() as f64 / live.len() as f64;
+        if average == 0.0 {
+            return;
+        }
+
+        for (pid, priority, cpu) in live {
+            let share = cpu as f64 / average;
+            let penalty = if share >= 3.0 { 2 } else if share >= 1.5 { 1 } else { 0 };
+            let nice = (priority + penalty).min(MAX_NICE);
+            let old_nice = self.scheduler.nice(pid);
+            if nice == old_nice {
+                continue;
+            }
+
+            self.scheduler.set_nice(pid, nice);
+            output.push_str(&format!(
+                "         ⚖ Autonice: PID {} nice {} → {} ({:.1}x average CPU)\n",
+                pid, old_nice, nice, share
+            ));
+
+            let floor = self.scheduler.nice_floor(pid);
+            if self.scheduler.get_process_queue(pid).is_some_and(|queue| queue < floor)
+                && self.scheduler.move_to_queue(pid, floor)
+            {
+                self.stats.record_queue_change(pid, QueueChange::Demotion);
+            }
+        }
+    }
+
+    // ========================================================================
+    // CGROUP COMMANDS
+    // ========================================================================
+
+    fn cmd_cgroup_create(&mut self, name: &str, cpu_limit: u64) -> String {
+        match self.cgroups.create(name, cpu_limit) {
+            Ok(()) => format!("✓ Cgroup '{}' created with a {}ms CPU limit", name, cpu_limit),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    fn cmd_cgroup_add(&mut self, name: &str, pid: u32) -> Result<String, String> {
+        if pid == IDLE_PID || pid == 1 {
+            return Err(format!("Error: Cannot put PID {} in a cgroup", pid));
+        }
+        if self.manager.get_or_error(pid)?.state == ProcessState::Terminated {
+            return Err(format!("Error: Process {} is terminated", pid));
+        }
+
+        let previous = self.cgroups.add(name, pid).map_err(|e| format!("Error: {}", e))?;
+        Ok(match previous {
+            Some(previous) => format!("✓ Process {} moved from cgroup '{}' to '{}'", pid, previous, name),
+            None => format!("✓ Process {} added to cgroup '{}'", pid, name),
+        })
+    }
+
+    fn cmd_cgroup_stats(&self, name: &str) -> Result<String, String> {
+        let group = self.cgroups
+            .get(name)
+            .ok_or_else(|| format!("Error: Cgroup '{}' does not exist", name))?;
+
+        let mut output = format!(
+            "Cgroup '{}': {}/{}ms CPU used{}\n",
+            name,
+            group.cpu_used,
+            group.cpu_limit,
+            if group.is_throttled() { " (throttled)" } else { "" }
         );
+        output.push_str("PID   STATE       CPU_TIME  CONTEXT_SWITCHES\n");
 
-        output.push_str("System Summary:\n");
-        output.push_str("────────────────────────────────────────────────────────────\n");
-        output.push_str(&format!("Total Processes:          {}\n", self.manager.process_count()));
-        output.push_str(&format!("Scheduler State:          Running\n"));
-        output.push_str(&format!("Current Process:          {}\n\n",
-                                 self.scheduler.current_process().map_or("None".to_string(), |p| p.to_string())));
+        let mut total_switches = 0;
+        for &pid in &group.members {
+            let Some(process) = self.manager.get_process(pid) else {
+                continue;
+            };
+            let switches = self.stats.get_process_metrics(pid).map_or(0, |m| m.context_switches);
+            total_switches += switches;
+            output.push_str(&format!(
+                "{:<5} {:<11} {:>6}ms  {}\n",
+                pid,
+                process.state.name(),
+                process.total_time,
+                switches
+            ));
+        }
+        output.push_str(&format!(
+            "Total: {} members, {}ms CPU, {} context switches\n",
+            group.members.len(),
+            group.cpu_used,
+            total_switches
+        ));
+        Ok(output)
+    }
 
-        let lengths = self.scheduler.queue_lengths();
-        output.push_str("Queue Status:\n");
-        output.push_str("────────────────────────────────────────────────────────────\n");
-        output.push_str(&format!("Q0 (8ms):   {} processes\n", lengths[0]));
-        output.push_str(&format!("Q1 (16ms):  {} processes\n", lengths[1]));
-        output.push_str(&format!("Q2 (32ms):  {} processes\n", lengths[2]));
-        output.push_str(&format!("Q3 (64ms):  {} processes\n\n", lengths[3]));
+    fn cmd_sem_create(&mut self, name: &str, count: u32) -> String {
+        match self.semaphores.create(name, count) {
+            Ok(()) => format!("✓ Semaphore '{}' created with count {}", name, count),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
 
-        output.push_str("Performance Metrics:\n");
-        output.push_str("────────────────────────────────────────────────────────────\n");
-        output.push_str(&format!("CPU Utilization:          {:.2}%\n", self.stats.cpu_utilization()));
-        output.push_str(&format!("Context Switch Rate:      {:.4} per tick\n", self.stats.context_switch_rate()));
-        output.push_str(&format!("Total Context Switches:   {}\n", self.stats.total_context_switches));
-        output.push_str(&format!("Total Execution Time:     {}ms\n\n", self.stats.total_execution_time));
+    fn cmd_sem_wait(&mut self, name: &str, pid: u32) -> String {
+        if let Err(e) = self.check_can_wait(pid) {
+            return e;
+        }
 
-        output.push_str("Queue Distribution:\n");
-        output.push_str("────────────────────────────────────────────────────────────\n");
-        for (idx, &len) in lengths.iter().enumerate() {
-            output.push_str(&format!("Q{}: ", idx));
-            for _ in 0..len {
-                output.push('■');
+        match self.semaphores.wait(name, pid) {
+            Ok(true) => {
+                let count = self.semaphores.get(name).map_or(0, |sem| sem.count);
+                format!("✓ Process {} passed wait on '{}' (count now {})", pid, name, count)
             }
-            output.push_str(&format!(" ({})\n", len));
+            Ok(false) => {
+                self.park(pid, ProcessState::Blocked);
+                format!("✓ Process {} blocked on '{}' (count is 0)", pid, name)
+            }
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    fn cmd_sem_signal(&mut self, name: &str) -> String {
+        match self.semaphores.signal(name) {
+            Ok(Some(pid)) => {
+                self.unpark(pid);
+                format!("✓ Signaled '{}': woke process {}", name, pid)
+            }
+            Ok(None) => {
+                let count = self.semaphores.get(name).map_or(0, |sem| sem.count);
+                format!("✓ Signaled '{}' (count now {})", name, count)
+            }
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    /// Report every cycle in the lock wait-for graph
+    fn cmd_deadlock(&self) -> String {
+        let graph = ipc::wait_for_graph(&self.locks);
+        let cycles = ipc::find_cycles(&graph);
+
+        if cycles.is_empty() {
+            return "✓ No deadlock detected".to_string();
+        }
+
+        let mut output = format!(
+            "Deadlock Detected ({} cycle{})\n\
+             ────────────────────────────────────\n",
+            cycles.len(),
+            if cycles.len() == 1 { "" } else { "s" }
+        );
+
+        for cycle in &cycles {
+            for (i, &pid) in cycle.iter().enumerate() {
+                let next = cycle[(i + 1) % cycle.len()];
+                let resource = self.locks.waiting_on(pid).unwrap_or_else(|| "?".to_string());
+                output.push_str(&format!(
+                    "  PID {} waits on '{}' held by PID {}\n",
+                    pid, resource, next
+                ));
+            }
+            output.push('\n');
         }
 
+        let deadlocked: Vec<String> = ipc::deadlocked_set(&graph)
+            .iter()
+            .map(|pid| pid.to_string())
+            .collect();
+        output.push_str(&format!("Deadlocked processes: {}\n", deadlocked.join(", ")));
+
         output
     }
 
-    fn cmd_programs(&self) -> String {
-        let registry = crate::scheduler::programs::ProgramRegistry::new();
-        registry.print_catalog()
+    /// Flag a waiter on `resource` outranking its holder, and boost the holder
+    /// to the waiter's level when inheritance is on
+    fn check_inversion(&mut self, resource: &str, output: &mut String) {
+        let Some(holder) = self.locks.holder(resource) else {
+            return;
+        };
+        let Some(holder_queue) = self.scheduler.get_process_queue(holder) else {
+            return;
+        };
+        let Some((waiter, waiter_queue)) = self.locks
+            .waiters(resource)
+            .into_iter()
+            .filter_map(|pid| self.parked.get(&pid).map(|&q| (pid, q)))
+            .min_by_key(|&(_, q)| q)
+        else {
+            return;
+        };
+
+        if waiter_queue >= holder_queue {
+            return;
+        }
+
+        output.push_str(&format!(
+            "  ⚠ Priority inversion: PID {} (Q{}) waits on '{}' held by PID {} (Q{})\n",
+            waiter, waiter_queue, resource, holder, holder_queue
+        ));
+
+        if self.priority_inheritance && self.scheduler.move_to_queue(holder, waiter_queue) {
+            self.inherited.entry(holder).or_insert(holder_queue);
+            self.stats.record_queue_change(holder, QueueChange::Boost);
+            output.push_str(&format!(
+                "  ↑ Priority inheritance: PID {} boosted to Q{}\n",
+                holder, waiter_queue
+            ));
+        }
+    }
+
+    /// Take a waiting process off the run queues, remembering its level
+    fn park(&mut self, pid: u32, state: ProcessState) {
+        let queue = self.scheduler.get_process_queue(pid).unwrap_or(3);
+        self.scheduler.remove_process(pid);
+        self.parked.insert(pid, queue);
+        if let Some(process) = self.manager.get_process_mut(pid) {
+            process.set_state(state);
+        }
+    }
+
+    /// Return a parked process to the level it was waiting at
+    fn unpark(&mut self, pid: u32) {
+        if let Some(queue) = self.parked.remove(&pid) {
+            self.scheduler.add_process_to_queue(pid, queue);
+            if let Some(process) = self.manager.get_process_mut(pid) {
+                process.set_state(ProcessState::Ready);
+            }
+        }
+    }
+
+    // ========================================================================
+    // SCHEDULER CONTROL COMMANDS
+    // ========================================================================
+
+    fn cmd_nice(&mut self, pid: u32, priority: u8) -> Result<String, String> {
+        if priority > 3 {
+            return Err("Error: Priority must be 0-3 (0=highest, 3=lowest)".to_string());
+        }
+        if pid == IDLE_PID {
+            return Err(format!("Error: Cannot renice the idle process (PID {})", IDLE_PID));
+        }
+
+        let process = self.manager.get_mut_or_error(pid)?;
+        let old_priority = process.priority;
+        let runnable = matches!(process.state, ProcessState::Ready | ProcessState::Running);
+        process.priority = priority;
+        self.scheduler.set_nice(pid, priority);
+
+        let mut output = format!("✓ Process {} priority changed from {} to {}", pid, old_priority, priority);
+        if runnable && !self.scheduler.contains(pid) {
+            output.push_str(&self.unscheduled_warning(pid));
+            return Ok(output);
+        }
+
+        match self.scheduler.get_process_queue(pid) {
+            // Already at the requested level: keep its FIFO position
+            Some(old_queue) if old_queue == priority as usize => {
+                return Ok(format!(
+                    "✓ Process {} already in Q{} (queue position unchanged)",
+                    pid, old_queue
+                ));
+            }
+            Some(old_queue) => {
+                self.scheduler.remove_process(pid);
+                self.scheduler.add_process_to_queue(pid, priority as usize);
+                let reason = if (priority as usize) > old_queue {
+                    QueueChange::Demotion
+                } else {
+                    QueueChange::Promotion
+                };
+                self.stats.record_queue_change(pid, reason);
+            }
+            None => {}
+        }
+
+        Ok(output)
+    }
+
+    /// Move a process exactly one level down (`demote`) or up (`promote`)
+    /// by hand, without running a cycle
+    fn cmd_shift(&mut self, pid: u32, demote: bool) -> Result<String, String> {
+        let state = self.manager.get_or_error(pid)?.state;
+        if !self.scheduler.contains(pid) {
+            return Err(format!("Error: Process {} ({}) is not in any scheduler queue", pid, state.name()));
+        }
+
+        let (before, after) = if demote {
+            self.scheduler.demote(pid)
+        } else {
+            self.scheduler.promote(pid)
+        }
+        .expect("process is enrolled");
+
+        if before == after {
+            let reason = if demote { "already the lowest queue" } else { "already the highest queue" };
+            return Ok(format!("✓ Process {} stays in Q{} ({})", pid, before, reason));
+        }
+
+        let (verb, reason) = if demote {
+            ("demoted", QueueChange::Demotion)
+        } else {
+            ("promoted", QueueChange::Promotion)
+        };
+        self.stats.record_queue_change(pid, reason);
+        Ok(format!("✓ Process {} {}: Q{} → Q{}", pid, verb, before, after))
+    }
+
+    fn cmd_chrt(&mut self, pid: u32, class: SchedClass) -> Result<String, String> {
+        let process = self.manager.get_mut_or_error(pid)?;
+        if process.state == ProcessState::Terminated {
+            return Err(format!("Error: Process {} is terminated", pid));
+        }
+
+        let old_class = process.sched_class;
+        process.sched_class = class;
+        self.scheduler.set_sched_class(pid, class);
+        Ok(format!("✓ Process {} class changed from {:?} to {:?}", pid, old_class, class))
+    }
+
+    /// Redraw `sched_stats` after each of `frames` single-cycle steps, as a
+    /// crude live dashboard. Frames are paced `WATCH_FRAME_INTERVAL_MS` apart
+    /// by the sleeper, and Ctrl-C stops the watch early.
+    fn cmd_sched_stats_watch(&mut self, frames: u32) -> String {
+        if let Err(e) = self.check_not_frozen() {
+            return e;
+        }
+
+        let mut output = String::new();
+        if frames > WATCH_MAX_FRAMES {
+            output.push_str(&format!(
+                "Warning: {} frames requested; capped at {}\n",
+                frames, WATCH_MAX_FRAMES
+            ));
+        }
+        let frames = frames.min(WATCH_MAX_FRAMES);
+
+        for frame in 0..frames {
+            if self.cancel.swap(false, Ordering::Relaxed) {
+                output.push_str(&format!("Interrupted after {} frames", frame));
+                return output;
+            }
+            if frame > 0 {
+                self.sleeper.sleep(Duration::from_millis(WATCH_FRAME_INTERVAL_MS));
+            }
+
+            (self.watch_step)(self);
+            let stats = self.cmd_sched_stats();
+            self.screen.clear();
+            self.screen.draw(&stats);
+        }
+
+        output.push_str(&format!("✓ Watched {} frames", frames));
+        output
+    }
+
+    fn cmd_sched_stats(&self) -> String {
+        let mut output = String::from(
+            "╔════════════════════════════════════════════════════════════════╗\n\
+             ║           DETAILED SCHEDULER STATISTICS                       ║\n\
+             ╚════════════════════════════════════════════════════════════════╝\n\n"
+        );
+
+        output.push_str("System Summary:\n");
+        output.push_str("────────────────────────────────────────────────────────────\n");
+        output.push_str(&format!("Total Processes:          {}\n", self.manager.process_count()));
+        output.push_str("Scheduler State:          Running\n");
+        output.push_str(&format!("Current Process:          {}\n\n",
+                                 self.scheduler.current_process().map_or("None".to_string(), |p| p.to_string())));
+
+        let levels = self.scheduler.levels();
+        let lengths = self.scheduler.queue_lengths();
+        output.push_str("Queue Status:\n");
+        output.push_str("────────────────────────────────────────────────────────────\n");
+        for (level, len) in lengths.iter().enumerate().take(levels) {
+            let label = format!("Q{} ({}ms):", level, self.scheduler.quantum(level).unwrap_or(0));
+            output.push_str(&format!("{:<11} {} processes\n", label, len));
+        }
+        output.push_str(&format!("Priority Boost:           {}\n\n", self.boost_countdown()));
+
+        output.push_str("Performance Metrics:\n");
+        output.push_str("────────────────────────────────────────────────────────────\n");
+        output.push_str(&format!("CPU Utilization:          {:.2}%\n", self.stats.cpu_utilization()));
+        output.push_str(&format!("Context Switch Rate:      {:.4} per tick\n", self.stats.context_switch_rate()));
+        output.push_str(&format!("Total Context Switches:   {}\n", self.stats.total_context_switches));
+        output.push_str(&format!("Total Execution Time:     {}ms\n\n", self.stats.total_execution_time));
+
+        output.push_str("Context Switches by Queue:\n");
+        output.push_str("────────────────────────────────────────────────────────────\n");
+        for (idx, switches) in self.stats.switches_per_queue.iter().enumerate().take(levels) {
+            output.push_str(&format!("Q{}: {}\n", idx, switches));
+        }
+        output.push('\n');
+
+        output.push_str("Queue Distribution:\n");
+        output.push_str("────────────────────────────────────────────────────────────\n");
+        for (idx, &len) in lengths.iter().enumerate().take(levels) {
+            output.push_str(&format!("Q{}: ", idx));
+            for _ in 0..len {
+                output.push('■');
+            }
+            output.push_str(&format!(" ({})\n", len));
+        }
+
+        output
+    }
+
+    fn cmd_sparkline(&self, queue: Option<usize>) -> String {
+        let count = self.scheduler.levels();
+        let levels = match queue {
+            Some(q) if q < count => q..q + 1,
+            Some(q) => return format!("Error: Queue {} does not exist (0-{})", q, count - 1),
+            None => 0..count,
+        };
+
+        if self.stats.queue_depth_samples.is_empty() {
+            return "No queue-depth samples yet. Run 'schedule' first.".to_string();
+        }
+
+        let mut output = format!("Queue Depth Trend ({} samples):\n", self.stats.queue_depth_samples.len());
+        for level in levels {
+            output.push_str(&format!("Q{}: {}\n", level, self.stats.queue_sparkline(level)));
+        }
+        output
+    }
+
+    fn cmd_heatmap(&self) -> String {
+        if self.stats.execution_log.is_empty() {
+            return "No execution recorded yet. Run 'schedule' first.".to_string();
+        }
+        self.stats.queue_heatmap(self.scheduler.levels())
+    }
+
+    fn cmd_at(&mut self, tick: u64, line: String) -> String {
+        let message = format!("✓ '{}' will run when the clock reaches {}ms", line, tick);
+        // Keep ticks ordered; equal ticks fire in the order they were queued
+        let index = self.timed.partition_point(|(t, _)| *t <= tick);
+        self.timed.insert(index, (tick, line));
+        message
+    }
+
+    fn cmd_at_list(&self) -> String {
+        if self.timed.is_empty() {
+            return "No timed commands pending".to_string();
+        }
+
+        let mut output = format!("Timed Commands (clock {}ms):\n", self.clock);
+        for (tick, line) in &self.timed {
+            output.push_str(&format!("  {:>6}ms  {}\n", tick, line));
+        }
+        output
+    }
+
+    fn cmd_programs(&self) -> String {
+        self.registry.print_catalog()
+    }
+
+    fn cmd_program_search(&self, query: &str) -> String {
+        let matches = self.registry.search(query);
+        if matches.is_empty() {
+            return format!("No programs match '{}'", query);
+        }
+
+        let mut output = format!("Programs matching '{}' ({}):\n", query, matches.len());
+        for prog in matches {
+            output.push_str(&format!(
+                "  {} - {}\n    Usage: {:.0}% quantum\n",
+                prog.name, prog.description,
+                prog.typical_quantum_usage * 100.0
+            ));
+        }
+        output
+    }
+
+    fn cmd_run_program(&mut self, program_name: &str, count: u32) -> String {
+        let Some(program) = self.registry.get_program(program_name) else {
+            return format!("Error: Program '{}' not found. Type 'programs' to see available programs.", program_name);
+        };
+        if count > RUN_PROGRAM_MAX_INSTANCES {
+            return format!(
+                "Error: Cannot start {} instances at once (at most {})",
+                count, RUN_PROGRAM_MAX_INSTANCES
+            );
+        }
+
+        let mut pids = Vec::new();
+        let mut stopped = None;
+        for _ in 0..count {
+            match self.spawn_program(&program) {
+                Ok(pid) => pids.push(pid),
+                Err(e) => {
+                    stopped = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let (Some(&first), Some(&last)) = (pids.first(), pids.last()) else {
+            return stopped.unwrap_or_default();
+        };
+        let started = if count == 1 {
+            format!("✓ Program '{}' started as PID {}", program.name, first)
+        } else {
+            format!(
+                "✓ Program '{}' started {} of {} instances as PIDs {}-{}",
+                program.name,
+                pids.len(),
+                count,
+                first,
+                last
+            )
+        };
+
+        let mut output = format!(
+            "{}\n\
+             Description: {}\n\
+             Behavior: {}\n\
+             Expected Priority: Q{}",
+            started,
+            program.description,
+            program.behavior_description(),
+            program.expected_priority
+        );
+        if let Some(e) = stopped {
+            output.push('\n');
+            output.push_str(&e);
+        }
+        output
+    }
+
+    /// Create one process running `program`, subject to the capacity limits
+    fn spawn_program(&mut self, program: &Program) -> Result<u32, String> {
+        self.check_capacity()?;
+
+        let pid = self.manager.create_process(1);
+        self.apply_current_user(pid);
+        if let Some(process) = self.manager.get_process_mut(pid) {
+            process.name = program.name.clone();
+            process.program_length = Some(program.estimated_length);
+            process.sched_class = program.sched_class();
+        }
+        self.admit(pid);
+        self.stats.record_process_created_at(pid, self.clock);
+        self.process_programs.insert(pid, program.clone());
+        Ok(pid)
+    }
+
+    fn cmd_unregister_program(&mut self, program_name: &str) -> String {
+        let running = self.processes_running_program(program_name);
+        if !running.is_empty() {
+            return format!(
+                "Error: Program '{}' still has running processes {:?}. Kill them first.",
+                program_name, running
+            );
+        }
+
+        if self.registry.remove(program_name) {
+            format!("✓ Program '{}' removed from the catalog", program_name)
+        } else {
+            format!("Error: Program '{}' not found", program_name)
+        }
+    }
+
+    fn cmd_edit_program(&mut self, program_name: &str, usage: f32) -> String {
+        if !(0.0..=1.0).contains(&usage) {
+            return "Error: Quantum usage must be between 0.0 and 1.0".to_string();
+        }
+
+        match self.registry.get_program(program_name) {
+            Some(mut program) => {
+                let old_usage = program.typical_quantum_usage;
+                program.typical_quantum_usage = usage;
+                self.registry.update(program_name, program);
+                format!(
+                    "✓ Program '{}' quantum usage changed from {:.0}% to {:.0}%",
+                    program_name,
+                    old_usage * 100.0,
+                    usage * 100.0
+                )
+            }
+            None => format!("Error: Program '{}' not found", program_name),
+        }
+    }
+
+    /// New processes of the program replay `script` before behaving randomly
+    fn cmd_script_program(&mut self, program_name: &str, script: Vec<bool>) -> String {
+        match self.registry.get_program(program_name) {
+            Some(mut program) => {
+                let pattern: String = script.iter().map(|&full| if full { 'T' } else { 'F' }).collect();
+                program.set_behavior_script(script);
+                self.registry.update(program_name, program);
+                format!("✓ Program '{}' will follow script {} before random behavior", program_name, pattern)
+            }
+            None => format!("Error: Program '{}' not found", program_name),
+        }
+    }
+
+    /// PIDs of non-terminated processes launched from the named program
+    fn processes_running_program(&self, program_name: &str) -> Vec<u32> {
+        self.process_programs
+            .iter()
+            .filter(|(_, program)| program.name == program_name)
+            .map(|(&pid, _)| pid)
+            .filter(|&pid| {
+                self.manager
+                    .get_process(pid)
+                    .is_some_and(|p| p.state != ProcessState::Terminated)
+            })
+            .collect()
+    }
+
+    // ========================================================================
+    // STATISTICS COMMANDS
+    // ========================================================================
+
+    fn cmd_stats(&self) -> String {
+        self.stats.summary_report() + &self.stats.in_flight_report(self.clock)
+    }
+
+    fn cmd_stats_since(&self, tick: u64) -> String {
+        if tick > self.clock {
+            return format!("Error: Tick {} is in the future (clock is at {}ms)", tick, self.clock);
+        }
+        self.stats.window_report(tick)
+    }
+
+    fn cmd_metrics(&self, pid: u32) -> String {
+        match self.stats.get_process_metrics(pid) {
+            Some(metrics) => {
+                format!(
+                    "Process Metrics (PID: {})\n\
+                     ════════════════════════════════════════════════════════════\n\
+                     Turnaround Time:     {}ms\n\
+                     Response Time:       {}ms\n\
+                     Waiting Time:        {}ms\n\
+                     Execution Time:      {}ms\n\
+                     Context Switches:    {} ({} voluntary, {} involuntary)\n\
+                     Queue Changes:       {} ({} demotions, {} promotions, {} boosts)\n\
+                     Time Ready:          {}ms\n\
+                     Time Running:        {}ms\n\
+                     Time Blocked:        {}ms\n",
+                    metrics.pid,
+                    metrics.turnaround_time,
+                    metrics.response_time,
+                    metrics.waiting_time,
+                    metrics.execution_time,
+                    metrics.context_switches,
+                    metrics.voluntary_switches,
+                    metrics.involuntary_switches,
+                    metrics.queue_changes,
+                    metrics.demotions,
+                    metrics.promotions,
+                    metrics.boosts,
+                    metrics.ready_time,
+                    metrics.running_time,
+                    metrics.blocked_time,
+                )
+            }
+            None => format!("Error: No metrics found for process {}", pid),
+        }
+    }
+
+    fn cmd_usage(&self) -> String {
+        let mut output = String::from(
+            "Resource Usage by Owner\n\
+             ────────────────────────────────────\n\
+             OWNER        PROCESSES  CPU_TIME\n"
+        );
+
+        for (owner, usage) in self.manager.usage_by_owner() {
+            output.push_str(&format!(
+                "{:<12} {:<10} {}ms\n",
+                owner, usage.processes, usage.cpu_time
+            ));
+        }
+
+        output
+    }
+
+    fn cmd_reset_stats(&mut self) -> String {
+        self.stats.reset();
+        "✓ All statistics have been reset".to_string()
+    }
+
+    fn cmd_reset_metrics(&mut self, pid: u32) -> String {
+        if self.stats.reset_process(pid) {
+            format!("✓ Metrics for process {} reset (system totals unchanged)", pid)
+        } else {
+            format!("Error: No metrics found for process {}", pid)
+        }
+    }
+
+    fn cmd_warmup(&mut self, cycles: u32) -> String {
+        self.stats.set_warmup(cycles);
+        if cycles == 0 {
+            "✓ Warm-up disabled".to_string()
+        } else {
+            format!("✓ Metrics will ignore the first {} scheduling cycles", cycles)
+        }
+    }
+
+    // ========================================================================
+    // SYSTEM COMMANDS
+    // ========================================================================
+
+    /// Compare MLFQ, set up like this shell's scheduler, against FCFS and
+    /// Round Robin on a generated workload
+    fn cmd_bench(&self, csv: bool, repeat: u32) -> String {
+        if repeat > 1 {
+            let results = bench::run_repeated(
+                &self.scheduler,
+                bench::DEFAULT_BENCH_SEED,
+                bench::DEFAULT_BENCH_JOBS,
+                repeat,
+            );
+            return if csv {
+                bench::format_repeated_csv(&results)
+            } else {
+                bench::format_repeated_table(&results)
+            };
+        }
+
+        let jobs = bench::generate_workload(bench::DEFAULT_BENCH_SEED, bench::DEFAULT_BENCH_JOBS);
+        let results = bench::run_bench(&self.scheduler, &jobs);
+
+        if csv {
+            bench::format_csv(&results)
+        } else {
+            bench::format_table(&results)
+        }
+    }
+
+    /// Play back a saved execution log, pacing events by their tick gaps.
+    /// Each event is drawn on the screen as it fires; when the command is
+    /// piped or redirected, `execute_line` captures those lines as output.
+    fn cmd_replay(&mut self, path: &str, speed: f64) -> String {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => return format!("Error: Cannot read {}: {}", path, e),
+        };
+        let events = match replay::load_trace(&contents) {
+            Ok(events) => events,
+            Err(e) => return format!("Error: {}", e),
+        };
+
+        // Events are drawn as they fire rather than collected, so a slow
+        // replay shows progress instead of going quiet until the end
+        let screen = &mut self.screen;
+        let result = replay::replay(&events, speed, self.sleeper.as_mut(), |slice| {
+            screen.draw(&format!(
+                "[{:>6}ms] PID {} ran for {}ms in Q{}",
+                slice.start_tick, slice.pid, slice.duration, slice.queue
+            ));
+        });
+
+        match result {
+            Ok(count) => format!("✓ Replayed {} events at {}x speed", count, speed),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    /// Save the execution log as JSON in the format `replay` reads back
+    fn cmd_trace_export(&self, path: &str) -> String {
+        let events = &self.stats.execution_log;
+        let json = serde_json::to_string_pretty(events).expect("execution log serializes to JSON");
+        match std::fs::write(path, json) {
+            Ok(()) => format!("✓ Wrote {} events to {}", events.len(), path),
+            Err(e) => format!("Error: Cannot write {}: {}", path, e),
+        }
+    }
+
+    fn cmd_history(&self) -> String {
+        let mut output = String::new();
+        for (i, line) in self.history.iter().enumerate() {
+            output.push_str(&format!("{:>4}  {}\n", i + 1, line));
+        }
+        output
+    }
+
+    /// Write the history as plain text, one command per line
+    fn cmd_history_save(&self, path: &str) -> String {
+        let mut text = self.history.join("\n");
+        text.push('\n');
+        match std::fs::write(path, text) {
+            Ok(()) => format!("✓ Saved {} commands to {}", self.history.len(), path),
+            Err(e) => format!("Error: Cannot write {}: {}", path, e),
+        }
+    }
+
+    /// Run every command in a saved history, picking the session up where
+    /// it was left; each one lands in this shell's history as it runs
+    fn cmd_history_load(&mut self, path: &str) -> String {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => return format!("Error: Cannot read {}: {}", path, e),
+        };
+
+        let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        for line in &lines {
+            self.execute_line(line);
+        }
+        format!("✓ Replayed {} commands from {}", lines.len(), path)
+    }
+
+    fn cmd_selftest(&self) -> String {
+        crate::scheduler::test_suite::run_all().to_report()
+    }
+
+    fn cmd_seed(&mut self, value: u64) -> String {
+        let previous = self.seed.replace(value);
+        self.rng = StdRng::seed_from_u64(value);
+        match previous {
+            Some(old) => format!("✓ RNG reseeded with {} (was {})", value, old),
+            None => format!("✓ RNG reseeded with {}", value),
+        }
+    }
+
+    /// Sim and wall time since stats were started, process counts and the
+    /// average number of queued processes
+    fn cmd_uptime(&self) -> String {
+        let wall = self.stats.start_time.elapsed().as_secs();
+        let (_, idle, overhead) = self.stats.cpu_split();
+        format!(
+            "up {}ms ({} cycles), wall {}:{:02}:{:02}, {} created, {} terminated, {} active, \
+             load average: {:.2}, idle={:.1}% ovh={:.1}%",
+            self.clock,
+            self.stats.cycles_seen,
+            wall / 3600,
+            wall / 60 % 60,
+            wall % 60,
+            self.stats.processes_created,
+            self.stats.processes_terminated,
+            self.manager.active_processes().len(),
+            self.stats.load_average(),
+            idle,
+            overhead
+        )
+    }
+
+    fn cmd_help(&self) -> String {
+        String::from(
+            "Available Commands:\n\
+             ────────────────────────────────────────────────────\n\
+             Process Management:\n\
+               fork [ppid]          - Create new process\n\
+               ps [state]           - List processes (optionally by state)\n\
+               kill [--now] <pid>   - SIGTERM (grace period) or SIGKILL\n\
+               kill -r <pid>        - SIGKILL a process and its descendants\n\
+               run <pid>            - Transition to running\n\
+             \n\
+             Process State:\n\
+               block <pid>          - Block process (I/O)\n\
+               unblock <pid>        - Unblock process\n\
+               sleep <pid> <ms>     - Sleep on a timer\n\
+               stop <pid>           - Suspend process\n\
+               cont <pid>           - Resume stopped process\n\
+               info <pid>           - Process information\n\
+               stat <pid>           - One-line process summary\n\
+               rename <pid> <name>  - Set a process's display name\n\
+               su <user>            - Owner for new processes\n\
+             \n\
+             Scheduler Control:\n\
+               nice <pid> <prio>    - Change priority (0-3)\n\
+               chrt <pid> <class>   - Set class (rt|normal|idle)\n\
+               demote <pid>         - Move a process down one queue level\n\
+               promote <pid>        - Move a process up one queue level\n\
+               schedule <cycles>    - Simulate N cycles\n\
+               schedule <n> --no-boost - Simulate without the priority boost\n\
+               schedule_fine <ms>   - Simulate exactly ms, preempting between slices\n\
+               waitall [max]        - Run until all processes finish\n\
+               at <tick> <command>  - Run a command when the clock hits tick\n\
+               at                   - List pending timed commands\n\
+               queues               - Show queue state\n\
+               sched_stats          - Detailed statistics\n\
+               sched_stats --watch <n> - Run a cycle and redraw stats, n times\n\
+               occupancy            - CPU- vs I/O-bound mix per queue\n\
+               goodput              - Useful CPU per workload vs overhead\n\
+               switch_cost <ms>     - CPU charged per context switch\n\
+               verbose <0-3>        - How much schedule prints\n\
+               rebalance            - Drop stale queue entries, free capacity\n\
+               sparkline [queue]    - Queue depth trend\n\
+               heatmap              - Running time per process and queue\n\
+               freeze / thaw        - Stop dispatching, keeping all state\n\
+               cgroup_create <n> <ms> - Group with a shared CPU limit\n\
+               cgroup_add <n> <pid> - Put a process in a cgroup\n\
+               cgroup_stats <n>     - Usage and members of a cgroup\n\
+             \n\
+             Synchronization:\n\
+               lock <pid> <res>     - Acquire a lock (blocks if held)\n\
+               unlock <pid> <res>   - Release a lock\n\
+               inherit on|off       - Toggle priority inheritance\n\
+               autonice on|off      - Renice CPU hogs automatically\n\
+               sem_create <n> <c>   - Create a semaphore with count c\n\
+               sem_wait <n> <pid>   - P: take a unit or block\n\
+               sem_signal <n>       - V: release a unit, waking a waiter\n\
+               deadlock             - Detect circular waits on locks\n\
+             \n\
+             Programs:\n\
+               programs             - List available programs\n\
+               programs <query>     - Programs whose name/description match\n\
+               run_program <n> [count] - Execute a program (count copies)\n\
+               unregister_program <n> - Remove a program\n\
+               edit_program <n> <u> - Set quantum usage (0.0-1.0)\n\
+               script_program <n> <TF..> - Script full (T) / early (F) quanta\n\
+             \n\
+             Statistics:\n\
+               stats                - Show metrics\n\
+               stats --sort <key>   - Order by turnaround|waiting|execution\n\
+               stats --json         - Full stats as JSON\n\
+               stats --since <tick> - Switches and CPU time from tick onward\n\
+               metrics <pid>        - Process metrics\n\
+               usage                - CPU time per owner\n\
+               reset_stats          - Clear statistics\n\
+               reset_metrics <pid>  - Zero one process's metrics\n\
+               validate             - Check CPU-time accounting invariants\n\
+               find_orphans         - Live processes whose parent is gone\n\
+               warmup <cycles>      - Exclude leading cycles from metrics\n\
+             \n\
+             System:\n\
+               bench [--csv] [--repeat N] - Compare MLFQ, FCFS and RR\n\
+               replay <file> [--speed N] - Play back a saved trace\n\
+               trace_export <file>  - Save the execution log as JSON\n\
+               history              - List commands run this session\n\
+               history --save <f>   - Write the history to a file\n\
+               history --load <f>   - Re-run a saved history\n\
+               selftest             - Run scheduler scenarios\n\
+               seed <value>         - Reseed the scheduling RNG\n\
+               uptime               - Session time, process counts, load, idle%\n\
+               subshell             - Explore in a copy of the current state\n\
+               exit merge|discard   - Leave a subshell, keeping or dropping changes\n\
+               help                 - Show this help\n\
+               exit                 - Exit simulator\n"
+        )
+    }
+
+    // ========================================================================
+    // UTILITY METHODS
+    // ========================================================================
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn process_count(&self) -> usize {
+        self.manager.process_count()
+    }
+
+    /// Set how many PIDs `queues` lists per level before truncating with `...`
+    pub fn set_queue_display_limit(&mut self, limit: usize) {
+        self.queue_display_limit = limit;
+    }
+
+    /// Replace how `replay` and `sched_stats --watch` wait between frames
+    pub fn set_sleeper(&mut self, sleeper: Box<dyn Sleeper>) {
+        self.sleeper = sleeper;
+    }
+
+    /// Replace where `sched_stats --watch` and `replay` draw their output
+    pub fn set_screen(&mut self, screen: Box<dyn Screen>) {
+        self.screen = screen;
+    }
+
+    /// Replace how `sched_stats --watch` advances the simulation per frame
+    pub fn set_watch_step(&mut self, step: fn(&mut Shell)) {
+        self.watch_step = step;
+    }
+
+    /// Seed the RNG was last set from, or None if it was seeded from entropy
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Current simulated time in ms
+    pub fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    /// Per-process metrics as structured rows, in PID order
+    pub fn metrics_table(&self) -> Vec<ProcessMetricsRow> {
+        self.stats
+            .process_metrics
+            .values()
+            .map(|m| ProcessMetricsRow {
+                pid: m.pid,
+                name: self.manager.get_process(m.pid).map(|p| p.name.clone()).unwrap_or_default(),
+                terminated: m.terminated,
+                turnaround_time: m.turnaround_time,
+                response_time: m.response_time,
+                waiting_time: m.waiting_time,
+                execution_time: m.execution_time,
+                context_switches: m.context_switches,
+                queue_changes: m.queue_changes,
+            })
+            .collect()
+    }
+
+    /// Every slice of CPU time a process received, as `(start_tick, duration, queue)`
+    pub fn timeline(&self, pid: u32) -> Vec<(u64, u64, usize)> {
+        self.stats
+            .process_timeline(pid)
+            .iter()
+            .map(|slice| (slice.start_tick, slice.duration, slice.queue))
+            .collect()
+    }
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::SchedulerConfig;
+
+    #[test]
+    fn test_parse_fork() {
+        let cmd = parse_command("fork 1").unwrap();
+        assert_eq!(cmd, Command::Fork { ppid: 1 });
+    }
+
+    #[test]
+    fn test_parse_ps() {
+        let cmd = parse_command("ps").unwrap();
+        assert_eq!(cmd, Command::Ps);
+    }
+
+    #[test]
+    fn test_parse_run() {
+        let cmd = parse_command("run 2").unwrap();
+        assert_eq!(cmd, Command::Run { pid: 2 });
+    }
+
+    #[test]
+    fn test_parse_kill() {
+        let cmd = parse_command("kill 2").unwrap();
+        assert_eq!(cmd, Command::Kill { pid: 2, force: false });
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let cmd = parse_command("schedule 5").unwrap();
+        assert_eq!(cmd, Command::Schedule { cycles: 5, no_boost: false });
+    }
+
+    #[test]
+    fn test_parse_nice() {
+        let cmd = parse_command("nice 2 0").unwrap();
+        assert_eq!(cmd, Command::Nice { pid: 2, priority: 0 });
+    }
+
+    #[test]
+    fn test_parse_sched_stats() {
+        let cmd = parse_command("sched_stats").unwrap();
+        assert_eq!(cmd, Command::SchedStats);
+    }
+
+    #[test]
+    fn test_parse_programs() {
+        let cmd = parse_command("programs").unwrap();
+        assert_eq!(cmd, Command::Programs);
+
+        let cmd = parse_command("programs network").unwrap();
+        assert_eq!(cmd, Command::ProgramSearch { query: "network".to_string() });
+        let output = Shell::new().execute(cmd);
+        assert!(output.starts_with("Programs matching 'network' (2):"));
+        assert!(output.contains("file_transfer - Transfers files over network"));
+    }
+
+    #[test]
+    fn test_parse_run_program() {
+        let cmd = parse_command("run_program video_encoder").unwrap();
+        assert_eq!(cmd, Command::RunProgram { program_name: "video_encoder".to_string(), count: 1 });
+    }
+
+    #[test]
+    fn test_run_program_count_spawns_instances() {
+        assert_eq!(
+            parse_command("run_program web_browser 5").unwrap(),
+            Command::RunProgram { program_name: "web_browser".to_string(), count: 5 }
+        );
+        assert_eq!(parse_command("run_program web_browser 0"), None);
+        assert_eq!(parse_command("run_program web_browser x"), None);
+
+        let mut shell = Shell::with_seed(2005);
+        let output = shell.execute_line("run_program web_browser 5");
+        assert!(output.starts_with("✓ Program 'web_browser' started 5 of 5 instances as PIDs 2-6"), "{}", output);
+
+        assert_eq!(shell.processes_running_program("web_browser"), vec![2, 3, 4, 5, 6]);
+        for pid in 2..=6 {
+            let process = shell.manager.get_process(pid).unwrap();
+            assert_eq!(process.name, "web_browser");
+            assert_eq!(shell.scheduler.get_process_queue(pid), Some(process.priority as usize));
+        }
+
+        let too_many = shell.execute_line(&format!("run_program web_browser {}", RUN_PROGRAM_MAX_INSTANCES + 1));
+        assert_eq!(too_many, format!("Error: Cannot start 1001 instances at once (at most {})", RUN_PROGRAM_MAX_INSTANCES));
+        assert_eq!(shell.manager.process_count(), 6);
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let cmd = parse_command("stats").unwrap();
+        assert_eq!(cmd, Command::Stats);
+    }
+
+    #[test]
+    fn test_parse_stats_sort() {
+        let cmd = parse_command("stats --sort waiting").unwrap();
+        assert_eq!(cmd, Command::StatsSorted { key: MetricSortKey::Waiting });
+        assert!(parse_command("stats --sort nonsense").is_none());
+        assert!(parse_command("stats --sort").is_none());
+    }
+
+    #[test]
+    fn test_stats_json() {
+        assert_eq!(parse_command("stats --json").unwrap(), Command::StatsJson);
+        assert!(parse_command("stats --json extra").is_none());
+
+        let mut shell = Shell::with_seed(1);
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Schedule { cycles: 5, no_boost: false });
+
+        let json: serde_json::Value =
+            serde_json::from_str(&shell.execute(Command::StatsJson)).unwrap();
+        assert_eq!(json["processes"].as_array().unwrap().len(), 2);
+        assert_eq!(json["totals"]["context_switches"], 5);
+    }
+
+    #[test]
+    fn test_stats_since_excludes_earlier_switches() {
+        assert_eq!(parse_command("stats --since 40"), Some(Command::StatsSince { tick: 40 }));
+        assert!(parse_command("stats --since").is_none());
+
+        let mut shell = Shell::with_seed(2021);
+        shell.execute(Command::Schedule { cycles: 5, no_boost: false }); // only init runs
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+        let phase_start = shell.clock;
+        shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+
+        assert_eq!(shell.stats.total_context_switches, 8);
+        let report = shell.execute_line(&format!("stats --since {}", phase_start));
+        assert!(report.contains(&format!("Metrics Since Tick {}:", phase_start)), "{}", report);
+        assert!(report.contains("Context Switches:         3\n"), "{}", report);
+        assert!(report.contains("\n2    3       "), "{}", report);
+        assert!(!report.contains("\n1    "), "{}", report);
+
+        assert!(shell.execute_line("stats --since 999999").starts_with("Error: Tick 999999 is in the future"));
+    }
+
+    #[test]
+    fn test_stats_sort_waiting_lists_worst_first() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.stats.record_process_terminated(2, 50, 0);
+        shell.stats.record_process_terminated(3, 500, 0);
+
+        let report = shell.execute(Command::StatsSorted { key: MetricSortKey::Waiting });
+        let table = &report[report.find("Per-Process Metrics").unwrap()..];
+        let first_row = table.lines().nth(4).unwrap();
+        assert!(first_row.starts_with("3 "));
+    }
+
+    #[test]
+    fn test_parse_metrics() {
+        let cmd = parse_command("metrics 2").unwrap();
+        assert_eq!(cmd, Command::Metrics { pid: 2 });
+    }
+
+    #[test]
+    fn test_shell_creation() {
+        let shell = Shell::new();
+        assert!(shell.is_running());
+        assert_eq!(shell.process_count(), 1);
+    }
+
+    #[test]
+    fn test_shell_fork_process() {
+        let mut shell = Shell::new();
+        let result = shell.execute(Command::Fork { ppid: 1 });
+
+        assert!(result.contains("✓"));
+        assert_eq!(shell.process_count(), 2);
+    }
+
+    #[test]
+    fn test_shell_kill_process() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        assert_eq!(shell.process_count(), 2);
+
+        let result = shell.execute(Command::Kill { pid: 2, force: true });
+
+        assert!(result.contains("✓"));
+
+        let info = shell.execute(Command::Info { pid: 2 });
+        assert!(info.contains("State:                terminated"));
+    }
+
+    #[test]
+    fn test_parse_kill_now() {
+        assert_eq!(parse_command("kill --now 3").unwrap(), Command::Kill { pid: 3, force: true });
+        assert_eq!(parse_command("kill 3 --now").unwrap(), Command::Kill { pid: 3, force: true });
+        assert!(parse_command("kill --now").is_none());
+    }
+
+    #[test]
+    fn test_kill_grace_period_escalates_to_sigkill() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+
+        let result = shell.execute(Command::Kill { pid: 2, force: false });
+        assert!(result.contains("SIGTERM"));
+
+        shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        assert_ne!(shell.manager.get_process(2).unwrap().state, ProcessState::Terminated);
+        assert!(shell.scheduler.contains(2));
+
+        let output = shell.execute(Command::Schedule { cycles: DEFAULT_KILL_GRACE_CYCLES - 1, no_boost: false });
+        assert!(output.contains("SIGKILL"));
+        assert_eq!(shell.manager.get_process(2).unwrap().state, ProcessState::Terminated);
+        assert_eq!(shell.stats.processes_terminated, 1);
+    }
+
+    #[test]
+    fn test_kill_now_skips_grace_period() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Kill { pid: 2, force: false });
+
+        let result = shell.execute(Command::Kill { pid: 2, force: true });
+        assert!(result.contains("SIGKILL"));
+        assert_eq!(shell.manager.get_process(2).unwrap().state, ProcessState::Terminated);
+
+        // The pending SIGTERM must not fire a second termination later on
+        shell.execute(Command::Schedule { cycles: DEFAULT_KILL_GRACE_CYCLES, no_boost: false });
+        assert_eq!(shell.stats.processes_terminated, 1);
+    }
+
+    #[test]
+    fn test_shell_cannot_kill_init() {
+        let mut shell = Shell::new();
+        let result = shell.execute(Command::Kill { pid: 1, force: false });
+
+        assert!(result.contains("Error"));
+    }
+
+    #[test]
+    fn test_shell_run_process() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        let result = shell.execute(Command::Run { pid: 2 });
+
+        assert!(result.contains("✓"));
+    }
+
+    #[test]
+    fn test_shell_block_unblock() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+
+        let block_result = shell.execute(Command::Block { pid: 2 });
+        assert!(block_result.contains("✓"));
+
+        let unblock_result = shell.execute(Command::Unblock { pid: 2 });
+        assert!(unblock_result.contains("✓"));
+    }
+
+    #[test]
+    fn test_block_and_stop_keep_q0_at_default_priority() {
+        let mut shell = Shell::with_seed(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2, priority 3
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 0);
+
+        shell.execute(Command::Block { pid: 2 });
+        shell.execute(Command::Unblock { pid: 2 });
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(0));
+
+        shell.execute(Command::Stop { pid: 2 });
+        shell.execute(Command::Cont { pid: 2 });
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(0));
+
+        shell.execute(Command::Stop { pid: 2 });
+        shell.execute(Command::Run { pid: 2 });
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(0));
+    }
+
+    #[test]
+    fn test_schedule_advances_program_counter() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.scheduler.remove_process(1);
+        shell.manager.get_process_mut(2).unwrap().program_length = Some(100);
+
+        shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        let pc = shell.manager.get_process(2).unwrap().program_counter;
+        assert!(pc > 0);
+        assert!(!shell.manager.get_process(2).unwrap().is_complete());
+
+        let output = shell.execute(Command::Schedule { cycles: 20, no_boost: false });
+        assert!(shell.manager.get_process(2).unwrap().is_complete());
+        assert!(output.contains("Burst complete"));
+        assert!(shell.execute(Command::Info { pid: 2 }).contains("100/100 (complete)"));
+    }
+
+    #[test]
+    fn test_early_yield_credits_part_of_quantum() {
+        let mut shell = Shell::with_seed(3);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+        let program = crate::scheduler::Program::new(
+            "poller", crate::scheduler::ProgramType::IoBound, "Always waits on I/O", 0.0,
+        );
+        shell.process_programs.insert(2, program);
+
+        let output = shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        assert!(output.contains("Yielded early"));
+
+        let executed = shell.stats.process_metrics[&2].execution_time;
+        assert!(executed > 0 && executed < 8, "credited {}ms of an 8ms quantum", executed);
+        assert_eq!(shell.manager.get_process(2).unwrap().total_time, executed);
+        assert_eq!(shell.stats.total_execution_time, executed);
+    }
+
+    #[test]
+    fn test_trace_reports_staying_within_allotment() {
+        let mut shell = Shell::with_seed(5);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+        let program = crate::scheduler::Program::new(
+            "cruncher", crate::scheduler::ProgramType::CpuBound, "Never yields", 1.0,
+        );
+        shell.process_programs.insert(2, program);
+        shell.scheduler.set_level_allotment(0, 24);
+
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 0);
+
+        let output = shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+        assert!(output.contains("Used full quantum → Stays in Q0 (allotment 8/24ms)"), "{}", output);
+        assert!(output.contains("Used full quantum → Stays in Q0 (allotment 16/24ms)"), "{}", output);
+        assert!(output.contains("Used full quantum → Demoted to Q1"), "{}", output);
+        assert!(!output.contains("Demoted to Q0"), "{}", output);
+    }
+
+    #[test]
+    fn test_cpu_bound_process_sinks() {
+        let mut shell = Shell::with_seed(5);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+        let program = crate::scheduler::Program::new(
+            "cruncher", crate::scheduler::ProgramType::CpuBound, "Never yields", 1.0,
+        );
+        shell.process_programs.insert(2, program);
+
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 0);
+
+        shell.execute(Command::Schedule { cycles: 6, no_boost: false });
+
+        let metrics = shell.stats.get_process_metrics(2).unwrap();
+        assert!(metrics.demotions > metrics.promotions);
+        assert_eq!(metrics.demotions, 3); // Q0 → Q1 → Q2 → Q3, then stays put
+        assert_eq!(metrics.queue_changes, metrics.demotions + metrics.promotions + metrics.boosts);
+        assert!(shell.execute(Command::Metrics { pid: 2 }).contains("(3 demotions, 0 promotions, 0 boosts)"));
+    }
+
+    #[test]
+    fn test_total_time_counts_past_u32_max() {
+        let mut shell = Shell::with_seed(2020);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+        let start = u32::MAX as u64 - 3;
+        shell.manager.get_process_mut(2).unwrap().total_time = start;
+
+        shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+
+        let executed = shell.stats.process_metrics[&2].execution_time;
+        assert!(executed > 3, "only ran {}ms", executed);
+        assert_eq!(shell.manager.get_process(2).unwrap().total_time, start + executed);
+    }
+
+    #[test]
+    fn test_mlfq_convergence_detection() {
+        use crate::scheduler::metrics::DEFAULT_CONVERGENCE_WINDOW;
+        use crate::scheduler::{Program, ProgramType};
+
+        // Fork PIDs 2 and 3 into Q0 and run with the periodic boost on,
+        // stopping 12 cycles after the boost at cycle 200. PID 3 is
+        // interactive: after each burst it waits 50ms on I/O, so it doesn't
+        // starve PID 2 out of the lower queues.
+        let workload = |first: Program| {
+            let mut shell = Shell::with_seed(2022);
+            shell.scheduler.remove_process(1);
+            let poller = Program::new("poller", ProgramType::IoBound, "Always waits on I/O", 0.0);
+            for (pid, program) in [(2, first), (3, poller)] {
+                shell.execute(Command::Fork { ppid: 1 });
+                shell.process_programs.insert(pid, program);
+                shell.scheduler.remove_process(pid);
+                shell.scheduler.add_process_to_queue(pid, 0);
+            }
+
+            let mut poller_runs = 0;
+            for _ in 0..212 {
+                shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+                let runs = shell.stats.get_process_metrics(3).unwrap().context_switches;
+                if runs > poller_runs {
+                    poller_runs = runs;
+                    shell.execute(Command::Sleep { pid: 3, ms: 50 });
+                }
+            }
+            assert!(poller_runs > 20, "poller only ran {} times", poller_runs);
+            shell
+        };
+        let level = |shell: &Shell, pid: u32| {
+            shell.scheduler.get_process_queue(pid).or_else(|| shell.parked.get(&pid).copied())
+        };
+
+        let settled = workload(Program::new("cruncher", ProgramType::CpuBound, "Never yields", 1.0));
+        assert!(settled.stats.get_process_metrics(2).unwrap().boosts >= 2);
+        assert_eq!(level(&settled, 2), Some(3));
+        assert_eq!(level(&settled, 3), Some(0));
+        assert!(settled.stats.is_converged(DEFAULT_CONVERGENCE_WINDOW));
+        assert!(settled.cmd_stats().contains("Converged: no queue changes for"));
+
+        let mut flipper = Program::new("flipper", ProgramType::Mixed, "Alternates", 0.5);
+        flipper.set_behavior_script([true, false].repeat(110));
+        let oscillating = workload(flipper);
+        assert!(oscillating.stats.get_process_metrics(2).unwrap().queue_changes > 20);
+        assert!(!oscillating.stats.is_converged(DEFAULT_CONVERGENCE_WINDOW));
+        assert!(oscillating.cmd_stats().contains("Not converged:"));
+    }
+
+    #[test]
+    fn test_process_terminates_when_burst_completes() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.scheduler.remove_process(1);
+        shell.manager.get_process_mut(2).unwrap().program_length = Some(100);
+
+        shell.execute(Command::Schedule { cycles: 20, no_boost: false });
+
+        let process = shell.manager.get_process(2).unwrap();
+        assert_eq!(process.state, ProcessState::Terminated);
+        assert_eq!(process.total_time, 100);
+        assert_eq!(shell.scheduler.get_process_queue(2), None);
+
+        assert_eq!(shell.stats.processes_terminated, 1);
+        assert_eq!(shell.stats.get_process_metrics(2).unwrap().execution_time, 100);
+    }
+
+    #[test]
+    fn test_parse_waitall() {
+        assert_eq!(parse_command("waitall").unwrap(), Command::WaitAll { max_cycles: None });
+        assert_eq!(parse_command("waitall 50").unwrap(), Command::WaitAll { max_cycles: Some(50) });
+    }
+
+    #[test]
+    fn test_waitall_runs_workload_to_completion() {
+        let mut shell = Shell::new();
+        shell.execute(Command::RunProgram { program_name: "terminal".to_string(), count: 1 });
+        shell.execute(Command::RunProgram { program_name: "web_browser".to_string(), count: 1 });
+        shell.execute(Command::RunProgram { program_name: "database".to_string(), count: 1 });
+
+        let output = shell.execute(Command::WaitAll { max_cycles: None });
+
+        assert!(output.contains("All processes terminated"));
+        assert!(output.contains("SCHEDULER METRICS"));
+        assert_eq!(shell.stats.processes_terminated, 3);
+        assert_eq!(shell.manager.active_processes().len(), 1);
+    }
+
+    #[test]
+    fn test_waitall_respects_cycle_cap() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+
+        let output = shell.execute(Command::WaitAll { max_cycles: Some(25) });
+
+        assert!(output.contains("Stopped after 25 cycles"));
+        assert_eq!(shell.stats.total_ticks, 25);
+    }
+
+    #[test]
+    fn test_switches_credited_per_queue() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.scheduler.remove_process(1);
+
+        for _ in 0..10 {
+            shell.execute(Command::Nice { pid: 2, priority: 0 });
+            shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        }
+
+        assert_eq!(shell.stats.switches_per_queue[0], 10);
+        assert_eq!(shell.stats.switches_per_queue[3], 0);
+        assert!(shell.execute(Command::SchedStats).contains("Context Switches by Queue"));
+    }
+
+    #[test]
+    fn test_timeline_reports_each_dispatch() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Nice { pid: 1, priority: 0 });
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 3);
+
+        // Cycle 1 runs init from Q0, then PID 2 is interleaved with init
+        shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+
+        let timeline = shell.timeline(2);
+        assert_eq!(timeline.len(), 2);
+        // Slices may be cut short by early yields, so check they line up back to back
+        let init_slice = shell.timeline(1)[0];
+        assert_eq!(init_slice.0, 0);
+        assert_eq!(timeline[0].0, init_slice.1);
+        assert_eq!(timeline[0].2, 3);
+        assert!(timeline[0].1 <= 64);
+        assert_eq!(timeline[1].0, timeline[0].0 + timeline[0].1);
+        assert_eq!(shell.clock(), timeline[1].0 + timeline[1].1);
+    }
+
+    #[test]
+    fn test_parse_program_management() {
+        assert_eq!(
+            parse_command("unregister_program backup").unwrap(),
+            Command::UnregisterProgram { program_name: "backup".to_string() }
+        );
+        assert_eq!(
+            parse_command("edit_program backup 0.4").unwrap(),
+            Command::EditProgram { program_name: "backup".to_string(), usage: 0.4 }
+        );
+        assert!(parse_command("edit_program backup").is_none());
+        assert_eq!(
+            parse_command("script_program game TFt").unwrap(),
+            Command::ScriptProgram { program_name: "game".to_string(), script: vec![true, false, true] }
+        );
+        assert!(parse_command("script_program game TXF").is_none());
+    }
+
+    #[test]
+    fn test_unregister_program_guards_running_processes() {
+        let mut shell = Shell::new();
+        shell.execute(Command::RunProgram { program_name: "backup".to_string(), count: 1 });
+
+        let result = shell.execute(Command::UnregisterProgram { program_name: "backup".to_string() });
+        assert!(result.contains("Error"));
+        assert!(shell.registry.get_program("backup").is_some());
+
+        shell.execute(Command::Kill { pid: 2, force: true });
+        let result = shell.execute(Command::UnregisterProgram { program_name: "backup".to_string() });
+        assert!(result.contains("✓"));
+        assert!(shell.execute(Command::RunProgram { program_name: "backup".to_string(), count: 1 }).contains("Error"));
+    }
+
+    #[test]
+    fn test_edit_program() {
+        let mut shell = Shell::new();
+        let result = shell.execute(Command::EditProgram { program_name: "game".to_string(), usage: 0.9 });
+        assert!(result.contains("✓"));
+        assert_eq!(shell.registry.get_program("game").unwrap().typical_quantum_usage, 0.9);
+
+        let result = shell.execute(Command::EditProgram { program_name: "game".to_string(), usage: 1.5 });
+        assert!(result.contains("Error"));
+    }
+
+    #[test]
+    fn test_scripted_program_follows_script() {
+        let mut shell = Shell::new();
+        let result = shell.execute_line("script_program game TFT");
+        assert!(result.starts_with("✓"), "{}", result);
+        shell.execute(Command::RunProgram { program_name: "game".to_string(), count: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+
+        let output = shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+        let decisions: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("• "))
+            .map(|line| line.split(" →").next().unwrap())
+            .collect();
+        assert_eq!(decisions, vec!["Used full quantum", "Yielded early", "Used full quantum"]);
+        assert!(shell.execute_line("script_program nope TF").starts_with("Error"));
+    }
+
+    #[test]
+    fn test_autonice_lowers_cpu_hog() {
+        // One level is plain round robin, so the hog keeps getting the CPU
+        let config = ShellConfig {
+            scheduler: SchedulerConfig { quantums: vec![20], boost_interval: 0, ..Default::default() },
+            seed: Some(11),
+            ..Default::default()
+        };
+        let mut shell = Shell::from_config(&config).unwrap();
+        assert_eq!(parse_command("autonice on").unwrap(), Command::Autonice { enabled: true });
+        shell.execute(Command::Autonice { enabled: true });
+        shell.scheduler.remove_process(1);
+
+        // Every worker keeps the default priority (3)
+        for pid in 2..=5 {
+            shell.execute(Command::Fork { ppid: 1 });
+            let usage = if pid == 2 { 1.0 } else { 0.0 };
+            let program = crate::scheduler::Program::new(
+                "worker", crate::scheduler::ProgramType::Mixed, "Autonice test", usage,
+            );
+            shell.process_programs.insert(pid, program);
+        }
+
+        let output = shell.execute(Command::Schedule { cycles: 20, no_boost: false });
+        assert!(output.contains("Autonice: PID 2 nice 3 → 5"), "{}", output);
+        let hog = shell.scheduler.nice(2);
+        assert_eq!(hog, 5);
+        assert!(hog > shell.scheduler.nice(3));
+        assert_eq!(shell.scheduler.nice(3), 3);
+        assert_eq!(shell.manager.get_process(2).unwrap().priority, 3);
+
+        shell.execute(Command::Autonice { enabled: false });
+        assert_eq!(shell.scheduler.nice(2), 3);
+    }
+
+    #[test]
+    fn test_validate_command() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Schedule { cycles: 4, no_boost: false });
+        shell.execute(Command::ResetMetrics { pid: 2 });
+        assert_eq!(parse_command("validate").unwrap(), Command::Validate);
+        assert_eq!(shell.execute(Command::Validate), "✓ Accounting invariants hold");
+
+        shell.stats.total_execution_time += 1;
+        assert!(shell.execute(Command::Validate).starts_with("Error: total_execution_time"));
+    }
+
+    #[test]
+    fn test_reset_metrics_leaves_other_processes() {
+        let mut shell = Shell::with_seed(5);
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Schedule { cycles: 6, no_boost: false });
+
+        let other = shell.stats.get_process_metrics(3).unwrap().clone();
+        let total = shell.stats.total_execution_time;
+        assert!(shell.stats.get_process_metrics(2).unwrap().context_switches > 0);
+
+        assert_eq!(parse_command("reset_metrics 2").unwrap(), Command::ResetMetrics { pid: 2 });
+        assert!(shell.execute(Command::ResetMetrics { pid: 2 }).starts_with("✓"));
+        assert_eq!(shell.stats.get_process_metrics(2).unwrap().context_switches, 0);
+        assert_eq!(shell.stats.get_process_metrics(3).unwrap().execution_time, other.execution_time);
+        assert_eq!(shell.stats.get_process_metrics(3).unwrap().context_switches, other.context_switches);
+        assert_eq!(shell.stats.total_execution_time, total);
+        assert!(shell.execute(Command::ResetMetrics { pid: 42 }).starts_with("Error"));
+    }
+
+    #[test]
+    fn test_cpu_sampling_during_schedule() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.stats.begin_sampling(std::time::Duration::ZERO);
+
+        for _ in 0..3 {
+            shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+        }
+
+        let series = shell.stats.cpu_series();
+        assert_eq!(series.len(), 6);
+        assert!(series.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    }
+
+    #[test]
+    fn test_nice_to_same_level_keeps_fifo_position() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        for _ in 0..3 {
+            shell.execute(Command::Fork { ppid: 1 });
+        }
+
+        let result = shell.execute(Command::Nice { pid: 2, priority: 3 });
+        assert!(result.contains("unchanged"));
+        assert_eq!(shell.stats.get_process_metrics(2).unwrap().queue_changes, 0);
+
+        let order: Vec<u32> = (0..3).map(|_| shell.scheduler.next_process().unwrap().0).collect();
+        assert_eq!(order, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_usage_reports_separate_owner_totals() {
+        let mut shell = Shell::new();
+        assert_eq!(parse_command("su alice").unwrap(), Command::Su { user: "alice".to_string() });
+
+        shell.execute(Command::Su { user: "alice".to_string() });
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Su { user: "bob".to_string() });
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+
+        shell.manager.get_process_mut(2).unwrap().total_time = 64;
+        shell.manager.get_process_mut(3).unwrap().total_time = 8;
+        shell.manager.get_process_mut(4).unwrap().total_time = 16;
+
+        let usage = shell.manager.usage_by_owner();
+        assert_eq!(usage["alice"].cpu_time, 64);
+        assert_eq!(usage["bob"].cpu_time, 24);
+        assert_eq!(usage["bob"].processes, 2);
+
+        let output = shell.execute(Command::Usage);
+        assert!(output.contains("alice"));
+        assert!(output.contains("24ms"));
+        assert!(shell.execute(Command::Info { pid: 3 }).contains("bob"));
+    }
+
+    #[test]
+    fn test_selftest_passes() {
+        let mut shell = Shell::new();
+        assert_eq!(parse_command("selftest").unwrap(), Command::SelfTest);
+
+        let output = shell.execute(Command::SelfTest);
+        assert!(output.contains("4 passed, 0 failed"));
+        assert!(!output.contains("✗"));
+    }
+
+    #[test]
+    fn test_priority_inheritance_boosts_holder() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2, low priority holder
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Nice { pid: 3, priority: 0 });
+        assert_eq!(parse_command("inherit on").unwrap(), Command::Inherit { enabled: true });
+        shell.execute(Command::Inherit { enabled: true });
+
+        let output = shell.execute(parse_command("lock 2 disk").unwrap());
+        assert!(output.contains("acquired 'disk'"));
+
+        let output = shell.execute(Command::Lock { pid: 3, resource: "disk".to_string() });
+        assert!(output.contains("Priority inversion: PID 3 (Q0) waits on 'disk' held by PID 2 (Q3)"));
+        assert!(output.contains("PID 2 boosted to Q0"));
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(0));
+        assert_eq!(shell.scheduler.get_process_queue(3), None);
+        assert_eq!(shell.manager.get_process(3).unwrap().state, ProcessState::Blocked);
+
+        let output = shell.execute(Command::Unlock { pid: 2, resource: "disk".to_string() });
+        assert!(output.contains("PID 2 restored to Q3"));
+        assert!(output.contains("PID 3 acquired 'disk'"));
+        assert_eq!(shell.scheduler.get_process_queue(3), Some(0));
+        assert_eq!(shell.locks.holder("disk"), Some(3));
+    }
+
+    #[test]
+    fn test_priority_inversion_flagged_without_inheritance() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Nice { pid: 3, priority: 0 });
+
+        shell.execute(Command::Lock { pid: 2, resource: "disk".to_string() });
+        let output = shell.execute(Command::Lock { pid: 3, resource: "disk".to_string() });
+
+        assert!(output.contains("Priority inversion"));
+        assert!(!output.contains("boosted"));
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(3));
+        assert!(shell.execute(Command::Info { pid: 3 }).contains("Waiting On:           disk"));
+
+        // Killing the holder hands the lock to the waiter
+        shell.execute(Command::Kill { pid: 2, force: true });
+        assert_eq!(shell.locks.holder("disk"), Some(3));
+        assert_eq!(shell.manager.get_process(3).unwrap().state, ProcessState::Ready);
+    }
+
+    #[test]
+    fn test_binary_semaphore_serializes_processes() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+
+        assert_eq!(
+            parse_command("sem_create mutex 1").unwrap(),
+            Command::SemCreate { name: "mutex".to_string(), count: 1 }
+        );
+        shell.execute(Command::SemCreate { name: "mutex".to_string(), count: 1 });
+
+        let output = shell.execute(parse_command("sem_wait mutex 2").unwrap());
+        assert!(output.contains("passed wait on 'mutex' (count now 0)"));
+        let output = shell.execute(Command::SemWait { name: "mutex".to_string(), pid: 3 });
+        assert!(output.contains("blocked on 'mutex'"));
+
+        // Only the holder runs while PID 3 is off the run queues
+        let output = shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+        assert!(output.contains("PID 2 ran"));
+        assert!(!output.contains("PID 3 ran"));
+        assert_eq!(shell.manager.get_process(3).unwrap().state, ProcessState::Blocked);
+
+        let output = shell.execute(Command::SemSignal { name: "mutex".to_string() });
+        assert_eq!(output, "✓ Signaled 'mutex': woke process 3");
+        assert_eq!(shell.manager.get_process(3).unwrap().state, ProcessState::Ready);
+        assert!(shell.scheduler.contains(3));
+
+        // No waiters left: the unit goes back to the pool
+        let output = shell.execute(Command::SemSignal { name: "mutex".to_string() });
+        assert!(output.contains("count now 1"));
+    }
+
+    #[test]
+    fn test_deadlock_detects_circular_wait() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+
+        assert_eq!(parse_command("deadlock").unwrap(), Command::Deadlock);
+        assert_eq!(shell.execute(Command::Deadlock), "✓ No deadlock detected");
+
+        shell.execute(Command::Lock { pid: 2, resource: "a".to_string() });
+        shell.execute(Command::Lock { pid: 3, resource: "b".to_string() });
+        shell.execute(Command::Lock { pid: 2, resource: "b".to_string() });
+        assert_eq!(shell.execute(Command::Deadlock), "✓ No deadlock detected");
+
+        shell.execute(Command::Lock { pid: 3, resource: "a".to_string() });
+        let output = shell.execute(Command::Deadlock);
+        assert!(output.contains("Deadlock Detected (1 cycle)"));
+        assert!(output.contains("PID 2 waits on 'b' held by PID 3"));
+        assert!(output.contains("PID 3 waits on 'a' held by PID 2"));
+        assert!(output.contains("Deadlocked processes: 2, 3"));
+    }
+
+    #[test]
+    fn test_pipe_grep_filters_ps() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Block { pid: 2 });
+
+        let output = shell.execute_line("ps | grep blocked");
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines.iter().all(|line| line.contains("blocked")));
+        assert!(lines[0].starts_with("2 "));
+
+        assert!(shell.execute_line("ps | sort").starts_with("Error: Unknown filter 'sort'"));
+        assert!(shell.execute_line("grep Blocked").starts_with("Error: Unknown command"));
+    }
+
+    #[test]
+    fn test_pipe_head_tail_wc() {
+        let mut shell = Shell::new();
+        for _ in 0..6 {
+            shell.execute(Command::Fork { ppid: 1 });
+        }
+
+        // Two header lines plus seven processes
+        assert_eq!(shell.execute_line("ps | wc").split_whitespace().next(), Some("9"));
+        assert_eq!(shell.execute_line("ps | head 5").lines().count(), 5);
+
+        let tail = shell.execute_line("ps | tail 2");
+        assert_eq!(tail.lines().count(), 2);
+        assert!(tail.starts_with("6 "));
+
+        assert_eq!(shell.execute_line("ps | grep ready | head 3 | wc").split_whitespace().next(), Some("3"));
+    }
+
+    #[test]
+    fn test_redirect_output_to_file() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+
+        let path = std::env::temp_dir().join(format!("os-sim-redirect-{}.txt", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+
+        let result = shell.execute_line(&format!("ps > {}", path_str));
+        assert_eq!(result, format!("✓ Output written to {}", path_str));
+        let expected = shell.execute(Command::Ps);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+
+        shell.execute_line(&format!("ps | grep Ready >> {}", path_str));
+        let appended = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(appended, format!("{}{}", expected, shell.execute_line("ps | grep Ready")));
+
+        assert!(shell.execute_line("ps >").starts_with("Error: Invalid redirection"));
+    }
+
+    #[test]
+    fn test_bench_csv_output() {
+        let mut shell = Shell::new();
+        assert_eq!(parse_command("bench --csv").unwrap(), Command::Bench { csv: true, repeat: 1 });
+
+        let csv = shell.execute(Command::Bench { csv: true, repeat: 1 });
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "scheduler,avg_turnaround,avg_waiting,fairness,throughput");
+        assert_eq!(lines.len(), 4);
+
+        let schedulers: Vec<&str> = lines[1..].iter().map(|l| l.split(',').next().unwrap()).collect();
+        assert_eq!(schedulers, vec!["MLFQ", "FCFS", "RR"]);
+        for line in &lines[1..] {
+            let fields: Vec<&str> = line.split(',').collect();
+            assert_eq!(fields.len(), 5);
+            assert!(fields[1..].iter().all(|f| f.parse::<f64>().is_ok()), "{}", line);
+        }
+
+        let table = shell.execute(Command::Bench { csv: false, repeat: 1 });
+        assert!(table.starts_with("Scheduler Comparison"));
+    }
+
+    #[test]
+    fn test_bench_repeat_reports_stddev() {
+        let mut shell = Shell::new();
+        assert_eq!(
+            parse_command("bench --repeat 3 --csv").unwrap(),
+            Command::Bench { csv: true, repeat: 3 }
+        );
+        assert!(parse_command("bench --repeat 0").is_none());
+        assert!(parse_command("bench --repeat").is_none());
+
+        let csv = shell.execute_line("bench --csv --repeat 3");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].contains("avg_turnaround_stddev"));
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1..].iter().all(|line| line.split(',').nth(1) == Some("3")));
+
+        let table = shell.execute_line("bench --repeat 3");
+        assert!(table.starts_with("Scheduler Comparison (3 runs, mean ± stddev)"));
+        assert!(table.contains(" ± "));
+    }
+
+    #[test]
+    fn test_chrt_realtime_preempts_normal() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Nice { pid: 2, priority: 0 });
+
+        assert_eq!(
+            parse_command("chrt 3 rt").unwrap(),
+            Command::Chrt { pid: 3, class: SchedClass::RealTime }
+        );
+        let output = shell.execute(Command::Chrt { pid: 3, class: SchedClass::RealTime });
+        assert!(output.contains("from Normal to RealTime"));
+
+        // PID 3 sits in a lower queue but its class puts it first
+        let output = shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        assert!(output.contains("PID 3 ran"));
+        assert!(shell.execute(Command::Info { pid: 3 }).contains("Sched Class:          RealTime"));
+    }
+
+    #[test]
+    fn test_idle_task_accrues_time_when_nothing_runnable() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Block { pid: 1 });
+
+        let output = shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+        assert!(output.contains("@t=0 Cycle 1: idle (PID 0) for 8ms"), "{}", output);
+        assert!(output.contains("@t=16 Cycle 3: idle (PID 0) for 8ms"), "{}", output);
+        assert_eq!(shell.stats.idle_time, 24);
+        assert_eq!(shell.clock, 24);
+        assert!(shell.execute(Command::Stats).contains("Idle Time (PID 0):        24ms"));
+
+        assert!(shell.execute(Command::Kill { pid: 0, force: true }).contains("Cannot kill the idle process"));
+        assert!(shell.execute(Command::Nice { pid: 0, priority: 1 }).contains("Cannot renice the idle process"));
+    }
+
+    #[test]
+    fn test_chrt_idle_runs_only_when_alone() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Chrt { pid: 2, class: SchedClass::Idle });
+
+        let output = shell.execute(Command::Schedule { cycles: 5, no_boost: false });
+        assert!(!output.contains("PID 2 ran"));
+
+        shell.execute(Command::Kill { pid: 3, force: true });
+        let output = shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        assert!(output.contains("PID 2 ran"));
+    }
+
+    #[test]
+    fn test_sched_class_survives_block_and_nice() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Chrt { pid: 2, class: SchedClass::RealTime });
+
+        shell.execute(Command::Block { pid: 2 });
+        shell.execute(Command::Unblock { pid: 2 });
+        assert_eq!(shell.scheduler.sched_class(2), SchedClass::RealTime);
+
+        shell.execute(Command::Nice { pid: 2, priority: 1 });
+        assert_eq!(shell.scheduler.sched_class(2), SchedClass::RealTime);
+
+        // Only exiting forgets it
+        shell.execute(Command::Kill { pid: 2, force: true });
+        assert_eq!(shell.scheduler.sched_class(2), SchedClass::default());
+    }
+
+    #[test]
+    fn test_queues_lists_pids_in_order() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Nice { pid: 3, priority: 1 });
+
+        let output = shell.execute(Command::Queues);
+        assert!(output.contains("Q1 (16ms):  [3]\n"));
+        assert!(output.contains("Q2 (32ms):  []\n"));
+        assert!(output.contains("Q3 (64ms):  [1, 2]\n"));
+    }
+
+    #[test]
+    fn test_queues_truncates_past_display_limit() {
+        let mut shell = Shell::new();
+        for _ in 0..4 {
+            shell.execute(Command::Fork { ppid: 1 });
+        }
+        shell.set_queue_display_limit(3);
+
+        let output = shell.execute(Command::Queues);
+        assert!(output.contains("Q3 (64ms):  [1, 2, 3, ...] (5 processes)"));
+    }
+
+    #[test]
+    fn test_ps_filtered_by_state() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Block { pid: 3 });
+
+        assert_eq!(
+            parse_command("ps Blocked").unwrap(),
+            Command::PsState { state: ProcessState::Blocked }
+        );
+        assert!(parse_command("ps zombie").is_none());
+
+        let output = shell.execute(Command::PsState { state: ProcessState::Blocked });
+        let rows: Vec<&str> = output.lines().skip(2).collect();
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].starts_with("3 "));
+    }
+
+    #[test]
+    fn test_rename_shows_in_ps() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        assert_eq!(
+            parse_command("rename 2 webserver").unwrap(),
+            Command::Rename { pid: 2, name: "webserver".to_string() }
+        );
+        assert!(parse_command("rename 2 two words").is_none());
+
+        let before = shell.execute(Command::Ps);
+        assert!(before.lines().any(|line| line.starts_with("1 ") && line.ends_with(" init")));
+        assert!(before.lines().any(|line| line.starts_with("2 ") && line.ends_with(" init-child")));
+
+        assert_eq!(
+            shell.execute(Command::Rename { pid: 2, name: "webserver".to_string() }),
+            "✓ Process 2 renamed from 'init-child' to 'webserver'"
+        );
+        let after = shell.execute(Command::Ps);
+        assert!(after.lines().any(|line| line.starts_with("2 ") && line.ends_with(" webserver")));
+        assert!(shell.execute(Command::Info { pid: 2 }).contains("Name:                 webserver"));
+
+        shell.execute(Command::Fork { ppid: 2 }); // PID 3
+        assert_eq!(shell.manager.get_process(3).unwrap().name, "webserver-child");
+        assert!(shell.execute(Command::Rename { pid: 9, name: "x".to_string() }).starts_with("Error"));
+    }
+
+    #[test]
+    fn test_ps_columns_widen_to_fit_values() {
+        let shell = Shell::new();
+        let mut wide = Process::new(12345, 1);
+        wide.set_state(ProcessState::Terminated);
+        let narrow = Process::new(7, 1);
+
+        let output = shell.process_table([&wide, &narrow].into_iter());
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[2].starts_with("12345 1    terminated"));
+        assert!(lines[3].starts_with("7     1    ready     "));
+
+        // Every row's PRIORITY column starts where its header does
+        let column = lines[0].find("PRIORITY").unwrap();
+        assert_eq!(lines[1].chars().count(), lines[2].len());
+        assert_eq!(&lines[2][column..column + 1], "3");
+        assert_eq!(&lines[3][column..column + 1], "3");
+    }
+
+    #[test]
+    fn test_queues_shows_boost_countdown() {
+        let mut shell = Shell::new();
+        assert!(shell.execute(Command::Queues).contains("next boost in 100 ticks"));
+
+        shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+        assert!(shell.execute(Command::Queues).contains("next boost in 97 ticks"));
+        assert!(shell.execute(Command::SchedStats).contains("next boost in 97 ticks"));
+
+        shell.scheduler.set_boost_interval(0);
+        assert!(shell.execute(Command::Queues).contains("Priority Boost:   disabled"));
+    }
+
+    #[test]
+    fn test_state_breakdown_counts_blocked_and_sleeping() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Fork { ppid: 1 }); // PID 4
+
+        shell.execute(Command::Block { pid: 2 });
+        assert_eq!(parse_command("sleep 3 10000").unwrap(), Command::Sleep { pid: 3, ms: 10000 });
+        let output = shell.execute(Command::Sleep { pid: 3, ms: 10000 });
+        assert!(output.contains("sleeping for 10000ms"));
+        shell.execute(Command::Stop { pid: 4 });
+
+        let output = shell.execute(Command::Schedule { cycles: 4, no_boost: false });
+        assert!(!output.contains("PID 2 ran"));
+        assert!(!output.contains("PID 3 ran"));
+        assert!(!output.contains("PID 4 ran"));
+
+        let breakdown = shell.stats.state_breakdown;
+        assert_eq!(breakdown.samples, 4);
+        assert_eq!(breakdown.blocked, 4);
+        assert_eq!(breakdown.sleeping, 4);
+        assert_eq!(breakdown.stopped, 4);
+        assert!(shell.stats.summary_report().contains("State Breakdown"));
+    }
+
+    #[test]
+    fn test_sleeper_wakes_when_clock_passes() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Sleep { pid: 2, ms: 50 });
+
+        // Nothing else is runnable, so the clock idles forward to the wake-up
+        let output = shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        assert!(output.contains("PID 2 woke up at 50ms"));
+        assert!(output.contains("PID 2 ran"));
+
+        shell.execute(Command::Stop { pid: 2 });
+        assert_eq!(shell.execute(Command::Cont { pid: 2 }), "✓ Process 2 continued");
+        assert_eq!(shell.manager.get_process(2).unwrap().state, ProcessState::Ready);
+    }
+
+    #[test]
+    fn test_occupancy_sorts_workloads_by_queue() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.scheduler.set_boost_interval(0);
+        shell.execute(Command::EditProgram { program_name: "video_encoder".to_string(), usage: 1.0 });
+        shell.execute(Command::EditProgram { program_name: "text_editor".to_string(), usage: 0.0 });
+        shell.execute(Command::RunProgram { program_name: "video_encoder".to_string(), count: 1 });
+        shell.execute(Command::RunProgram { program_name: "text_editor".to_string(), count: 1 });
+
+        shell.execute(Command::Schedule { cycles: 30, no_boost: false });
+
+        let occupancy = shell.stats.queue_occupancy;
+        assert_eq!(occupancy.samples, 30);
+        assert!(occupancy.io_fraction(0) > occupancy.cpu_fraction(0));
+        assert!(occupancy.cpu_fraction(3) > occupancy.io_fraction(3));
+        assert_eq!(shell.stats.queue_depth_samples.len(), 30);
+
+        assert_eq!(parse_command("occupancy").unwrap(), Command::Occupancy);
+        assert!(shell.execute(Command::Occupancy).contains("Queue Occupancy by Workload (30 samples)"));
+    }
+
+    #[test]
+    fn test_interactive_goodput_beats_batch_under_interactive_load() {
+        let mut shell = Shell::with_seed(2000);
+        shell.scheduler.remove_process(1);
+        for _ in 0..4 {
+            shell.execute(Command::RunProgram { program_name: "terminal".to_string(), count: 1 });
+        }
+        shell.execute(Command::RunProgram { program_name: "backup".to_string(), count: 1 });
+        for pid in 2..=6 {
+            shell.execute(Command::Nice { pid, priority: 0 });
+        }
+        assert_eq!(parse_command("switch_cost 1").unwrap(), Command::SwitchCost { ms: 1 });
+        shell.execute(Command::SwitchCost { ms: 1 });
+
+        shell.execute(Command::Schedule { cycles: 200, no_boost: false });
+
+        let goodput = shell.stats.goodput;
+        assert!(goodput.interactive > goodput.batch, "{:?}", goodput);
+        assert_eq!(goodput.switch_overhead, 200);
+        assert_eq!(goodput.useful(), shell.stats.total_execution_time);
+
+        assert_eq!(parse_command("goodput").unwrap(), Command::Goodput);
+        assert!(shell.execute(Command::Goodput).contains("Goodput: "));
+    }
+
+    #[test]
+    fn test_rebalance_after_bulk_kill() {
+        let mut shell = Shell::with_seed(2002);
+        for _ in 0..40 {
+            shell.execute(Command::Fork { ppid: 1 });
+        }
+        for pid in 2..=41 {
+            shell.execute(Command::Kill { pid, force: true });
+        }
+
+        assert_eq!(parse_command("rebalance").unwrap(), Command::Rebalance);
+        assert_eq!(
+            shell.execute(Command::Rebalance),
+            "✓ Scheduler rebalanced: removed 0 phantom entries, re-synced 0"
+        );
+        assert!(shell.scheduler.get_process_queue(1).is_some());
+        assert_eq!(shell.scheduler.get_process_queue(2), None);
+    }
+
+    fn schedule_after_reseed(mut shell: Shell, seed: u64) -> String {
+        for _ in 0..3 {
+            shell.execute(Command::Fork { ppid: 1 });
+        }
+        shell.execute(Command::RunProgram { program_name: "text_editor".to_string(), count: 1 });
+
+        assert_eq!(parse_command(&format!("seed {}", seed)).unwrap(), Command::Seed { value: seed });
+        assert!(shell.execute(Command::Seed { value: seed }).starts_with("✓ RNG reseeded with"));
+        assert_eq!(shell.seed(), Some(seed));
+        shell.execute(Command::Schedule { cycles: 20, no_boost: false })
+    }
+
+    #[test]
+    fn test_reseed_reproduces_schedule() {
+        let first = schedule_after_reseed(Shell::with_seed(1), 7);
+        let second = schedule_after_reseed(Shell::new(), 7);
+        assert_eq!(first, second);
+
+        let mut shell = Shell::with_seed(1);
+        assert_eq!(shell.execute(Command::Seed { value: 9 }), "✓ RNG reseeded with 9 (was 1)");
+    }
+
+    #[test]
+    fn test_live_turnaround_grows_with_clock() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+
+        shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+        let early = shell.stats.current_turnaround(2, shell.clock()).unwrap();
+        shell.execute(Command::Schedule { cycles: 4, no_boost: false });
+        let later = shell.stats.current_turnaround(2, shell.clock()).unwrap();
+
+        assert!(later > early);
+        assert!(shell.execute(Command::Stats).contains("In-Flight Processes (2 at"));
+    }
+
+    #[test]
+    fn test_warmup_skips_leading_cycles() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        assert_eq!(parse_command("warmup 3").unwrap(), Command::Warmup { cycles: 3 });
+        shell.execute(Command::Warmup { cycles: 3 });
+
+        shell.execute(Command::Schedule { cycles: 5, no_boost: false });
+        assert_eq!(shell.stats.total_context_switches, 2);
+        assert_eq!(shell.stats.total_ticks, 2);
+        assert_eq!(shell.stats.execution_log.len(), 5);
+    }
+
+    #[test]
+    fn test_sparkline_command() {
+        let mut shell = Shell::new();
+        assert_eq!(parse_command("sparkline 2").unwrap(), Command::Sparkline { queue: Some(2) });
+        assert!(shell.execute(Command::Sparkline { queue: None }).starts_with("No queue-depth samples"));
+
+        shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+        let output = shell.execute(Command::Sparkline { queue: None });
+        assert!(output.starts_with("Queue Depth Trend (3 samples)"));
+        assert_eq!(output.lines().filter(|line| line.starts_with('Q')).count(), 5);
+        assert!(shell.execute(Command::Sparkline { queue: Some(4) }).starts_with("Error:"));
+    }
+
+    #[test]
+    fn test_run_and_nice_flag_process_missing_from_scheduler() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        assert!(!shell.execute(Command::Nice { pid: 2, priority: 1 }).contains("⚠"));
+
+        shell.scheduler.remove_process(2);
+        let output = shell.execute(Command::Nice { pid: 2, priority: 2 });
+        assert!(output.contains("Process 2 is not in any scheduler queue"));
+
+        // Blocked processes are expected to be out of the queues
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Block { pid: 3 });
+        assert!(!shell.execute(Command::Nice { pid: 3, priority: 0 }).contains("⚠"));
+    }
+
+    #[test]
+    fn test_run_reenrolls_process_missing_from_scheduler() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Nice { pid: 2, priority: 1 });
+        shell.scheduler.remove_process(2);
+        assert!(!shell.scheduler.contains(2));
+
+        let output = shell.execute(Command::Run { pid: 2 });
+        assert_eq!(output, "✓ Process 2 is now running\n  • Re-enrolled in scheduler at Q1");
+        assert!(shell.scheduler.contains(2));
+
+        shell.scheduler.remove_process(1);
+        assert!(shell.execute(Command::Schedule { cycles: 1, no_boost: false }).contains("PID 2 ran"));
+
+        // Already enrolled: nothing to add
+        assert_eq!(shell.execute(Command::Run { pid: 2 }), "✓ Process 2 is now running");
+    }
+
+    #[test]
+    fn test_manual_demote_and_promote() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Nice { pid: 2, priority: 0 });
+        shell.scheduler.move_to_queue(2, 1);
+        // A manual move ignores the allotment it would otherwise have to use up
+        shell.scheduler.set_level_allotment(1, 64);
+
+        assert_eq!(parse_command("demote 2").unwrap(), Command::Demote { pid: 2 });
+        assert_eq!(parse_command("promote 2").unwrap(), Command::Promote { pid: 2 });
+
+        assert_eq!(shell.execute(Command::Demote { pid: 2 }), "✓ Process 2 demoted: Q1 → Q2");
+        assert_eq!(shell.execute(Command::Promote { pid: 2 }), "✓ Process 2 promoted: Q2 → Q1");
+        assert_eq!(shell.execute(Command::Promote { pid: 2 }), "✓ Process 2 promoted: Q1 → Q0");
+        assert_eq!(
+            shell.execute(Command::Promote { pid: 2 }),
+            "✓ Process 2 stays in Q0 (already the highest queue)"
+        );
+
+        shell.scheduler.move_to_queue(2, 3);
+        assert_eq!(
+            shell.execute(Command::Demote { pid: 2 }),
+            "✓ Process 2 stays in Q3 (already the lowest queue)"
+        );
+
+        let metrics = shell.stats.get_process_metrics(2).unwrap();
+        assert_eq!((metrics.demotions, metrics.promotions), (1, 3)); // one promotion came from nice
+
+        shell.execute(Command::Block { pid: 2 });
+        assert!(shell.execute(Command::Demote { pid: 2 }).starts_with("Error: Process 2 (blocked)"));
+    }
+
+    #[test]
+    fn test_metrics_table_matches_recorded_metrics() {
+        let mut shell = Shell::with_seed(8);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Schedule { cycles: 6, no_boost: false });
+        shell.execute(Command::Kill { pid: 3, force: true });
+
+        let rows = shell.metrics_table();
+        assert_eq!(rows.iter().map(|row| row.pid).collect::<Vec<_>>(), vec![1, 2, 3]);
+        for row in &rows {
+            let recorded = shell.stats.get_process_metrics(row.pid).unwrap();
+            assert_eq!(row.execution_time, recorded.execution_time);
+            assert_eq!(row.context_switches, recorded.context_switches);
+            assert_eq!(row.queue_changes, recorded.queue_changes);
+            assert_eq!(row.turnaround_time, recorded.turnaround_time);
+        }
+        assert_eq!(rows[0].name, "init");
+        assert!(rows[2].terminated && !rows[1].terminated);
+        assert_eq!(rows.iter().map(|row| row.execution_time).sum::<u64>(), shell.stats.total_execution_time);
+    }
+
+    #[test]
+    fn test_schedule_lines_carry_clock() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+
+        let output = shell.execute(Command::Schedule { cycles: 5, no_boost: false });
+        let mut expected = 0;
+        for line in output.lines().filter(|line| line.starts_with("@t=")) {
+            let (stamp, rest) = line.split_once(' ').unwrap();
+            assert_eq!(stamp, format!("@t={}", expected));
+
+            // "Cycle N: PID P ran X/Qms in Qn (outcome)"
+            let ran: u64 = rest.split("ran ").nth(1).unwrap().split('/').next().unwrap().parse().unwrap();
+            expected += ran;
+        }
+        assert_eq!(expected, shell.clock());
+        assert!(expected > 0);
+    }
+
+    #[test]
+    fn test_schedule_reports_partial_quantum_on_yield() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+        let mut program = crate::scheduler::Program::new(
+            "editor", crate::scheduler::ProgramType::Interactive, "Types a little", 0.25,
+        );
+        program.set_behavior_script(vec![false, true]);
+        shell.process_programs.insert(2, program);
+
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 0);
+
+        let output = shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+        assert!(output.contains("@t=0 Cycle 1: PID 2 ran 2/8ms in Q0 (yielded)"), "{}", output);
+        assert!(output.contains("@t=2 Cycle 2: PID 2 ran 8/8ms in Q0 (full)"), "{}", output);
+    }
+
+    #[test]
+    fn test_frozen_scheduler_rejects_dispatch_and_keeps_state() {
+        let mut shell = Shell::with_seed(2007);
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Schedule { cycles: 5, no_boost: false });
+        let state = |shell: &Shell| {
+            format!("{:?}{:?}{:?}{}", shell.scheduler, shell.manager, shell.stats, shell.clock)
+        };
+        let before = state(&shell);
+
+        assert_eq!(parse_command("freeze").unwrap(), Command::Freeze);
+        assert!(shell.execute(Command::Freeze).starts_with("✓ Scheduler frozen at t="));
+        assert!(shell.execute(Command::Freeze).starts_with("Error"));
+        assert!(shell.execute(Command::Schedule { cycles: 3, no_boost: false }).starts_with("Error: Scheduler is frozen"));
+        assert!(shell.execute(Command::WaitAll { max_cycles: None }).starts_with("Error: Scheduler is frozen"));
+
+        // A subshell copies the frozen moment exactly
+        shell.execute(Command::Subshell);
+        assert_eq!(state(&shell), before);
+        assert!(shell.execute(Command::Schedule { cycles: 1, no_boost: false }).starts_with("Error"));
+        shell.execute(Command::ExitSubshell { merge: false });
+
+        assert_eq!(parse_command("thaw").unwrap(), Command::Thaw);
+        assert!(shell.execute(Command::Thaw).starts_with("✓ Scheduler thawed"));
+        assert!(shell.execute(Command::Thaw).starts_with("Error"));
+        assert_eq!(state(&shell), before);
+        assert!(shell.execute(Command::Schedule { cycles: 1, no_boost: false }).contains("Cycle 1:"));
+    }
+
+    #[test]
+    fn test_verbosity_scales_schedule_output() {
+        let run = |level: u8| {
+            let mut shell = Shell::with_seed(2008);
+            shell.execute(Command::Fork { ppid: 1 });
+            assert_eq!(parse_command(&format!("verbose {}", level)).unwrap(), Command::Verbose { level });
+            assert!(shell.execute(Command::Verbose { level }).starts_with("✓"));
+            shell.execute(Command::Schedule { cycles: 6, no_boost: false })
+        };
+        let cycle_lines = |output: &str| output.lines().filter(|l| l.starts_with("@t=")).count();
+
+        assert_eq!(run(0), "");
+        let mut quiet = Shell::with_seed(2008);
+        quiet.execute(Command::Verbose { level: 0 });
+        quiet.cancel_handle().store(true, Ordering::Relaxed);
+        assert_eq!(
+            quiet.execute(Command::Schedule { cycles: SCHEDULE_MAX_CYCLES + 1, no_boost: false }),
+            format!(
+                "Warning: {} cycles requested; capped at {}\nInterrupted after 0 cycles\n",
+                SCHEDULE_MAX_CYCLES + 1,
+                SCHEDULE_MAX_CYCLES
+            )
+        );
+
+        let summary = run(1);
+        assert_eq!(cycle_lines(&summary), 0);
+        assert!(summary.contains("Ran 6 cycles in "), "{}", summary);
+        assert!(summary.contains("ms idle, idle=") && summary.ends_with("ovh=0.0%\n"), "{}", summary);
+        assert_eq!(cycle_lines(&run(2)), 6);
+        assert_eq!(run(3).matches("Queue depths:").count(), 6);
+
+        assert!(Shell::new().execute(Command::Verbose { level: 4 }).starts_with("Error"));
+    }
+
+    #[test]
+    fn test_cgroup_caps_combined_cpu() {
+        let mut shell = Shell::with_seed(2010);
+        shell.scheduler.remove_process(1);
+        for _ in 0..3 {
+            shell.execute(Command::Fork { ppid: 1 });
+        }
+
+        assert_eq!(
+            parse_command("cgroup_create batch 100").unwrap(),
+            Command::CgroupCreate { name: "batch".to_string(), cpu_limit: 100 }
+        );
+        assert!(shell.execute(Command::CgroupCreate { name: "batch".to_string(), cpu_limit: 100 }).starts_with("✓"));
+        assert_eq!(
+            parse_command("cgroup_add batch 2").unwrap(),
+            Command::CgroupAdd { name: "batch".to_string(), pid: 2 }
+        );
+        for pid in [2, 3] {
+            assert!(shell.execute(Command::CgroupAdd { name: "batch".to_string(), pid }).starts_with("✓"));
+        }
+        assert!(shell.execute(Command::CgroupAdd { name: "nope".to_string(), pid: 4 }).starts_with("Error"));
+
+        shell.execute(Command::Schedule { cycles: 60, no_boost: false });
+
+        let used = |shell: &Shell, pid: u32| shell.manager.get_process(pid).unwrap().total_time;
+        assert_eq!(used(&shell, 2) + used(&shell, 3), 100);
+        assert!(used(&shell, 4) > 100);
+        // Running into the cap is not an early yield
+        let metrics = |pid: u32| shell.stats.get_process_metrics(pid).unwrap().clone();
+        assert_eq!(metrics(2).promotions, 0);
+        assert_eq!(metrics(3).promotions, 0);
+        // Once throttled, neither member is dispatched again
+        let before = (used(&shell, 2), used(&shell, 3));
+        shell.execute(Command::Schedule { cycles: 10, no_boost: false });
+        assert_eq!((used(&shell, 2), used(&shell, 3)), before);
+
+        assert_eq!(parse_command("cgroup_stats batch").unwrap(), Command::CgroupStats { name: "batch".to_string() });
+        let stats = shell.execute(Command::CgroupStats { name: "batch".to_string() });
+        assert!(stats.starts_with("Cgroup 'batch': 100/100ms CPU used (throttled)"), "{}", stats);
+        assert!(stats.contains("Total: 2 members, 100ms CPU"), "{}", stats);
+    }
+
+    #[test]
+    fn test_terminated_pid_left_in_queue_is_removed() {
+        let mut shell = Shell::with_seed(2012);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+
+        // Terminate behind the scheduler's back
+        shell.manager.terminate_process(2);
+        assert!(shell.scheduler.get_process_queue(2).is_some());
+
+        let output = shell.execute(Command::Schedule { cycles: 4, no_boost: false });
+        assert!(output.contains("Removed terminated PID 2 lingering in Q"), "{}", output);
+        assert!(!output.contains("PID 2 ran"), "{}", output);
+        assert_eq!(shell.scheduler.get_process_queue(2), None);
+        assert!(shell.scheduler.snapshot_queues().iter().all(|q| !q.contains(&2)));
+    }
+
+    #[test]
+    fn test_audio_program_preempts_cpu_bound_work() {
+        let mut shell = Shell::with_seed(2013);
+        shell.scheduler.remove_process(1);
+        shell.execute_line("run_program compiler 2"); // PIDs 2-3
+        shell.execute_line("run_program audio"); // PID 4
+        assert_eq!(shell.manager.get_process(4).unwrap().sched_class, SchedClass::RealTime);
+
+        let output = shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        assert!(output.contains("Cycle 1: PID 4 ran"), "{}", output);
+    }
+
+    #[test]
+    fn test_schedule_fine_spreads_quantum_over_slices() {
+        assert_eq!(parse_command("schedule_fine 20").unwrap(), Command::ScheduleFine { ms: 20 });
+
+        let mut shell = Shell::with_seed(2015);
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 0);
+        assert_eq!(shell.scheduler.quantum(0), Some(8));
+
+        let output = shell.execute(Command::ScheduleFine { ms: 20 });
+        assert_eq!(shell.clock(), 20, "{}", output);
+
+        let slices = &shell.stats.execution_log;
+        assert!(slices.iter().all(|s| s.pid == 2 && s.duration <= FINE_SLICE_MS));
+        let dispatches = output.lines().filter(|l| l.starts_with("@t=")).count();
+        assert!(slices.len() > dispatches, "{} slices over {} dispatches", slices.len(), dispatches);
+        assert_eq!(shell.manager.get_process(2).unwrap().total_time, 20);
+    }
+
+    #[test]
+    fn test_schedule_fine_preempts_for_higher_queue() {
+        let mut shell = Shell::with_seed(2015);
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 3);
+        shell.scheduler.remove_process(3);
+        shell.scheduler.add_process_to_queue(3, 0);
+        shell.execute(Command::Sleep { pid: 3, ms: 3 });
+
+        let output = shell.execute(Command::ScheduleFine { ms: 10 });
+        assert!(output.contains("PID 2 ran 4/"), "{}", output);
+        assert!(output.contains("(preempted)"), "{}", output);
+        assert!(output.contains("@t=4 Cycle 2: PID 3 ran"), "{}", output);
+    }
+
+    #[test]
+    fn test_schedule_no_boost_starves_q3_for_one_run() {
+        assert_eq!(
+            parse_command("schedule 200 --no-boost").unwrap(),
+            Command::Schedule { cycles: 200, no_boost: true }
+        );
+        assert!(parse_command("schedule 200 --boost").is_none());
+
+        let starving_run = |line: &str| {
+            let mut shell = Shell::with_seed(2016);
+            shell.scheduler.remove_process(1);
+            for _ in 0..3 {
+                shell.execute(Command::Fork { ppid: 1 });
+            }
+            // PID 2 sits in Q3 behind two processes that never leave Q0
+            shell.scheduler.remove_process(2);
+            shell.scheduler.add_process_to_queue(2, 3);
+            for pid in [3, 4] {
+                let program = crate::scheduler::Program::new(
+                    "poller", crate::scheduler::ProgramType::IoBound, "Always waits on I/O", 0.0,
+                );
+                shell.process_programs.insert(pid, program);
+                shell.scheduler.remove_process(pid);
+                shell.scheduler.add_process_to_queue(pid, 0);
+            }
+            shell.execute_line(line);
+            shell
+        };
+
+        let starved = starving_run("schedule 200 --no-boost");
+        assert_eq!(starved.manager.get_process(2).unwrap().total_time, 0);
+        assert_eq!(starved.scheduler.boost_interval(), 100);
+
+        let boosted = starving_run("schedule 200");
+        assert!(boosted.manager.get_process(2).unwrap().total_time > 0);
+    }
+
+    #[test]
+    fn test_io_bound_yielder_switches_voluntarily() {
+        let mut shell = Shell::with_seed(2018);
+        shell.scheduler.remove_process(1);
+        shell.execute_line("run_program web_browser"); // PID 2
+        shell.execute(Command::Schedule { cycles: 40, no_boost: false });
+
+        let browser = shell.stats.get_process_metrics(2).unwrap().clone();
+        assert_eq!(browser.voluntary_switches + browser.involuntary_switches, browser.context_switches);
+        assert!(browser.voluntary_switches > 2 * browser.involuntary_switches, "{:?}", browser);
+
+        let report = shell.execute(Command::Metrics { pid: 2 });
+        assert!(report.contains(&format!(
+            "Context Switches:    {} ({} voluntary, {} involuntary)",
+            browser.context_switches, browser.voluntary_switches, browser.involuntary_switches
+        )), "{}", report);
     }
 
-    fn cmd_run_program(&mut self, program_name: &str) -> String {
-        let registry = crate::scheduler::programs::ProgramRegistry::new();
+    #[test]
+    fn test_quantum_expired_matches_demotion() {
+        let mut shell = Shell::with_seed(2006);
+        shell.scheduler.remove_process(1);
+        for _ in 0..4 {
+            shell.execute(Command::Fork { ppid: 1 });
+        }
 
-        match registry.get_program(program_name) {
-            Some(program) => {
-                let pid = self.manager.create_process(1);
-                self.scheduler.add_process(pid);
-                self.stats.record_process_created(pid);
+        shell.scheduler.set_boost_interval(0);
 
-                format!(
-                    "✓ Program '{}' started as PID {}\n\
-                     Description: {}\n\
-                     Behavior: {}\n\
-                     Expected Priority: Q{}",
-                    program.name,
-                    pid,
-                    program.description,
-                    program.behavior_description(),
-                    program.expected_priority
-                )
-            }
-            None => {
-                format!("Error: Program '{}' not found. Type 'programs' to see available programs.", program_name)
+        for _ in 0..40 {
+            let output = shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+            let slice = *shell.stats.execution_log.last().unwrap();
+            let (pid, before) = (slice.pid, slice.queue);
+            let after = shell.scheduler.get_process_queue(pid).unwrap();
+
+            let process = shell.manager.get_process(pid).unwrap();
+            assert_eq!(Some(process.time_allocated), shell.scheduler.quantum(before));
+            if process.quantum_expired() {
+                assert!(output.contains("(full)") && after == (before + 1).min(3), "{}", output);
+            } else {
+                assert!(output.contains("(yielded)") && after == before.saturating_sub(1), "{}", output);
             }
         }
     }
 
-    // ========================================================================
-    // STATISTICS COMMANDS
-    // ========================================================================
+    #[test]
+    fn test_replay_history_reproduces_session() {
+        let mut shell = Shell::with_seed(21);
+        for line in ["fork", "fork 2", "fork 1", "schedule 6", "kill 3 --now", "bogus", "nice 4 0", "schedule 4"] {
+            shell.execute_line(line);
+        }
+        assert_eq!(shell.history().len(), 7);
+        assert_eq!(shell.history()[4], "kill 3 --now");
 
-    fn cmd_stats(&self) -> String {
-        self.stats.summary_report()
+        let mut replayed = shell.replay_history();
+        assert_eq!(replayed.history(), shell.history());
+        assert_eq!(replayed.clock(), shell.clock());
+        assert_eq!(replayed.execute(Command::Ps), shell.execute(Command::Ps));
     }
 
-    fn cmd_metrics(&self, pid: u32) -> String {
-        match self.stats.get_process_metrics(pid) {
-            Some(metrics) => {
-                format!(
-                    "Process Metrics (PID: {})\n\
-                     ════════════════════════════════════════════════════════════\n\
-                     Turnaround Time:     {}ms\n\
-                     Response Time:       {}ms\n\
-                     Waiting Time:        {}ms\n\
-                     Execution Time:      {}ms\n\
-                     Context Switches:    {}\n\
-                     Queue Changes:       {}\n",
-                    metrics.pid,
-                    metrics.turnaround_time,
-                    metrics.response_time,
-                    metrics.waiting_time,
-                    metrics.execution_time,
-                    metrics.context_switches,
-                    metrics.queue_changes,
-                )
-            }
-            None => format!("Error: No metrics found for process {}", pid),
-        }
-    }
+    #[test]
+    fn test_schedule_zero_and_capped_counts() {
+        let mut shell = Shell::new();
+        assert_eq!(shell.execute(Command::Schedule { cycles: 0, no_boost: false }), "No cycles requested");
+        assert_eq!(shell.clock(), 0);
 
-    fn cmd_reset_stats(&mut self) -> String {
-        self.stats.reset();
-        "✓ All statistics have been reset".to_string()
+        // Cancel up front so the clamped run stops before doing any work
+        shell.cancel_handle().store(true, Ordering::Relaxed);
+        let output = shell.execute(Command::Schedule { cycles: 4_000_000_000, no_boost: false });
+        assert!(output.starts_with(&format!(
+            "Warning: 4000000000 cycles requested; capped at {}\nSimulating {} scheduling cycles:",
+            SCHEDULE_MAX_CYCLES, SCHEDULE_MAX_CYCLES
+        )));
+        assert!(output.ends_with("Interrupted after 0 cycles\n"));
+        assert!(!shell.cancel_handle().load(Ordering::Relaxed));
+
+        shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+        assert!(shell.clock() > 0);
     }
 
-    // ========================================================================
-    // SYSTEM COMMANDS
-    // ========================================================================
+    #[test]
+    fn test_dispatch_skips_blocked_process_left_in_queue() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
 
-    fn cmd_help(&self) -> String {
-        String::from(
-            "Available Commands:\n\
-             ────────────────────────────────────────────────────\n\
-             Process Management:\n\
-               fork [ppid]          - Create new process\n\
-               ps                   - List all processes\n\
-               kill <pid>           - Terminate process\n\
-               run <pid>            - Transition to running\n\
-             \n\
-             Process State:\n\
-               block <pid>          - Block process (I/O)\n\
-               unblock <pid>        - Unblock process\n\
-               info <pid>           - Process information\n\
-             \n\
-             Scheduler Control:\n\
-               nice <pid> <prio>    - Change priority (0-3)\n\
-               schedule <cycles>    - Simulate N cycles\n\
-               queues               - Show queue state\n\
-               sched_stats          - Detailed statistics\n\
-             \n\
-             Programs:\n\
-               programs             - List available programs\n\
-               run_program <n>      - Execute a program\n\
-             \n\
-             Statistics:\n\
-               stats                - Show metrics\n\
-               metrics <pid>        - Process metrics\n\
-               reset_stats          - Clear statistics\n\
-             \n\
-             System:\n\
-               help                 - Show this help\n\
-               exit                 - Exit simulator\n"
-        )
+        // Blocked behind the scheduler's back: still queued
+        shell.manager.get_process_mut(2).unwrap().set_state(ProcessState::Blocked);
+        assert!(!shell.manager.get_process(2).unwrap().is_runnable());
+        assert!(shell.scheduler.contains(2));
+
+        let output = shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+        assert!(!output.contains("PID 2 ran"));
+        assert!(output.contains("PID 3 ran"));
+        assert!(shell.scheduler.contains(2));
     }
 
-    // ========================================================================
-    // UTILITY METHODS
-    // ========================================================================
+    #[test]
+    fn test_blocked_process_never_dispatched_until_unblocked() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Nice { pid: 2, priority: 0 });
+        shell.execute(Command::Promote { pid: 2 });
+        shell.execute(Command::Promote { pid: 2 });
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(0));
 
-    pub fn is_running(&self) -> bool {
-        self.running
-    }
+        shell.execute(Command::Block { pid: 2 });
+        assert!(!shell.scheduler.contains(2));
+        for _ in 0..10 {
+            let (pid, _) = shell.scheduler.next_process().unwrap();
+            assert_ne!(pid, 2);
+            shell.scheduler.process_used_full_quantum(pid);
+        }
 
-    pub fn process_count(&self) -> usize {
-        self.manager.process_count()
+        shell.execute(Command::Unblock { pid: 2 });
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(0));
+        assert_eq!(shell.scheduler.queue_lengths().iter().sum::<usize>(), 2);
+        assert_eq!(shell.scheduler.next_process().unwrap().0, 2);
     }
-}
 
-impl Default for Shell {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+    #[test]
+    fn test_long_blocked_process_wakes_at_front_of_q0() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2, sits in Q3
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+        shell.execute(Command::Fork { ppid: 1 }); // PID 4
+        shell.scheduler.move_to_queue(3, 0);
+        shell.scheduler.move_to_queue(4, 0);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        shell.execute(Command::Block { pid: 2 });
+        shell.clock += INTERACTIVE_CREDIT_MS;
+        let output = shell.execute(Command::Unblock { pid: 2 });
+        assert!(output.contains("interactive credit"), "{}", output);
+        assert_eq!(shell.scheduler.snapshot_queues()[0], vec![2, 3, 4]);
+        assert_eq!(shell.stats.get_process_metrics(2).unwrap().boosts, 1);
 
-    #[test]
-    fn test_parse_fork() {
-        let cmd = parse_command("fork 1").unwrap();
-        assert_eq!(cmd, Command::Fork { ppid: 1 });
+        // A short wait only earns the usual one-level promotion
+        shell.scheduler.move_to_queue(2, 3);
+        shell.execute(Command::Block { pid: 2 });
+        shell.clock += INTERACTIVE_CREDIT_MS - 1;
+        shell.execute(Command::Unblock { pid: 2 });
+        assert_eq!(shell.scheduler.get_process_queue(2), Some(2));
     }
 
     #[test]
-    fn test_parse_ps() {
-        let cmd = parse_command("ps").unwrap();
-        assert_eq!(cmd, Command::Ps);
+    fn test_stat_one_liner() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        assert_eq!(parse_command("stat 2").unwrap(), Command::Stat { pid: 2 });
+
+        let line = shell.execute(Command::Stat { pid: 2 });
+        assert_eq!(line.lines().count(), 1);
+        let queue = shell.scheduler.get_process_queue(2).unwrap();
+        assert!(line.starts_with(&format!("2 1 ready Q{} pc=0x0 cpu=0ms wait=", queue)), "{}", line);
+
+        shell.execute(Command::Block { pid: 2 });
+        assert!(shell.execute(Command::Stat { pid: 2 }).starts_with("2 1 blocked Q- "));
+        assert!(shell.execute(Command::Stat { pid: 99 }).starts_with("Error"));
     }
 
     #[test]
-    fn test_parse_run() {
-        let cmd = parse_command("run 2").unwrap();
-        assert_eq!(cmd, Command::Run { pid: 2 });
+    fn test_heatmap_for_process_stuck_in_q3() {
+        let mut shell = Shell::new();
+        assert_eq!(parse_command("heatmap").unwrap(), Command::Heatmap);
+        assert!(shell.execute(Command::Heatmap).starts_with("No execution recorded"));
+
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.scheduler.remove_process(1);
+        shell.scheduler.remove_process(2);
+        shell.scheduler.add_process_to_queue(2, 3);
+        let program = crate::scheduler::Program::new(
+            "cruncher", crate::scheduler::ProgramType::CpuBound, "Never yields", 1.0,
+        );
+        shell.process_programs.insert(2, program);
+        shell.execute(Command::Schedule { cycles: 3, no_boost: false });
+
+        let output = shell.execute(Command::Heatmap);
+        let row: Vec<&str> = output.lines().find(|line| line.starts_with("2 ")).unwrap().split_whitespace().collect();
+        assert_eq!(row, vec!["2", "0", "0", "0", "███", "192"]);
     }
 
     #[test]
-    fn test_parse_kill() {
-        let cmd = parse_command("kill 2").unwrap();
-        assert_eq!(cmd, Command::Kill { pid: 2 });
+    fn test_state_time_accounting_sums_to_elapsed() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 1 }); // PID 3
+
+        shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+        shell.execute(Command::Block { pid: 2 });
+        shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+        shell.execute(Command::Unblock { pid: 2 });
+        shell.execute(Command::Schedule { cycles: 2, no_boost: false });
+
+        let metrics = shell.stats.get_process_metrics(2).unwrap();
+        assert!(metrics.blocked_time > 0);
+        assert!(metrics.running_time > 0);
+        assert_eq!(metrics.running_time, metrics.execution_time);
+        assert_eq!(metrics.ready_time + metrics.running_time + metrics.blocked_time, shell.clock());
+
+        assert!(shell.execute(Command::Metrics { pid: 2 }).contains("Time Blocked:"));
+        assert!(shell.execute(Command::Info { pid: 2 }).contains("Time in States:       ready"));
     }
 
     #[test]
-    fn test_parse_schedule() {
-        let cmd = parse_command("schedule 5").unwrap();
-        assert_eq!(cmd, Command::Schedule { cycles: 5 });
+    fn test_from_config_two_levels_and_seed() {
+        let config = ShellConfig::from_toml(
+            "seed = 11\n\
+             process_limit = 3\n\
+             [scheduler]\n\
+             quantums = [10, 40]\n"
+        )
+        .unwrap();
+        let mut shell = Shell::from_config(&config).unwrap();
+
+        assert_eq!(shell.seed(), Some(11));
+        assert_eq!(shell.scheduler.levels(), 2);
+        assert_eq!(shell.scheduler.get_process_queue(1), Some(1));
+
+        let queues = shell.execute(Command::Queues);
+        assert!(queues.contains("Q1 (40ms):  [1]"));
+        assert!(!queues.contains("Q2"));
+
+        shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 });
+        assert_eq!(shell.execute(Command::Fork { ppid: 1 }), "Error: Process limit of 3 reached");
+
+        let bad = ShellConfig { scheduler: SchedulerConfig { quantums: vec![], boost_interval: 0, ..Default::default() }, ..Default::default() };
+        assert!(Shell::from_config(&bad).is_err());
     }
 
     #[test]
-    fn test_parse_nice() {
-        let cmd = parse_command("nice 2 0").unwrap();
-        assert_eq!(cmd, Command::Nice { pid: 2, priority: 0 });
+    fn test_memory_budget_and_colorize() {
+        let config = ShellConfig { memory_budget: Some(0x6000), colorize: true, ..Default::default() };
+        let mut shell = Shell::from_config(&config).unwrap();
+
+        // init already reserves 0x3000, leaving room for exactly one more
+        assert_eq!(shell.execute_line("fork 1"), "\x1b[32m✓ Process created: PID 2 (parent: 1)\x1b[0m");
+        assert!(shell.execute_line("fork 1").starts_with("\x1b[31mError: Memory budget exhausted"));
     }
 
     #[test]
-    fn test_parse_sched_stats() {
-        let cmd = parse_command("sched_stats").unwrap();
-        assert_eq!(cmd, Command::SchedStats);
+    fn test_at_fires_when_clock_crosses_tick() {
+        let config = ShellConfig {
+            scheduler: SchedulerConfig { quantums: vec![20], boost_interval: 0, ..Default::default() },
+            ..Default::default()
+        };
+        let mut shell = Shell::from_config(&config).unwrap();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+
+        assert_eq!(
+            parse_command("at 50 kill 2 --now").unwrap(),
+            Command::At { tick: 50, line: "kill 2 --now".to_string() }
+        );
+        assert!(parse_command("at 50 bogus").is_none());
+        shell.execute_line("at 50 kill 2 --now");
+        assert!(shell.execute(Command::AtList).contains("50ms  kill 2 --now"));
+
+        // Early yields make slices shorter than 20ms, so step one cycle at a time
+        let mut output = String::new();
+        while shell.clock < 50 {
+            assert_eq!(shell.manager.get_process(2).unwrap().state, ProcessState::Ready);
+            output = shell.execute(Command::Schedule { cycles: 1, no_boost: false });
+        }
+        assert!(output.contains(&format!("@50ms (clock {}ms): kill 2 --now", shell.clock)));
+        assert_eq!(shell.manager.get_process(2).unwrap().state, ProcessState::Terminated);
+        assert_eq!(shell.execute(Command::AtList), "No timed commands pending");
     }
 
     #[test]
-    fn test_parse_programs() {
-        let cmd = parse_command("programs").unwrap();
-        assert_eq!(cmd, Command::Programs);
+    fn test_info_shows_quantum_history() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        assert!(shell.execute(Command::Info { pid: 2 }).contains("Quantum History:      none"));
+
+        shell.scheduler.process_used_full_quantum(2);
+        shell.scheduler.process_yielded_early(2);
+        assert!(shell.execute(Command::Info { pid: 2 }).contains("Quantum History:      FY (F=full, Y=yielded)"));
     }
 
     #[test]
-    fn test_parse_run_program() {
-        let cmd = parse_command("run_program video_encoder").unwrap();
-        assert_eq!(cmd, Command::RunProgram { program_name: "video_encoder".to_string() });
+    fn test_info_shows_quantum_left_for_running_process() {
+        let mut shell = Shell::new();
+        shell.scheduler.remove_process(1);
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2, Q3
+        assert!(shell.execute(Command::Info { pid: 2 }).contains("Quantum Left:         N/A (not running)"));
+
+        assert_eq!(shell.scheduler.next_process(), Some((2, 64)));
+        shell.scheduler.tick(24);
+        assert!(shell.execute(Command::Info { pid: 2 }).contains("Quantum Left:         40ms"));
     }
 
     #[test]
-    fn test_parse_stats() {
-        let cmd = parse_command("stats").unwrap();
-        assert_eq!(cmd, Command::Stats);
+    fn test_info_lists_children_and_parent_program() {
+        let mut shell = Shell::new();
+        shell.execute(Command::RunProgram { program_name: "terminal".to_string(), count: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 2 }); // PID 3
+        shell.execute(Command::Fork { ppid: 2 }); // PID 4
+
+        let parent = shell.execute(Command::Info { pid: 2 });
+        assert!(parent.contains("Children:             3, 4"));
+        assert!(parent.contains("Parent Program:       none"));
+
+        let child = shell.execute(Command::Info { pid: 3 });
+        assert!(child.contains("Children:             none"));
+        assert!(child.contains("Parent Program:       terminal"));
     }
 
     #[test]
-    fn test_parse_metrics() {
-        let cmd = parse_command("metrics 2").unwrap();
-        assert_eq!(cmd, Command::Metrics { pid: 2 });
+    fn test_kill_recursive_takes_whole_subtree() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2: subtree root
+        shell.execute(Command::Fork { ppid: 2 }); // PID 3
+        shell.execute(Command::Fork { ppid: 3 }); // PID 4
+        shell.execute(Command::Fork { ppid: 1 }); // PID 5: unrelated
+
+        assert_eq!(parse_command("kill -r 2").unwrap(), Command::KillTree { pid: 2 });
+        let output = shell.execute(Command::KillTree { pid: 2 });
+        assert_eq!(output, "✓ Terminated 3 processes (SIGKILL): 2, 3, 4");
+
+        for pid in [2, 3, 4] {
+            assert_eq!(shell.manager.get_process(pid).unwrap().state, ProcessState::Terminated);
+            assert_eq!(shell.scheduler.get_process_queue(pid), None);
+        }
+        assert_eq!(shell.manager.get_process(5).unwrap().state, ProcessState::Ready);
+        assert_eq!(shell.manager.get_process(1).unwrap().state, ProcessState::Ready);
+        assert!(shell.execute(Command::KillTree { pid: 1 }).starts_with("Error: Cannot kill init"));
     }
 
     #[test]
-    fn test_shell_creation() {
-        let shell = Shell::new();
-        assert!(shell.is_running());
-        assert_eq!(shell.process_count(), 1);
+    fn test_plain_kill_reparents_children_to_init() {
+        let mut shell = Shell::new();
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 2 }); // PID 3
+
+        shell.execute(Command::Kill { pid: 2, force: true });
+        let child = shell.manager.get_process(3).unwrap();
+        assert_eq!(child.state, ProcessState::Ready);
+        assert_eq!(child.ppid, 1);
+
+        assert_eq!(parse_command("find_orphans").unwrap(), Command::FindOrphans);
+        assert_eq!(shell.execute(Command::FindOrphans), "✓ No orphaned processes");
     }
 
     #[test]
-    fn test_shell_fork_process() {
+    fn test_find_orphans_reports_unreparented_child() {
         let mut shell = Shell::new();
-        let result = shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Fork { ppid: 1 }); // PID 2
+        shell.execute(Command::Fork { ppid: 2 }); // PID 3
 
-        assert!(result.contains("✓"));
-        assert_eq!(shell.process_count(), 2);
+        // Terminate the parent behind the shell's back, skipping reparenting
+        shell.manager.terminate_process(2);
+        let report = shell.execute(Command::FindOrphans);
+        assert!(report.starts_with("Found 1 orphaned process(es):"), "{}", report);
+        assert!(report.contains("PID 3 (init-child-child) -> parent 2 (terminated)"), "{}", report);
+    }
+
+    struct NoSleep;
+
+    impl Sleeper for NoSleep {
+        fn sleep(&mut self, _duration: std::time::Duration) {}
+    }
+
+    /// Records each delay instead of sleeping
+    struct RecordingSleeper {
+        slept: std::rc::Rc<std::cell::RefCell<Vec<std::time::Duration>>>,
+    }
+
+    impl Sleeper for RecordingSleeper {
+        fn sleep(&mut self, duration: std::time::Duration) {
+            self.slept.borrow_mut().push(duration);
+        }
+    }
+
+    /// Records what a live view would have drawn
+    struct RecordingScreen {
+        log: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    }
+
+    impl Screen for RecordingScreen {
+        fn clear(&mut self) {
+            self.log.borrow_mut().push("<clear>".to_string());
+        }
+
+        fn draw(&mut self, frame: &str) {
+            self.log.borrow_mut().push(frame.to_string());
+        }
     }
 
     #[test]
-    fn test_shell_kill_process() {
-        let mut shell = Shell::new();
-        shell.execute(Command::Fork { ppid: 1 });
-        assert_eq!(shell.process_count(), 2);
+    fn test_sched_stats_watch_advances_and_redraws() {
+        assert_eq!(parse_command("sched_stats --watch 3"), Some(Command::SchedStatsWatch { frames: 3 }));
+        assert!(parse_command("sched_stats --watch 0").is_none());
+        assert!(parse_command("sched_stats --watch").is_none());
 
-        let result = shell.execute(Command::Kill { pid: 2 });
+        let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut shell = Shell::with_seed(2019);
+        shell.set_screen(Box::new(RecordingScreen { log: std::rc::Rc::clone(&log) }));
+        // Each step forks one process, so every frame shows a new total
+        shell.set_watch_step(|shell| {
+            shell.execute(Command::Fork { ppid: 1 });
+        });
 
-        assert!(result.contains("✓"));
+        let slept = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        shell.set_sleeper(Box::new(RecordingSleeper { slept: std::rc::Rc::clone(&slept) }));
 
-        let info = shell.execute(Command::Info { pid: 2 });
-        assert!(info.contains("Terminated"));
+        let output = shell.execute_line("sched_stats --watch 3");
+        assert_eq!(output, "✓ Watched 3 frames");
+        assert_eq!(shell.manager.process_count(), 4);
+        // Paced between frames, not before the first
+        assert_eq!(*slept.borrow(), vec![std::time::Duration::from_millis(WATCH_FRAME_INTERVAL_MS); 2]);
+
+        // Oversized requests are clamped; cancel first so nothing is drawn
+        shell.cancel_handle().store(true, Ordering::Relaxed);
+        assert_eq!(
+            shell.execute(Command::SchedStatsWatch { frames: WATCH_MAX_FRAMES + 1 }),
+            format!(
+                "Warning: {} frames requested; capped at {}\nInterrupted after 0 frames",
+                WATCH_MAX_FRAMES + 1,
+                WATCH_MAX_FRAMES
+            )
+        );
+        assert_eq!(log.borrow().len(), 6);
+
+        let log = log.borrow();
+        assert_eq!(log.len(), 6);
+        for (i, pair) in log.chunks(2).enumerate() {
+            assert_eq!(pair[0], "<clear>");
+            assert!(pair[1].contains("DETAILED SCHEDULER STATISTICS"));
+            assert!(pair[1].contains(&format!("Total Processes:          {}\n", i + 2)), "{}", pair[1]);
+        }
     }
 
     #[test]
-    fn test_shell_cannot_kill_init() {
+    fn test_replay_saved_trace() {
+        assert_eq!(
+            parse_command("replay trace.json --speed 2").unwrap(),
+            Command::Replay { path: "trace.json".to_string(), speed: 2.0 }
+        );
+        assert!(parse_command("replay trace.json --speed 0").is_none());
+
         let mut shell = Shell::new();
-        let result = shell.execute(Command::Kill { pid: 1 });
+        shell.execute(Command::Schedule { cycles: 3, no_boost: false });
 
-        assert!(result.contains("Error"));
+        let path = std::env::temp_dir().join(format!("os-sim-replay-{}.json", std::process::id()));
+        let json = serde_json::to_string(&shell.stats.execution_log).unwrap();
+        std::fs::write(&path, json).unwrap();
+
+        let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        shell.set_sleeper(Box::new(NoSleep));
+        shell.set_screen(Box::new(RecordingScreen { log: std::rc::Rc::clone(&log) }));
+        let output = shell.execute(Command::Replay {
+            path: path.to_string_lossy().to_string(),
+            speed: 4.0,
+        });
+        let piped = shell.execute_line(&format!("replay {} --speed 4 | grep PID", path.to_string_lossy()));
+        let too_slow = shell.execute_line(&format!("replay {} --speed 1e-300", path.to_string_lossy()));
+        std::fs::remove_file(&path).unwrap();
+
+        // Events stream to the screen as they fire; piped ones are captured
+        assert_eq!(output, "✓ Replayed 3 events at 4x speed");
+        assert_eq!(log.borrow().len(), 3);
+        assert!(log.borrow()[0].starts_with("[     0ms] PID 1 ran for"), "{:?}", log.borrow());
+        assert_eq!(piped.lines().count(), 3, "{}", piped);
+        assert_eq!(log.borrow().len(), 3);
+        assert!(too_slow.starts_with("Error: speed is too slow"), "{}", too_slow);
+
+        let missing = shell.execute(Command::Replay { path: "/nonexistent/trace.json".to_string(), speed: 1.0 });
+        assert!(missing.starts_with("Error: Cannot read"));
     }
 
     #[test]
-    fn test_shell_run_process() {
-        let mut shell = Shell::new();
+    fn test_history_save_and_load_resume_session() {
+        assert_eq!(parse_command("history").unwrap(), Command::History);
+        assert_eq!(
+            parse_command("history --save lab.txt").unwrap(),
+            Command::HistorySave { path: "lab.txt".to_string() }
+        );
+        assert!(parse_command("history --load").is_none());
+
+        let mut shell = Shell::with_seed(2014);
+        for line in ["fork", "fork 2", "schedule 5", "nice 3 0", "schedule 3"] {
+            shell.execute_line(line);
+        }
+
+        let path = std::env::temp_dir().join(format!("os-sim-history-{}.txt", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        assert_eq!(shell.execute_line(&format!("history --save {}", path)), format!("✓ Saved 5 commands to {}", path));
+        assert!(shell.execute_line("history").contains("   3  schedule 5"));
+
+        let mut resumed = Shell::with_seed(2014);
+        assert_eq!(
+            resumed.execute_line(&format!("history --load {}", path)),
+            format!("✓ Replayed 5 commands from {}", path)
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.history(), shell.history());
+        assert_eq!(resumed.clock(), shell.clock());
+        assert_eq!(resumed.execute(Command::Ps), shell.execute(Command::Ps));
+        assert!(resumed.execute_line("history --load /nonexistent/lab.txt").starts_with("Error: Cannot read"));
+    }
+
+    #[test]
+    fn test_trace_export_round_trips() {
+        assert_eq!(
+            parse_command("trace_export run.json").unwrap(),
+            Command::TraceExport { path: "run.json".to_string() }
+        );
+        assert!(parse_command("trace_export").is_none());
+
+        let mut shell = Shell::with_seed(3);
         shell.execute(Command::Fork { ppid: 1 });
-        let result = shell.execute(Command::Run { pid: 2 });
+        shell.execute(Command::Schedule { cycles: 4, no_boost: false });
 
-        assert!(result.contains("✓"));
+        let path = std::env::temp_dir().join(format!("os-sim-export-{}.json", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        assert_eq!(shell.execute(Command::TraceExport { path: path.clone() }), format!("✓ Wrote 4 events to {}", path));
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let events = json.as_array().unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0]["start_tick"], 0);
+        assert_eq!(events[1]["start_tick"], events[0]["duration"]);
+
+        let failed = shell.execute(Command::TraceExport { path: "/nonexistent/run.json".to_string() });
+        assert!(failed.starts_with("Error: Cannot write"));
     }
 
     #[test]
-    fn test_shell_block_unblock() {
-        let mut shell = Shell::new();
+    fn test_uptime_counts_processes() {
+        assert_eq!(parse_command("uptime").unwrap(), Command::Uptime);
+
+        let mut shell = Shell::with_seed(2);
+        shell.execute(Command::Fork { ppid: 1 });
         shell.execute(Command::Fork { ppid: 1 });
+        shell.execute(Command::Kill { pid: 3, force: true });
+        shell.execute(Command::Schedule { cycles: 4, no_boost: false });
 
-        let block_result = shell.execute(Command::Block { pid: 2 });
-        assert!(block_result.contains("✓"));
+        let uptime = shell.execute(Command::Uptime);
+        assert!(uptime.starts_with(&format!("up {}ms (4 cycles), wall 0:00:0", shell.clock())), "{}", uptime);
+        assert!(uptime.contains("3 created, 1 terminated, 2 active"), "{}", uptime);
+        assert!(uptime.contains(&format!("load average: {:.2}, idle=", shell.stats.load_average())));
+    }
 
-        let unblock_result = shell.execute(Command::Unblock { pid: 2 });
-        assert!(unblock_result.contains("✓"));
+    #[test]
+    fn test_uptime_cpu_split_sums_to_100() {
+        let mut shell = Shell::with_seed(2003);
+        shell.execute(Command::RunProgram { program_name: "terminal".to_string(), count: 1 });
+        shell.execute(Command::SwitchCost { ms: 2 });
+        shell.execute(Command::Schedule { cycles: 30, no_boost: false });
+        // Block everything so the CPU idles for a while
+        for pid in [1, 2] {
+            shell.execute(Command::Block { pid });
+        }
+        shell.execute(Command::Schedule { cycles: 5, no_boost: false });
+
+        let (useful, idle, overhead) = shell.stats.cpu_split();
+        assert!(idle > 0.0 && overhead > 0.0 && useful > 0.0);
+        assert!((useful + idle + overhead - 100.0).abs() < 1e-9);
+
+        let uptime = shell.execute(Command::Uptime);
+        assert!(uptime.ends_with(&format!("idle={:.1}% ovh={:.1}%", idle, overhead)), "{}", uptime);
+    }
+
+    #[test]
+    fn test_discarded_subshell_leaves_parent_untouched() {
+        let mut shell = Shell::with_seed(4);
+        shell.execute_line("fork");
+        assert!(shell.execute_line("subshell").starts_with("✓ Entered subshell (depth 1)"));
+        assert_eq!(shell.subshell_depth(), 1);
+
+        shell.execute_line("fork");
+        shell.execute_line("fork");
+        shell.execute_line("schedule 5");
+        assert_eq!(shell.process_count(), 4);
+
+        // A bare exit only reminds which mode to pick
+        assert!(shell.execute_line("exit").starts_with("Subshell (depth 1) still open"));
+        assert!(shell.is_running());
+
+        assert_eq!(shell.execute_line("exit discard"), "✓ Subshell discarded (depth 0)");
+        assert_eq!(shell.subshell_depth(), 0);
+        assert_eq!(shell.process_count(), 2);
+        assert_eq!(shell.clock(), 0);
+        assert_eq!(shell.history(), &["fork".to_string()]);
+        assert_eq!(shell.execute_line("exit discard"), "Error: Not in a subshell");
+    }
+
+    #[test]
+    fn test_merged_subshell_keeps_changes() {
+        let mut shell = Shell::with_seed(4);
+        shell.execute(Command::Subshell);
+        shell.execute(Command::Subshell);
+        shell.execute(Command::Fork { ppid: 1 });
+        assert_eq!(shell.subshell_depth(), 2);
+
+        assert_eq!(shell.execute(Command::ExitSubshell { merge: true }), "✓ Subshell changes merged (depth 1)");
+        assert_eq!(shell.process_count(), 2);
+        shell.execute(Command::ExitSubshell { merge: false });
+        assert_eq!(shell.process_count(), 1);
+        assert!(parse_command("exit later").is_none());
     }
 
     #[test]
@@ -0,0 +1,182 @@
+// src/shell/pipeline.rs
+// Filters that post-process a command's output after a `|`
+
+/// A text filter applied to captured command output
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Keep lines containing the pattern
+    Grep { pattern: String },
+    /// Keep the first `n` lines
+    Head { n: usize },
+    /// Keep the last `n` lines
+    Tail { n: usize },
+    /// Count lines, words, and characters
+    Wc,
+}
+
+/// Lines kept by `head`/`tail` when no count is given
+pub const DEFAULT_LINE_COUNT: usize = 10;
+
+/// Parse one pipeline stage such as `grep Blocked`
+pub fn parse_filter(input: &str) -> Option<Filter> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+
+    match *parts.first()? {
+        "grep" if parts.len() >= 2 => Some(Filter::Grep { pattern: parts[1..].join(" ") }),
+        "head" => line_count(parts.get(1)).map(|n| Filter::Head { n }),
+        "tail" => line_count(parts.get(1)).map(|n| Filter::Tail { n }),
+        "wc" if parts.len() == 1 => Some(Filter::Wc),
+        _ => None,
+    }
+}
+
+fn line_count(arg: Option<&&str>) -> Option<usize> {
+    match arg {
+        Some(n) => n.parse().ok(),
+        None => Some(DEFAULT_LINE_COUNT),
+    }
+}
+
+impl Filter {
+    /// Run the filter over `input`, one line at a time
+    pub fn apply(&self, input: &str) -> String {
+        match self {
+            Filter::Grep { pattern } => {
+                collect_lines(input.lines().filter(|line| line.contains(pattern.as_str())))
+            }
+            Filter::Head { n } => collect_lines(input.lines().take(*n)),
+            Filter::Tail { n } => {
+                let total = input.lines().count();
+                collect_lines(input.lines().skip(total.saturating_sub(*n)))
+            }
+            Filter::Wc => format!(
+                "{} {} {}\n",
+                input.lines().count(),
+                input.split_whitespace().count(),
+                input.chars().count()
+            ),
+        }
+    }
+}
+
+/// Where a line's final output should go instead of the terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Redirect {
+    /// `> path`: create or truncate the file
+    Truncate(String),
+    /// `>> path`: append to the file
+    Append(String),
+}
+
+impl Redirect {
+    /// Write `output` to the target file
+    pub fn write(&self, output: &str) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let (path, append) = match self {
+            Redirect::Truncate(path) => (path, false),
+            Redirect::Append(path) => (path, true),
+        };
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        file.write_all(output.as_bytes())
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Redirect::Truncate(path) | Redirect::Append(path) => path,
+        }
+    }
+}
+
+/// Split a trailing `> path` or `>> path` off a line
+pub fn split_redirect(line: &str) -> Result<(&str, Option<Redirect>), String> {
+    let Some(index) = line.find('>') else {
+        return Ok((line, None));
+    };
+
+    let (command, rest) = line.split_at(index);
+    let (append, target) = match rest.strip_prefix(">>") {
+        Some(target) => (true, target),
+        None => (false, &rest[1..]),
+    };
+
+    let path = target.trim();
+    if path.is_empty() || path.contains('>') || path.contains(char::is_whitespace) {
+        return Err(format!("Invalid redirection target '{}'", path));
+    }
+
+    let redirect = if append {
+        Redirect::Append(path.to_string())
+    } else {
+        Redirect::Truncate(path.to_string())
+    };
+    Ok((command, Some(redirect)))
+}
+
+fn collect_lines<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(parse_filter("grep Ready"), Some(Filter::Grep { pattern: "Ready".to_string() }));
+        assert_eq!(parse_filter("grep"), None);
+        assert_eq!(parse_filter("head 5"), Some(Filter::Head { n: 5 }));
+        assert_eq!(parse_filter("tail"), Some(Filter::Tail { n: DEFAULT_LINE_COUNT }));
+        assert_eq!(parse_filter("head x"), None);
+        assert_eq!(parse_filter("wc"), Some(Filter::Wc));
+        assert_eq!(parse_filter("sort"), None);
+    }
+
+    #[test]
+    fn test_grep_keeps_matching_lines() {
+        let filter = Filter::Grep { pattern: "Blocked".to_string() };
+        assert_eq!(filter.apply("1 Ready\n2 Blocked\n3 Blocked\n"), "2 Blocked\n3 Blocked\n");
+        assert_eq!(filter.apply("1 Ready\n"), "");
+    }
+
+    #[test]
+    fn test_head_and_tail_line_counts() {
+        let input = "a\nb\nc\nd\n";
+        assert_eq!(Filter::Head { n: 2 }.apply(input), "a\nb\n");
+        assert_eq!(Filter::Tail { n: 2 }.apply(input), "c\nd\n");
+        assert_eq!(Filter::Head { n: 10 }.apply(input).lines().count(), 4);
+        assert_eq!(Filter::Tail { n: 0 }.apply(input), "");
+    }
+
+    #[test]
+    fn test_wc_counts_lines_words_chars() {
+        assert_eq!(Filter::Wc.apply("one two\nthree\n"), "2 3 14\n");
+        assert_eq!(Filter::Wc.apply(""), "0 0 0\n");
+    }
+
+    #[test]
+    fn test_split_redirect() {
+        assert_eq!(split_redirect("ps"), Ok(("ps", None)));
+        assert_eq!(
+            split_redirect("stats > report.txt"),
+            Ok(("stats ", Some(Redirect::Truncate("report.txt".to_string()))))
+        );
+        assert_eq!(
+            split_redirect("ps | grep Ready >> log.txt"),
+            Ok(("ps | grep Ready ", Some(Redirect::Append("log.txt".to_string()))))
+        );
+        assert!(split_redirect("ps >").is_err());
+        assert!(split_redirect("ps > a > b").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+// src/shell/watch.rs
+// Terminal output for live views (`sched_stats --watch`, `replay`), swappable
+// so tests can capture frames
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Where live-view frames are drawn
+pub trait Screen {
+    /// Wipe the previous frame
+    fn clear(&mut self);
+    fn draw(&mut self, frame: &str);
+}
+
+/// Draws to stdout, clearing with ANSI escapes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalScreen;
+
+impl Screen for TerminalScreen {
+    fn clear(&mut self) {
+        print!("\x1B[2J\x1B[H");
+    }
+
+    fn draw(&mut self, frame: &str) {
+        println!("{}", frame);
+    }
+}
+
+/// Collects every frame into a shared buffer instead of drawing it, so a
+/// live view can be piped or redirected like any other output
+#[derive(Debug, Clone, Default)]
+pub struct CapturedScreen {
+    pub buffer: Rc<RefCell<String>>,
+}
+
+impl Screen for CapturedScreen {
+    fn clear(&mut self) {}
+
+    fn draw(&mut self, frame: &str) {
+        let mut buffer = self.buffer.borrow_mut();
+        buffer.push_str(frame);
+        buffer.push('\n');
+    }
+}